@@ -1,18 +1,21 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use env_logger::Env;
-use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
 
 use rayon::prelude::*;
 
-use goldfisher::deck::{Decklist};
-use goldfisher::game::{Game, GameResult, Outcome};
-use goldfisher::strategy::{DeckStrategy, Strategy};
+use goldfisher::card::Card;
+use goldfisher::deck::{Deck, Decklist, Format, ParseDeckError};
+use goldfisher::game::{GameConfig, GameResult, MulliganPolicy, Phase};
+use goldfisher::strategy::{DeckStrategy, NoMulligan, Strategy};
+use goldfisher::summary::{render_chart, render_comparison, Summary, TurnWindow};
 
 #[macro_use]
 extern crate log;
 
+const CHART_WIDTH: usize = 50;
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum ArgDeckStrategy {
     PatternCombo,
@@ -32,6 +35,23 @@ impl From<ArgDeckStrategy> for DeckStrategy {
     }
 }
 
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum ArgMulliganPolicy {
+    Aggressive,
+    Conservative,
+    KeepAll,
+}
+
+impl From<ArgMulliganPolicy> for MulliganPolicy {
+    fn from(other: ArgMulliganPolicy) -> MulliganPolicy {
+        match other {
+            ArgMulliganPolicy::Aggressive => MulliganPolicy::Aggressive,
+            ArgMulliganPolicy::Conservative => MulliganPolicy::Conservative,
+            ArgMulliganPolicy::KeepAll => MulliganPolicy::KeepAll,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
@@ -43,99 +63,372 @@ struct Args {
     #[clap(short, long, action)]
     verbose: bool,
 
-    /// The name of the deck strategy to use.
-    #[clap(short, long, value_enum)]
-    strategy: ArgDeckStrategy,
+    /// Print the game actions of only this turn, simulating a single game
+    #[clap(long, value_parser)]
+    verbose_turn: Option<usize>,
+
+    /// The name of the deck strategy to use, see --list-strategies. Repeat to compare multiple strategies.
+    #[clap(short, long, value_enum, required_unless_present_any = &["list-cards", "list-strategies"])]
+    strategy: Vec<ArgDeckStrategy>,
 
-    /// Path to custom decklist file
+    /// Path to custom decklist file. Repeat once per strategy to compare multiple decklists.
     #[clap(short, long)]
-    decklist: Option<String>,
+    decklist: Vec<String>,
+
+    /// Always keep the opening seven instead of letting the strategy mulligan
+    #[clap(long, action)]
+    no_mulligan: bool,
+
+    /// How eagerly to mulligan, instead of leaving it to the strategy
+    #[clap(long, value_enum)]
+    mulligan_policy: Option<ArgMulliganPolicy>,
+
+    /// Starting life total, instead of the default of the strategy
+    #[clap(long, value_parser)]
+    life_total: Option<i32>,
+
+    /// Size of the opponent's library, instead of the default of the strategy
+    #[clap(long, value_parser)]
+    opponent_library: Option<i32>,
+
+    /// Flip a coin for each game to decide whether to be on the play or on the draw
+    #[clap(long, action)]
+    random_play_draw: bool,
+
+    /// Count the wins after this turn as losses, listed as slow wins among the losses
+    #[clap(long, value_parser)]
+    success_turn_cap: Option<usize>,
+
+    /// Only count the wins on this turn or later as wins in the results
+    #[clap(long, value_parser)]
+    min_turn: Option<usize>,
+
+    /// Only count the wins on this turn or earlier as wins in the results
+    #[clap(long, value_parser)]
+    max_turn: Option<usize>,
+
+    /// Leave the games won outside of the --min-turn and --max-turn window out of the results,
+    /// instead of counting them as losses
+    #[clap(long, action)]
+    exclude_outside_turns: bool,
+
+    /// Print a bar chart of the win percentage per turn
+    #[clap(long, action)]
+    chart: bool,
+
+    /// Print the mana curve, colors and legality issues of the decklists without simulating any games
+    #[clap(long, action)]
+    analyze: bool,
+
+    /// Path to a decklist file to print the changes to from the decklist of the first strategy
+    #[clap(long)]
+    diff: Option<String>,
+
+    /// Print the names, types and mana values of the implemented cards and exit
+    #[clap(long, action)]
+    list_cards: bool,
+
+    /// Print the deck strategies with a description of each and exit
+    #[clap(long, action)]
+    list_strategies: bool,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let cli = Args::parse();
     init_logger(cli.verbose);
 
-    let mut win_statistics: HashMap<usize, usize> = HashMap::new();
-    let mut loss_statistics: HashMap<usize, usize> = HashMap::new();
-    let simulated_games = cli.games;
+    if cli.list_cards {
+        for line in render_card_list() {
+            info!("{line}");
+        }
+        return Ok(());
+    }
 
-    let decklist: Decklist = match cli.decklist {
-        Some(path) => fs::read_to_string(path)?.parse()?,
-        None => {
-            let strategy: Box<dyn Strategy> = goldfisher::strategy::from_enum(&cli.strategy.clone().into());
-            strategy.default_decklist()
+    if cli.list_strategies {
+        for line in render_strategy_list() {
+            info!("{line}");
         }
+        return Ok(());
+    }
+
+    if !cli.decklist.is_empty() && cli.decklist.len() != cli.strategy.len() {
+        return Err(format!(
+            "expected a decklist for each of the {} strategies, got {}",
+            cli.strategy.len(),
+            cli.decklist.len()
+        )
+        .into());
+    }
+
+    let window = TurnWindow {
+        min_turn: cli.min_turn,
+        max_turn: cli.max_turn,
+        exclude_outside: cli.exclude_outside_turns,
     };
 
-    let results: Vec<_> = (0..simulated_games)
+    let mut summaries = Vec::with_capacity(cli.strategy.len());
+
+    for (index, arg_strategy) in cli.strategy.iter().enumerate() {
+        let deck_strategy: DeckStrategy = arg_strategy.clone().into();
+        let strategy = new_strategy(&deck_strategy, cli.no_mulligan);
+
+        let (name, decklist): (String, Decklist) = match cli.decklist.get(index) {
+            Some(path) => (format!("{} ({path})", strategy.name()), fs::read_to_string(path)?.parse()?),
+            None => (strategy.name(), strategy.default_decklist()),
+        };
+
+        if let (0, Some(path)) = (index, cli.diff.as_ref()) {
+            let other: Decklist = fs::read_to_string(path)?.parse()?;
+            info!("========================[ DIFF ]============================");
+            info!("{name} -> {path}");
+            for line in render_diff(&decklist, &other) {
+                info!("{line}");
+            }
+        }
+
+        let defaults = strategy.default_config();
+        let config = GameConfig {
+            log_actions: cli.verbose,
+            life_total: cli.life_total.unwrap_or(defaults.life_total),
+            opponent_library: cli.opponent_library.unwrap_or(defaults.opponent_library),
+            mulligan_policy: cli.mulligan_policy.clone().map(MulliganPolicy::from).unwrap_or(defaults.mulligan_policy),
+            random_play_draw: cli.random_play_draw || defaults.random_play_draw,
+            success_turn_cap: cli.success_turn_cap.or(defaults.success_turn_cap),
+            ..defaults
+        };
+
+        if cli.analyze {
+            for line in analyze(&name, &decklist, deck_strategy.format())? {
+                info!("{line}");
+            }
+            continue;
+        }
+
+        let games = if cli.verbose_turn.is_some() { 1 } else { cli.games };
+        let results = simulate(&deck_strategy, &decklist, games, &config, cli.no_mulligan);
+
+        if let (Some(turn), Some(result)) = (cli.verbose_turn, results.first()) {
+            info!("========================[ TURN ]============================");
+            info!("{name} - turn {turn}");
+            for line in render_turn(result, turn) {
+                info!("{line}");
+            }
+        }
+
+        summaries.push(Summary::with_turn_window(&name, &results, &window));
+    }
+
+    if cli.analyze {
+        return Ok(());
+    }
+
+    if let [summary] = summaries.as_slice() {
+        print_summary(summary);
+    } else {
+        info!("=====================[ COMPARISON ]=========================");
+        for line in render_comparison(&summaries) {
+            info!("{line}");
+        }
+    }
+
+    if cli.chart {
+        for summary in summaries.iter() {
+            info!("=======================[ CHART ]============================");
+            info!("{name}", name = summary.name);
+            for line in render_chart(summary, CHART_WIDTH) {
+                info!("{line}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn simulate(
+    deck_strategy: &DeckStrategy,
+    decklist: &Decklist,
+    simulated_games: usize,
+    config: &GameConfig,
+    no_mulligan: bool,
+) -> Vec<GameResult> {
+    (0..simulated_games)
         .into_par_iter()
         .map(|_| {
-            let mut strategy: Box<dyn Strategy> =
-                goldfisher::strategy::from_enum(&cli.strategy.clone().into());
+            let mut strategy = new_strategy(deck_strategy, no_mulligan);
 
-            let mut game = match Game::new(&decklist) {
-                Ok(game) => game,
+            match goldfisher::simulate_one(&mut strategy, decklist, config) {
+                Ok(result) => result,
                 Err(err) => {
                     panic!("failed to initialize game: {err:?}");
                 }
-            };
-
-            game.run(&mut strategy)
+            }
         })
+        .collect()
+}
+
+fn render_turn(result: &GameResult, turn: usize) -> Vec<String> {
+    let lines: Vec<String> = result
+        .output
+        .iter()
+        .filter(|event| event.turn == turn && event.phase != Phase::Info)
+        .map(|event| event.to_string())
         .collect();
 
-    let mut mulligans = Vec::with_capacity(simulated_games);
+    if lines.is_empty() {
+        return vec![format!("The game ended on turn {} before turn {turn}", result.turn)];
+    }
 
-    for GameResult { result, turn, mulligan_count, output: _ } in results {
-        match result {
-            Outcome::Win => {
-                *win_statistics.entry(turn).or_insert(0) += 1;
-                mulligans.push(mulligan_count);
-            }
-            Outcome::Lose | Outcome::Draw => {
-                *loss_statistics.entry(turn).or_insert(0) += 1;
-                mulligans.push(mulligan_count);
-            }
-        }
+    lines
+}
+
+fn analyze(name: &str, decklist: &Decklist, format: Format) -> Result<Vec<String>, ParseDeckError> {
+    let deck = Deck::new(decklist)?;
+    let mana_curve = deck.mana_curve();
+    let nonlands = mana_curve.values().sum::<usize>();
+
+    let mut lines = vec![
+        "=====================[ ANALYSIS ]===========================".to_owned(),
+        format!("{name} - {format:?}"),
+        format!("Cards: {} ({} lands, {nonlands} nonlands)", deck.len(), deck.len() - nonlands),
+        format!("Sideboard: {} cards", deck.sideboard.len()),
+        "=====================[ MANA CURVE ]=========================".to_owned(),
+    ];
+
+    for (mana_value, count) in mana_curve.iter() {
+        lines.push(format!("Mana value {mana_value}: {count} cards"));
+    }
+
+    lines.push("=======================[ COLORS ]===========================".to_owned());
+    for (color, count) in deck.colors().iter() {
+        lines.push(format!("{color:?}: {count} cards"));
     }
 
-    let mut wins_by_turn = win_statistics.iter().collect::<Vec<_>>();
-    let mut losses_by_turn = loss_statistics.iter().collect::<Vec<_>>();
+    lines.push("=====================[ LEGALITY ]===========================".to_owned());
+    let issues = deck.check_format(format);
+    if issues.is_empty() {
+        lines.push(format!("Legal in {format:?}"));
+    }
+    for issue in issues {
+        lines.push(format!("Not legal in {format:?}: {issue}"));
+    }
 
-    wins_by_turn.sort();
-    losses_by_turn.sort();
+    Ok(lines)
+}
 
-    let total_wins: usize = wins_by_turn.iter().map(|(_, wins)| *wins).sum();
-    let average_turn = wins_by_turn
+fn render_strategy_list() -> Vec<String> {
+    ArgDeckStrategy::value_variants()
         .iter()
-        .map(|(turn, wins)| *turn * *wins)
-        .sum::<usize>() as f32
-        / total_wins as f32;
+        .map(|arg_strategy| {
+            let value = arg_strategy.to_possible_value().unwrap();
+            let strategy = goldfisher::strategy::from_enum(&arg_strategy.clone().into());
+
+            format!("{} - {}: {}", value.get_name(), strategy.name(), strategy.description())
+        })
+        .collect()
+}
+
+fn render_card_list() -> Vec<String> {
+    Card::all()
+        .iter()
+        .map(|card| {
+            let mut card_types = card.card_types.iter().map(|card_type| format!("{card_type:?}")).collect::<Vec<_>>();
+            card_types.sort();
+
+            format!(
+                "{name} - {card_types} - mana value {mana_value}",
+                name = card.name,
+                card_types = card_types.join(" "),
+                mana_value = card.mana_value()
+            )
+        })
+        .collect()
+}
+
+fn render_diff(decklist: &Decklist, other: &Decklist) -> Vec<String> {
+    let (added, removed) = decklist.diff(other);
+    if added.is_empty() && removed.is_empty() {
+        return vec![String::from("No changes")];
+    }
+
+    added
+        .iter()
+        .map(|(name, quantity)| format!("+{quantity} {name}"))
+        .chain(removed.iter().map(|(name, quantity)| format!("-{quantity} {name}")))
+        .collect()
+}
+
+fn new_strategy(deck_strategy: &DeckStrategy, no_mulligan: bool) -> Box<dyn Strategy> {
+    let strategy = goldfisher::strategy::from_enum(deck_strategy);
+
+    if no_mulligan {
+        Box::new(NoMulligan(strategy))
+    } else {
+        strategy
+    }
+}
 
-    let average_mulligans = mulligans.iter().sum::<usize>() as f32 / mulligans.len() as f32;
+fn print_summary(summary: &Summary) {
+    let simulated_games = summary.simulated_games;
+    let average_turn = summary.average_turn();
+    let average_mulligans = summary.average_mulligans();
+    let average_net_cards_drawn = summary.average_net_cards_drawn();
+    let fastest_win = summary.fastest_win().unwrap_or(0);
+    let [p10, p50, p90] = [10.0, 50.0, 90.0].map(|percentile| summary.win_turn_percentile(percentile).unwrap_or(0));
 
     info!("=======================[ RESULTS ]==========================");
     info!("                   Average turn: {average_turn:.2}");
     info!("                 Average mulligans: {average_mulligans:.2}");
+    info!("                 Average net draws: {average_net_cards_drawn:.2}");
+    info!("                   Fastest win: turn {fastest_win}");
+    info!("       Win turn p10 / p50 / p90: {p10} / {p50} / {p90}");
     info!("              Wins per turn after {simulated_games} games:");
     info!("============================================================");
 
     let mut cumulative = 0.0;
-    for (turn, wins) in wins_by_turn {
+    for (turn, wins) in summary.wins_by_turn.iter() {
         let win_percentage = 100.0 * *wins as f32 / simulated_games as f32;
         cumulative += win_percentage;
         info!("Turn {turn:002}: {wins} wins ({win_percentage:.1}%) - cumulative {cumulative:.1}%");
     }
 
     let mut loss_cumulative = 0.0;
-    for (turn, losses) in losses_by_turn {
+    for (turn, losses) in summary.losses_by_turn.iter() {
         let loss_percentage = 100.0 * *losses as f32 / simulated_games as f32;
         loss_cumulative += loss_percentage;
         info!("Turn {turn:002}: {losses} losses ({loss_percentage:.1}%) - cumulative {loss_cumulative:.1}%");
     }
 
-    Ok(())
+    let mut draw_cumulative = 0.0;
+    for (turn, draws) in summary.draws_by_turn.iter() {
+        let draw_percentage = 100.0 * *draws as f32 / simulated_games as f32;
+        draw_cumulative += draw_percentage;
+        info!("Turn {turn:002}: {draws} draws ({draw_percentage:.1}%) - cumulative {draw_cumulative:.1}%");
+    }
+
+    info!("======================[ LOSSES ]============================");
+    for (loss_reason, games) in summary.loss_reasons.iter() {
+        let loss_percentage = 100.0 * *games as f32 / simulated_games as f32;
+        info!("Lost to {loss_reason}: {games} games ({loss_percentage:.1}%)");
+    }
+
+    info!("=====================[ MULLIGANS ]==========================");
+    for (mulligans, games) in summary.mulligan_distribution.iter() {
+        let mulligan_percentage = 100.0 * *games as f32 / simulated_games as f32;
+        info!("Kept at {mulligans} mulligans: {games} games ({mulligan_percentage:.1}%)");
+    }
+
+    info!("===================[ LIBRARY SIZE ]=========================");
+    for turn in summary.games_by_turn.keys() {
+        if let Some(library_size) = summary.average_library_size(*turn) {
+            info!("Turn {turn:002}: {library_size:.1} cards on average");
+        }
+    }
+
+    info!("=====================[ MAX STORM ]==========================");
+    for (storm, games) in summary.storm_histogram.iter() {
+        let storm_percentage = 100.0 * *games as f32 / simulated_games as f32;
+        info!("Storm {storm:002}: {games} games ({storm_percentage:.1}%)");
+    }
 }
 
 fn init_logger(verbose: bool) {
@@ -150,3 +443,67 @@ fn init_logger(verbose: bool) {
     .format_module_path(false)
     .init();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_analyzes_decklist_without_simulating() {
+        let strategy = goldfisher::strategy::from_enum(&DeckStrategy::Aluren);
+        let lines = analyze("Aluren", &strategy.default_decklist(), Format::Premodern).unwrap();
+
+        for expected in [
+            "Cards: 60 (22 lands, 38 nonlands)",
+            "Mana value 1: 11 cards",
+            "Mana value 2: 15 cards",
+            "Mana value 3: 8 cards",
+            "Mana value 4: 4 cards",
+            "Green: 15 cards",
+            "Legal in Premodern",
+        ] {
+            assert!(lines.iter().any(|line| line == expected), "missing line: {expected}");
+        }
+    }
+
+    #[test]
+    fn it_lists_the_strategies_with_their_descriptions() {
+        let lines = render_strategy_list();
+
+        assert_eq!(goldfisher::strategy::STRATEGIES.len(), lines.len());
+        assert!(lines[0].starts_with("pattern-combo - Premodern - Pattern Combo: "), "{lines:#?}");
+    }
+
+    #[test]
+    fn it_lists_the_implemented_cards() {
+        let lines = render_card_list();
+
+        assert!(!lines.is_empty());
+        assert!(lines.iter().any(|line| line.starts_with("Aluren - ")), "{lines:#?}");
+    }
+
+    #[test]
+    fn it_renders_decklist_diff() {
+        let decklist = "4 Llanowar Elves\n2 Forest\n".parse::<Decklist>().unwrap();
+        let other = "3 Llanowar Elves\n2 Forest\n1 Birds of Paradise\n".parse::<Decklist>().unwrap();
+
+        assert_eq!(vec!["+1 Birds of Paradise", "-1 Llanowar Elves"], render_diff(&decklist, &other));
+        assert_eq!(vec!["No changes"], render_diff(&decklist, &decklist));
+    }
+
+    #[test]
+    fn it_renders_only_the_actions_of_the_turn() {
+        let strategy = goldfisher::strategy::from_enum(&DeckStrategy::Aluren);
+        let config = GameConfig {
+            seed: Some(7),
+            ..strategy.default_config()
+        };
+
+        let results = simulate(&DeckStrategy::Aluren, &strategy.default_decklist(), 1, &config, true);
+        let lines = render_turn(&results[0], 3);
+
+        assert!(results[0].turn >= 3);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.starts_with("[Turn 03]")), "{lines:#?}");
+    }
+}
@@ -1,32 +1,42 @@
 use gloo_worker::{Spawnable, WorkerBridge};
-use log::debug;
+use log::{debug, warn};
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{EventTarget, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
 use yew::prelude::*;
 
-use goldfisher::deck::Deck;
-use goldfisher::game::{GameResult, Outcome};
+use goldfisher::deck::{Deck, LegalityIssue};
+use goldfisher::game::LogEvent;
+use goldfisher::summary::Summary;
 use goldfisher::strategy::{DeckStrategy, STRATEGIES};
 
-use goldfisher_web::{Cmd, Goldfish, Status};
+use goldfisher_web::{Cmd, Goldfish, RunId, Status, SummaryDelta};
 
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+const DEFAULT_SIMULATIONS: usize = 10000;
+
+const STORAGE_KEY_STRATEGY: &str = "goldfisher.strategy";
+const STORAGE_KEY_DECKLIST: &str = "goldfisher.decklist";
+const STORAGE_KEY_SIMULATIONS: &str = "goldfisher.simulations";
+const STORAGE_KEY_SUCCESS_TURN_CAP: &str = "goldfisher.success_turn_cap";
+
 #[derive(Debug)]
 pub enum Msg {
     ChangeStrategy(String),
     ChangeSimulationsCount(usize),
+    ChangeSuccessTurnCap(Option<usize>),
     ChangeDecklist(String),
     ChangeSampleGame(Option<usize>),
     BeginSimulation,
     CancelSimulation,
-    UpdateProgress(usize, usize, Vec<GameResult>),
-    FinishSimulation(usize, usize, Vec<GameResult>),
-    SimulationError(String),
+    UpdateProgress(RunId, usize, usize, SummaryDelta),
+    FinishSimulation(RunId, usize, usize, SummaryDelta),
+    SimulationError(RunId, String),
     DismissError,
+    CopyResults,
 }
 
 impl fmt::Display for Msg {
@@ -34,32 +44,105 @@ impl fmt::Display for Msg {
         match self {
             Msg::ChangeStrategy(name) => write!(f, "ChangeStrategy(\"{name:?}\")"),
             Msg::ChangeSimulationsCount(count) => write!(f, "ChangeSimulationsCount({count})"),
+            Msg::ChangeSuccessTurnCap(turn) => write!(f, "ChangeSuccessTurnCap({turn:?})"),
             Msg::ChangeDecklist(_decklist) => write!(f, "ChangeDecklist"),
             Msg::ChangeSampleGame(turn) => write!(f, "ChangeSampleGame({turn:?})"),
             Msg::BeginSimulation => write!(f, "BeginSimulation"),
             Msg::CancelSimulation => write!(f, "CancelSimulation"),
-            Msg::UpdateProgress(current, total, _results) => {
-                write!(f, "UpdateProgress({current}, {total})")
+            Msg::UpdateProgress(run_id, current, total, _delta) => {
+                write!(f, "UpdateProgress({run_id}, {current}, {total})")
+            }
+            Msg::FinishSimulation(run_id, current, total, _delta) => {
+                write!(f, "FinishSimulation({run_id}, {current}, {total})")
             }
-            Msg::FinishSimulation(current, total, _results) => {
-                write!(f, "FinishSimulation({current}, {total})")
+            Msg::SimulationError(run_id, message) => {
+                write!(f, "SimulationError({run_id}, {message:?})")
             }
-            Msg::SimulationError(message) => write!(f, "SimulationError({message:?})"),
             Msg::DismissError => write!(f, "DismissError"),
+            Msg::CopyResults => write!(f, "CopyResults"),
         }
     }
 }
 
 #[derive(Debug, Default)]
 struct Results {
+    summary: Summary,
     wins: BTreeMap<usize, usize>,
     losses: usize,
+    draws: usize,
     average_turn: f32,
-    mulligans: Vec<usize>,
     average_mulligans: f32,
+    mulligan_distribution: BTreeMap<usize, usize>,
+    average_final_life: f32,
+    average_damage_dealt: f32,
     percentage_wins: BTreeMap<usize, f32>,
     cumulative_wins: BTreeMap<usize, f32>,
-    sample_games: HashMap<usize, Vec<String>>
+    sample_games: HashMap<usize, Vec<LogEvent>>
+}
+
+/// Settings persisted to `localStorage` between page loads
+#[derive(Debug, PartialEq)]
+struct StoredSettings {
+    strategy: Option<DeckStrategy>,
+    decklist: String,
+    simulations: usize,
+    success_turn_cap: Option<usize>,
+}
+
+impl StoredSettings {
+    /// Storage entries for the settings, a missing strategy or turn cap is stored as an empty string
+    fn to_entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                STORAGE_KEY_STRATEGY,
+                self.strategy
+                    .as_ref()
+                    .map(|strategy| strategy.to_string())
+                    .unwrap_or_default(),
+            ),
+            (STORAGE_KEY_DECKLIST, self.decklist.clone()),
+            (STORAGE_KEY_SIMULATIONS, self.simulations.to_string()),
+            (
+                STORAGE_KEY_SUCCESS_TURN_CAP,
+                self.success_turn_cap
+                    .map(|turn| turn.to_string())
+                    .unwrap_or_default(),
+            ),
+        ]
+    }
+
+    /// Restores the settings with `get`, falling back to defaults on missing or invalid entries
+    fn from_entries(get: impl Fn(&str) -> Option<String>) -> Self {
+        Self {
+            strategy: get(STORAGE_KEY_STRATEGY).and_then(|strategy| strategy.parse().ok()),
+            decklist: get(STORAGE_KEY_DECKLIST).unwrap_or_default(),
+            simulations: get(STORAGE_KEY_SIMULATIONS)
+                .and_then(|simulations| simulations.parse().ok())
+                .unwrap_or(DEFAULT_SIMULATIONS),
+            success_turn_cap: get(STORAGE_KEY_SUCCESS_TURN_CAP).and_then(|turn| turn.parse().ok()),
+        }
+    }
+
+    fn load() -> Self {
+        match local_storage() {
+            Some(storage) => Self::from_entries(|key| storage.get_item(key).ok().flatten()),
+            None => Self::from_entries(|_| None),
+        }
+    }
+
+    fn save(&self) {
+        if let Some(storage) = local_storage() {
+            for (key, value) in self.to_entries() {
+                if let Err(err) = storage.set_item(key, &value) {
+                    warn!("Failed to save {key}: {err:?}");
+                }
+            }
+        }
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window().and_then(|window| window.local_storage().ok().flatten())
 }
 
 pub struct App {
@@ -67,50 +150,69 @@ pub struct App {
     decklist: String,
     is_busy: bool,
     is_decklist_error: bool,
+    legality_issues: Vec<LegalityIssue>,
     error_msg: Option<String>,
     simulations: usize,
+    success_turn_cap: Option<usize>,
     progress: (usize, usize),
     sample_game: Option<usize>,
     results: Results,
+    run_id: RunId,
     worker: WorkerBridge<Goldfish>,
 }
 
-impl App {
-    fn update_results(&mut self, new_results: Vec<GameResult>) {
-        for GameResult { result, turn, mulligan_count, output } in new_results.into_iter() {
-            match result {
-                Outcome::Win => {
-                    *self.results.wins.entry(turn).or_insert(0) += 1;
-                    self.results.sample_games.entry(turn).or_insert(output);
-                }
-                Outcome::Lose | Outcome::Draw => {
-                    self.results.losses += 1;
-                }
-            }
-            self.results.mulligans.push(mulligan_count);
-        }
+impl Results {
+    fn update(&mut self, delta: SummaryDelta, progress: usize) {
+        self.summary.merge(&delta.summary);
+        self.sample_games.extend(delta.sample_games);
 
-        let total_wins: usize = self.results.wins.iter().map(|(_, wins)| *wins).sum();
+        let summary = &self.summary;
+        let total_wins = summary.total_wins();
 
-        self.results.average_turn = self
-            .results
+        self.wins = summary.wins_by_turn.clone();
+        self.losses = summary.total_losses();
+        self.draws = summary.total_draws();
+        self.mulligan_distribution = summary.mulligan_distribution.clone();
+
+        self.average_turn = self
             .wins
             .iter()
             .map(|(turn, wins)| *turn * *wins)
             .sum::<usize>() as f32
             / usize::max(total_wins, 1) as f32;
 
-        self.results.average_mulligans = self.results.mulligans.iter().sum::<usize>() as f32
-            / usize::max(self.results.mulligans.len(), 1) as f32;
+        self.average_mulligans =
+            summary.total_mulligans as f32 / usize::max(summary.simulated_games, 1) as f32;
+
+        self.average_final_life =
+            summary.total_final_life_on_wins as f32 / usize::max(total_wins, 1) as f32;
+
+        self.average_damage_dealt =
+            summary.total_damage_dealt as f32 / usize::max(summary.simulated_games, 1) as f32;
 
-        let progress: usize = self.progress.0;
         let mut cumulative = 0.0;
-        for (turn, wins) in self.results.wins.iter() {
+        for (turn, wins) in self.wins.iter() {
             let win_percentage = 100.0 * *wins as f32 / progress as f32;
             cumulative += win_percentage;
-            *self.results.percentage_wins.entry(*turn).or_insert(0.0) = win_percentage;
-            *self.results.cumulative_wins.entry(*turn).or_insert(0.0) = cumulative;
+            *self.percentage_wins.entry(*turn).or_insert(0.0) = win_percentage;
+            *self.cumulative_wins.entry(*turn).or_insert(0.0) = cumulative;
+        }
+    }
+}
+
+impl App {
+    fn update_results(&mut self, delta: SummaryDelta) {
+        self.results.update(delta, self.progress.0);
+    }
+
+    fn save_settings(&self) {
+        StoredSettings {
+            strategy: self.strategy.clone(),
+            decklist: self.decklist.clone(),
+            simulations: self.simulations,
+            success_turn_cap: self.success_turn_cap,
         }
+        .save();
     }
 }
 
@@ -124,29 +226,47 @@ impl Component for App {
         let worker = Goldfish::spawner()
             .callback(move |results| {
                 match results {
-                    Status::InProgress(current, total, results) => {
-                        link.send_message(Msg::UpdateProgress(current, total, results))
+                    Status::Progress(run_id, current, total, delta) => {
+                        link.send_message(Msg::UpdateProgress(run_id, current, total, delta))
+                    }
+                    Status::Cancelled(run_id, current, total, delta) => {
+                        link.send_message(Msg::FinishSimulation(run_id, current, total, delta))
                     }
-                    Status::Cancelled(current, total) => {
-                        link.send_message(Msg::FinishSimulation(current, total, Vec::new()))
+                    Status::Complete(run_id, total, delta) => {
+                        link.send_message(Msg::FinishSimulation(run_id, total, total, delta))
                     }
-                    Status::Complete(total, results) => {
-                        link.send_message(Msg::FinishSimulation(total, total, results))
+                    Status::Error(run_id, message) => {
+                        link.send_message(Msg::SimulationError(run_id, message))
                     }
-                    Status::Error(message) => link.send_message(Msg::SimulationError(message)),
                 };
             })
             .spawn("/worker.js");
 
+        let settings = StoredSettings::load();
+
+        let (is_decklist_error, legality_issues) = match settings.decklist.parse::<Deck>() {
+            Ok(deck) => (
+                false,
+                match settings.strategy.as_ref() {
+                    Some(strategy) => deck.check_format(strategy.format()),
+                    None => Vec::new(),
+                },
+            ),
+            Err(_) => (!settings.decklist.is_empty(), Vec::new()),
+        };
+
         Self {
-            strategy: None,
+            strategy: settings.strategy,
             sample_game: None,
-            decklist: String::new(),
+            decklist: settings.decklist,
             is_busy: false,
-            is_decklist_error: false,
-            simulations: 10000,
+            is_decklist_error,
+            legality_issues,
+            simulations: settings.simulations,
+            success_turn_cap: settings.success_turn_cap,
             progress: (0, 0),
             results: Results::default(),
+            run_id: 0,
             error_msg: None,
             worker,
         }
@@ -163,27 +283,48 @@ impl Component for App {
             Msg::ChangeStrategy(deck_strategy) => match deck_strategy.parse::<DeckStrategy>() {
                 Err(_) => {
                     self.strategy = None;
+                    self.legality_issues = Vec::new();
+                    self.save_settings();
                 }
                 Ok(strategy) => {
                     self.decklist = goldfisher::strategy::from_enum(&strategy)
                         .default_decklist()
                         .to_string();
+                    self.legality_issues = match self.decklist.parse::<Deck>() {
+                        Ok(deck) => deck.check_format(strategy.format()),
+                        Err(_) => Vec::new(),
+                    };
                     self.strategy = Some(strategy);
+                    self.save_settings();
                 }
             },
             Msg::ChangeSimulationsCount(count) => {
                 self.simulations = count;
+                self.save_settings();
+            }
+            Msg::ChangeSuccessTurnCap(turn) => {
+                self.success_turn_cap = turn;
+                self.save_settings();
             }
             Msg::ChangeDecklist(decklist_str) => {
-                if let Err(err) = decklist_str.parse::<Deck>() {
-                    self.is_decklist_error = true;
-                    self.error_msg = Some(err.to_string());
-                } else {
-                    self.is_decklist_error = false;
-                    self.error_msg = None;
+                match decklist_str.parse::<Deck>() {
+                    Err(err) => {
+                        self.is_decklist_error = true;
+                        self.error_msg = Some(err.to_string());
+                        self.legality_issues = Vec::new();
+                    }
+                    Ok(deck) => {
+                        self.is_decklist_error = false;
+                        self.error_msg = None;
+                        self.legality_issues = match self.strategy.as_ref() {
+                            Some(strategy) => deck.check_format(strategy.format()),
+                            None => Vec::new(),
+                        };
+                    }
                 }
 
                 self.decklist = decklist_str;
+                self.save_settings();
             }
             Msg::ChangeSampleGame(turn) => {
                 self.sample_game = turn;
@@ -194,31 +335,51 @@ impl Component for App {
                     self.error_msg = None;
                     self.sample_game = None;
                     self.results = Results::default();
+                    self.run_id += 1;
 
                     self.worker.send(Cmd::Begin {
+                        run_id: self.run_id,
                         strategy: self.strategy.as_ref().unwrap().clone(),
                         decklist: self.decklist.clone(),
                         simulations: self.simulations,
+                        success_turn_cap: self.success_turn_cap,
                     });
                 }
             }
             Msg::CancelSimulation => {
-                self.worker.send(Cmd::Cancel);
+                self.worker.send(Cmd::Cancel {
+                    run_id: self.run_id,
+                });
+            }
+            Msg::UpdateProgress(run_id, _, _, _)
+            | Msg::FinishSimulation(run_id, _, _, _)
+            | Msg::SimulationError(run_id, _)
+                if run_id != self.run_id =>
+            {
+                debug!("Ignoring a status of a previous run {run_id}");
+                return false;
             }
-            Msg::UpdateProgress(progress, total_simulations, results) => {
+            Msg::UpdateProgress(_, progress, total_simulations, delta) => {
                 self.progress = (progress, total_simulations);
-                self.update_results(results);
+                self.update_results(delta);
             }
-            Msg::FinishSimulation(progress, total_simulations, results) => {
+            Msg::FinishSimulation(_, progress, total_simulations, delta) => {
                 self.progress = (progress, total_simulations);
                 self.is_busy = false;
-                self.update_results(results);
+                self.update_results(delta);
             }
-            Msg::SimulationError(message) => {
+            Msg::SimulationError(_, message) => {
                 self.is_busy = false;
                 self.error_msg = Some(message);
             }
             Msg::DismissError => self.error_msg = None,
+            Msg::CopyResults => {
+                let markdown = format_markdown(&self.results, self.progress.0);
+                if let Err(err) = copy_to_clipboard(&markdown) {
+                    warn!("Failed to copy results: {err:?}");
+                    self.error_msg = Some(String::from("Failed to copy results to clipboard."));
+                }
+            }
         }
 
         true
@@ -258,6 +419,7 @@ impl Component for App {
                                                         html! {
                                                             <option
                                                                 selected={self.strategy.as_ref().map(|current| current == strategy).unwrap_or(false)}
+                                                                title={goldfisher::strategy::from_enum(strategy).description()}
                                                                 value={strategy.to_string()}>
                                                                 {strategy.to_string()}
                                                             </option> }
@@ -285,6 +447,13 @@ impl Component for App {
                                                 })
                                             })}
                                         />
+                                        {
+                                            self.legality_issues.iter().map(|issue| {
+                                                html! {
+                                                    <p class="help is-warning">{format!("Warning: {issue}")}</p>
+                                                }
+                                            }).collect::<Html>()
+                                        }
                                     </div>
 
                                     <div class="field">
@@ -295,11 +464,24 @@ impl Component for App {
                                                 let select = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok());
                                                 select.map(|select| {
                                                     let count = select.value();
-                                                    Msg::ChangeSimulationsCount(count.parse().unwrap_or(10000))
+                                                    Msg::ChangeSimulationsCount(count.parse().unwrap_or(DEFAULT_SIMULATIONS))
                                                 })
                                             })}
                                         />
                                     </div>
+
+                                    <div class="field">
+                                        <label class="label" for="success-turn-cap">{"Count wins up to turn:"}</label>
+                                        <input class="input is-info" type="number" id="success-turn-cap" step="1" min="1" placeholder="Any turn"
+                                            value={self.success_turn_cap.map(|turn| turn.to_string()).unwrap_or_default()}
+                                            onchange={link.batch_callback(move |e: Event| {
+                                                let target: Option<EventTarget> = e.target();
+                                                let select = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok());
+                                                select.map(|select| Msg::ChangeSuccessTurnCap(select.value().parse().ok()))
+                                            })}
+                                        />
+                                        <p class="help">{"The later wins are counted as losses"}</p>
+                                    </div>
                                 </div>
 
                                 <div class="buttons">
@@ -312,6 +494,10 @@ impl Component for App {
                                     <button class="button" type="button" disabled={!self.is_busy} onclick={link.callback(|_| Msg::CancelSimulation)}>
                                         { "Cancel" }
                                     </button>
+
+                                    <button class="button" type="button" disabled={self.is_busy || progress == 0} onclick={link.callback(|_| Msg::CopyResults)}>
+                                        { "Copy as markdown" }
+                                    </button>
                                 </div>
                             </div>
 
@@ -350,6 +536,14 @@ impl Component for App {
                                             <label class="label">{"Average turn:"}</label>
                                             <span class="is-small">{format!("{:.2}", self.results.average_turn)}</span>
                                         </div>
+                                        <div class="column">
+                                            <label class="label">{"Average life on wins:"}</label>
+                                            <span class="is-small">{format!("{:.2}", self.results.average_final_life)}</span>
+                                        </div>
+                                        <div class="column">
+                                            <label class="label">{"Average damage dealt:"}</label>
+                                            <span class="is-small">{format!("{:.2}", self.results.average_damage_dealt)}</span>
+                                        </div>
                                         <div class="column">
                                             <label class="label">{"Bricked games:"}</label>
                                             <span class="is-small">{
@@ -358,9 +552,25 @@ impl Component for App {
                                                     100.0 * self.results.losses as f32 / usize::max(progress, 1) as f32)
                                             }</span>
                                         </div>
+                                        <div class="column">
+                                            <label class="label">{"Drawn games:"}</label>
+                                            <span class="is-small">{
+                                                format!("{} ({:.1}%)",
+                                                    self.results.draws,
+                                                    100.0 * self.results.draws as f32 / usize::max(progress, 1) as f32)
+                                            }</span>
+                                        </div>
                                         <div class="column">
                                             <label class="label">{"Average mulligans:"}</label>
                                             <span class="is-small">{format!("{:.2}", self.results.average_mulligans)}</span>
+                                            {
+                                                self.results.mulligan_distribution.iter().map(|(mulligans, games)| {
+                                                    let percentage = 100.0 * *games as f32 / usize::max(progress, 1) as f32;
+                                                    html! {
+                                                        <p class="is-size-7">{format!("Kept at {mulligans}: {games} ({percentage:.1}%)")}</p>
+                                                    }
+                                                }).collect::<Html>()
+                                            }
                                         </div>
                                     </div>
                                 </div>
@@ -377,7 +587,7 @@ impl Component for App {
                                                 </tr>
                                             </thead>
                                             <tbody>
-                                                {if self.results.wins.is_empty() && self.results.losses == 0 {
+                                                {if self.results.wins.is_empty() && self.results.losses == 0 && self.results.draws == 0 {
                                                     html! {
                                                         <tr>
                                                             <th>{"--"}</th>
@@ -446,8 +656,9 @@ impl Component for App {
                                             {
                                                 match self.results.sample_games.get(&turn) {
                                                     Some(sample_game) => {
-                                                        let lines = sample_game.iter().map(|log_line| {
-                                                            let wrapped = wrap_string(log_line, 80).join("\n");
+                                                        let lines = sample_game.iter().map(|log_event| {
+                                                            let log_line = log_event.to_string();
+                                                            let wrapped = wrap_string(&log_line, 80).join("\n");
                                                             wrapped
                                                         }).collect::<Vec<_>>();
     
@@ -474,6 +685,51 @@ impl Component for App {
     }
 }
 
+/// Formats the results as a markdown summary followed by a table of wins per turn
+fn format_markdown(results: &Results, progress: usize) -> String {
+    let mut lines = vec![
+        format!("**Games:** {progress}"),
+        format!("**Average turn:** {:.2}", results.average_turn),
+        format!(
+            "**Bricked games:** {} ({:.1}%)",
+            results.losses,
+            100.0 * results.losses as f32 / usize::max(progress, 1) as f32
+        ),
+        format!(
+            "**Drawn games:** {} ({:.1}%)",
+            results.draws,
+            100.0 * results.draws as f32 / usize::max(progress, 1) as f32
+        ),
+        format!("**Average mulligans:** {:.2}", results.average_mulligans),
+        format!("**Average life on wins:** {:.2}", results.average_final_life),
+        format!("**Average damage dealt:** {:.2}", results.average_damage_dealt),
+        String::new(),
+        String::from("| Turn | Wins | Cumulative (%) | Wins (%) |"),
+        String::from("| ---: | ---: | ---: | ---: |"),
+    ];
+
+    for (turn, wins) in results.wins.iter() {
+        let win_percentage = results.percentage_wins.get(turn).unwrap_or(&0.0);
+        let cumulative = results.cumulative_wins.get(turn).unwrap_or(&0.0);
+        lines.push(format!(
+            "| {turn} | {wins} | {cumulative:.1}% | {win_percentage:.1}% |"
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Writes the text to the clipboard with `navigator.clipboard.writeText`
+fn copy_to_clipboard(text: &str) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window"))?;
+    let clipboard = js_sys::Reflect::get(&window.navigator(), &JsValue::from_str("clipboard"))?;
+    let write_text: js_sys::Function =
+        js_sys::Reflect::get(&clipboard, &JsValue::from_str("writeText"))?.dyn_into()?;
+    write_text.call1(&clipboard, &JsValue::from_str(text))?;
+
+    Ok(())
+}
+
 fn wrap_string(s: &str, max_len: usize) -> Vec<&str> {
     let mut lines = vec![];
     let mut remaining = s;
@@ -490,3 +746,105 @@ fn main() {
     wasm_logger::init(wasm_logger::Config::new(log::Level::Info));
     yew::start_app::<App>();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goldfisher::game::{game_result, GameResult, Outcome};
+    use std::collections::BTreeSet;
+
+    fn delta(results: Vec<GameResult>) -> SummaryDelta {
+        SummaryDelta::new(results, &mut BTreeSet::new())
+    }
+
+    #[test]
+    fn it_averages_final_life_on_wins_and_damage_dealt() {
+        let mut results = Results::default();
+
+        results.update(
+            delta(vec![
+                GameResult { final_life: 20, damage_dealt: 20, ..game_result(Outcome::Win, 3) },
+                GameResult { final_life: 14, damage_dealt: 40, ..game_result(Outcome::Win, 4) },
+                GameResult { final_life: 2, damage_dealt: 0, ..game_result(Outcome::Lose, 10) },
+                GameResult { final_life: 0, damage_dealt: 20, ..game_result(Outcome::Draw, 12) },
+            ]),
+            4,
+        );
+
+        assert_eq!(34, results.summary.total_final_life_on_wins);
+        assert_eq!(17.0, results.average_final_life);
+        assert_eq!(20.0, results.average_damage_dealt);
+        assert_eq!(1, results.losses);
+        assert_eq!(1, results.draws);
+        assert_eq!(3.5, results.average_turn);
+    }
+
+    #[test]
+    fn it_restores_stored_settings() {
+        let settings = StoredSettings {
+            strategy: Some(DeckStrategy::Aluren),
+            decklist: String::from("4 Aluren\n"),
+            simulations: 500,
+            success_turn_cap: Some(5),
+        };
+
+        let entries: HashMap<&str, String> = settings.to_entries().into_iter().collect();
+        let restored = StoredSettings::from_entries(|key| entries.get(key).cloned());
+
+        assert_eq!(settings, restored);
+    }
+
+    #[test]
+    fn it_falls_back_to_defaults_on_invalid_stored_settings() {
+        let entries: HashMap<&str, String> = HashMap::from([
+            (STORAGE_KEY_STRATEGY, String::from("Not a strategy")),
+            (STORAGE_KEY_SIMULATIONS, String::from("many")),
+        ]);
+
+        let restored = StoredSettings::from_entries(|key| entries.get(key).cloned());
+
+        assert_eq!(
+            StoredSettings {
+                strategy: None,
+                decklist: String::new(),
+                simulations: DEFAULT_SIMULATIONS,
+                success_turn_cap: None,
+            },
+            restored
+        );
+    }
+
+    #[test]
+    fn it_formats_results_as_markdown() {
+        let mut results = Results::default();
+
+        results.update(
+            delta(vec![
+                GameResult { final_life: 20, damage_dealt: 20, ..game_result(Outcome::Win, 3) },
+                GameResult { final_life: 14, damage_dealt: 40, ..game_result(Outcome::Win, 4) },
+                GameResult { final_life: 18, damage_dealt: 20, ..game_result(Outcome::Win, 4) },
+                GameResult { final_life: 2, damage_dealt: 0, ..game_result(Outcome::Lose, 10) },
+            ]),
+            4,
+        );
+
+        assert_eq!(
+            vec![
+                "**Games:** 4",
+                "**Average turn:** 3.67",
+                "**Bricked games:** 1 (25.0%)",
+                "**Drawn games:** 0 (0.0%)",
+                "**Average mulligans:** 0.00",
+                "**Average life on wins:** 17.33",
+                "**Average damage dealt:** 20.00",
+                "",
+                "| Turn | Wins | Cumulative (%) | Wins (%) |",
+                "| ---: | ---: | ---: | ---: |",
+                "| 3 | 1 | 25.0% | 25.0% |",
+                "| 4 | 2 | 75.0% | 50.0% |",
+            ]
+            .join("\n"),
+            format_markdown(&results, 4)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use gloo_worker::{HandlerId, Worker, WorkerScope};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::error::Error;
 use std::sync::{Mutex, Arc};
 
@@ -9,24 +10,60 @@ use js_sys::Promise;
 use web_sys::WorkerGlobalScope;
 
 use goldfisher::deck::Decklist;
-use goldfisher::game::{Game, GameResult};
+use goldfisher::game::{GameConfig, GameResult, LogEvent, Outcome};
 use goldfisher::strategy::{DeckStrategy, Strategy};
+use goldfisher::summary::Summary;
 
 const MAX_BATCH_SIZE: usize = 25;
 
+/// Identifies a simulation run, echoed back in every `Status` of the run
+pub type RunId = usize;
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum Cmd {
-    Begin{ strategy: DeckStrategy, decklist: String, simulations: usize },
-    Cancel,
+    Begin{ run_id: RunId, strategy: DeckStrategy, decklist: String, simulations: usize, success_turn_cap: Option<usize> },
+    Cancel{ run_id: RunId },
 }
 
 #[derive(Debug, PartialEq)]
-enum State {
-    Idle,
+enum RunState {
     Running,
     Cancelling,
 }
 
+/// States of the runs in progress, several runs can be simulated side by side with their batches interleaved
+type Runs = HashMap<RunId, RunState>;
+
+/// A run accepted by the worker, simulated in batches until it completes or gets cancelled
+#[derive(Debug)]
+struct Run {
+    run_id: RunId,
+    deck_strategy: DeckStrategy,
+    decklist_str: String,
+    total_simulations: usize,
+    success_turn_cap: Option<usize>,
+    progress: usize,
+    sampled_turns: BTreeSet<usize>,
+}
+
+impl Run {
+    /// Simulates the next batch of games. A run cancelled meanwhile still reports the finished batch.
+    fn next_batch(&mut self, runs: &Mutex<Runs>, decklist: &Decklist) -> Status {
+        let batch_size = MAX_BATCH_SIZE.min(self.total_simulations - self.progress);
+        self.progress += batch_size;
+
+        match Goldfish::run_batch(&self.deck_strategy, decklist, batch_size, self.success_turn_cap) {
+            Ok(results) => {
+                let is_cancelling = runs.lock().unwrap().get(&self.run_id) == Some(&RunState::Cancelling);
+                let delta = SummaryDelta::new(results, &mut self.sampled_turns);
+
+                Goldfish::batch_status(self.run_id, is_cancelling, self.progress, self.total_simulations, delta)
+            }
+            Err(err) => Status::Error(self.run_id, format!("failed to simulate games: {err:?}")),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Msg {
     Command { cmd: Cmd, id: HandlerId },
@@ -34,10 +71,48 @@ pub enum Msg {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Status {
-    InProgress(usize, usize, Vec<GameResult>),
-    Cancelled(usize, usize),
-    Complete(usize, Vec<GameResult>),
-    Error(String),
+    Progress(RunId, usize, usize, SummaryDelta),
+    Cancelled(RunId, usize, usize, SummaryDelta),
+    Complete(RunId, usize, SummaryDelta),
+    Error(RunId, String),
+}
+
+/// Results of a batch aggregated into a summary, to be merged into the summary of the whole run
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SummaryDelta {
+    pub summary: Summary,
+    /// Log of a game won on each turn no earlier batch of the run had a sample game for
+    pub sample_games: BTreeMap<usize, Vec<LogEvent>>,
+}
+
+impl SummaryDelta {
+    /// Aggregates the results, only sampling the turns missing from `sampled_turns`
+    /// and adding the newly sampled turns to it
+    pub fn new(results: Vec<GameResult>, sampled_turns: &mut BTreeSet<usize>) -> Self {
+        let mut delta = SummaryDelta::default();
+
+        for result in results.into_iter() {
+            delta.summary.add(&result);
+
+            if result.result == Outcome::Win && sampled_turns.insert(result.turn) {
+                delta.sample_games.insert(result.turn, result.output);
+            }
+        }
+
+        delta
+    }
+}
+
+impl Status {
+    /// Id of the run this status belongs to
+    pub fn run_id(&self) -> RunId {
+        match self {
+            Status::Progress(run_id, ..)
+            | Status::Cancelled(run_id, ..)
+            | Status::Complete(run_id, ..)
+            | Status::Error(run_id, _) => *run_id,
+        }
+    }
 }
 
 /// Yields execution from worker by creating a `setTimeout(0)` with `WorkerGlobalScope`
@@ -56,101 +131,106 @@ pub async fn yield_now() {
 }
 
 pub struct Goldfish {
-    state: Arc<Mutex<State>>,
+    runs: Arc<Mutex<Runs>>,
 }
 
 impl Goldfish {
-    async fn run(
-        state: Arc<Mutex<State>>,
-        scope: WorkerScope<Self>,
-        id: HandlerId,
-        deck_strategy: DeckStrategy,
-        decklist_str: String,
-        total_simulations: usize,
-    ) {
-        {
-            let mut state = state.lock().unwrap();
-            if *state != State::Idle {
-                return;
+    /// Handles a command, returning the run to start. A run begun with the id of a run still
+    /// in progress is rejected with an error status, and cancelling a run that isn't running does nothing.
+    fn handle_command(&self, cmd: Cmd) -> Result<Option<Run>, Box<Status>> {
+        let mut runs = self.runs.lock().unwrap();
+
+        match cmd {
+            Cmd::Begin{ run_id, strategy, decklist, simulations, success_turn_cap } => {
+                if runs.contains_key(&run_id) {
+                    let message = format!("run {run_id} is already in progress");
+                    return Err(Box::new(Status::Error(run_id, message)));
+                }
+
+                runs.insert(run_id, RunState::Running);
+
+                Ok(Some(Run {
+                    run_id,
+                    deck_strategy: strategy,
+                    decklist_str: decklist,
+                    total_simulations: simulations,
+                    success_turn_cap,
+                    progress: 0,
+                    sampled_turns: BTreeSet::new(),
+                }))
             }
+            Cmd::Cancel{ run_id } => {
+                if let Some(run_state) = runs.get_mut(&run_id) {
+                    *run_state = RunState::Cancelling;
+                }
 
-            *state = State::Running;
+                Ok(None)
+            }
         }
+    }
 
-        let decklist = match decklist_str.parse::<Decklist>() {
+    async fn run(runs: Arc<Mutex<Runs>>, scope: WorkerScope<Self>, id: HandlerId, mut run: Run) {
+        let decklist = match run.decklist_str.parse::<Decklist>() {
             Ok(decklist) => decklist,
             Err(err) => {
+                runs.lock().unwrap().remove(&run.run_id);
                 scope.respond(
                     id,
-                    Status::Error(format!("failed to parse decklist: {err:?}")),
+                    Status::Error(run.run_id, format!("failed to parse decklist: {err:?}")),
                 );
                 return;
             }
         };
 
-        let mut progress = 0;
         scope.respond(
             id,
-            Status::InProgress(progress, total_simulations, Vec::new()),
+            Status::Progress(run.run_id, run.progress, run.total_simulations, SummaryDelta::default()),
         );
 
-        loop {
-            if progress >= total_simulations {
-                break;
-            }
-
-            if State::Cancelling == *state.lock().unwrap() {
-                scope.respond(
-                    id,
-                    Status::Cancelled(progress, total_simulations),
-                );
-                break;
-            }
-
+        while run.progress < run.total_simulations {
             yield_now().await;
 
-            let batch_size = if progress + MAX_BATCH_SIZE > total_simulations {
-                total_simulations - progress
-            } else {
-                MAX_BATCH_SIZE
-            };
-
-            progress += batch_size;
+            let status = run.next_batch(&runs, &decklist);
+            let is_cancelled = matches!(status, Status::Cancelled(..));
+            scope.respond(id, status);
 
-            match Goldfish::run_batch(&deck_strategy, &decklist, batch_size) {
-                Ok(results) => {
-                    if progress == total_simulations {
-                        scope.respond(id, Status::Complete(total_simulations, results));
-                    } else {
-                        scope.respond(id, Status::InProgress(progress, total_simulations, results));
-                    }
-                }
-                Err(err) => {
-                    scope.respond(
-                        id,
-                        Status::Error(format!("failed to simulate games: {err:?}")),
-                    );
-                }
+            if is_cancelled {
+                break;
             }
         }
 
-        *state.lock().unwrap() = State::Idle;
+        runs.lock().unwrap().remove(&run.run_id);
+    }
+
+    /// Status to respond with after a batch, a cancelled run still reports the finished batch
+    fn batch_status(
+        run_id: RunId,
+        is_cancelling: bool,
+        progress: usize,
+        total_simulations: usize,
+        delta: SummaryDelta,
+    ) -> Status {
+        if is_cancelling {
+            Status::Cancelled(run_id, progress, total_simulations, delta)
+        } else if progress == total_simulations {
+            Status::Complete(run_id, total_simulations, delta)
+        } else {
+            Status::Progress(run_id, progress, total_simulations, delta)
+        }
     }
 
     fn run_batch(
         deck_strategy: &DeckStrategy,
         decklist: &Decklist,
         batch_size: usize,
+        success_turn_cap: Option<usize>,
     ) -> Result<Vec<GameResult>, Box<dyn Error>> {
-        let mut results = Vec::new();
-
-        for _ in 0..batch_size {
-            let mut strategy: Box<dyn Strategy> = goldfisher::strategy::from_enum(deck_strategy);
-
-            let mut game = Game::new(&decklist)?;
-            let result = game.run(&mut strategy);
-            results.push(result);
-        }
+        let mut strategy: Box<dyn Strategy> = goldfisher::strategy::from_enum(deck_strategy);
+        let config = GameConfig {
+            success_turn_cap,
+            ..strategy.default_config()
+        };
+        let results = goldfisher::simulate_many(&mut strategy, decklist, &config, batch_size, |_| {})?;
 
         Ok(results)
     }
@@ -165,32 +245,23 @@ impl Worker for Goldfish {
 
     fn create(_scope: &WorkerScope<Self>) -> Self {
         Self {
-            state: Arc::new(Mutex::new(State::Idle)),
+            runs: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
     fn update(&mut self, scope: &WorkerScope<Self>, msg: Self::Message) {
         match msg {
             Msg::Command { cmd, id } => {
-                match cmd {
-                    Cmd::Begin{ strategy, decklist, simulations } => {
-                        let (state, scope) = (Arc::clone(&self.state), scope.clone());
+                match self.handle_command(cmd) {
+                    Ok(Some(run)) => {
+                        let (runs, scope) = (Arc::clone(&self.runs), scope.clone());
 
                         spawn_local(async move {
-                            Goldfish::run(
-                                state,
-                                scope,
-                                id,
-                                strategy,
-                                decklist,
-                                simulations,
-                            ).await;
+                            Goldfish::run(runs, scope, id, run).await;
                         });
                     }
-                    Cmd::Cancel => {
-                        let mut state = self.state.lock().unwrap();
-                        *state = State::Cancelling;
-                    }
+                    Ok(None) => {}
+                    Err(status) => scope.respond(id, *status),
                 }
             }
         }
@@ -200,3 +271,159 @@ impl Worker for Goldfish {
         scope.send_message(Msg::Command { cmd: msg, id })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_keeps_the_finished_batch_on_cancel() {
+        let worker = Goldfish {
+            runs: Arc::new(Mutex::new(HashMap::new())),
+        };
+        let deck_strategy = DeckStrategy::Aluren;
+        let decklist = goldfisher::strategy::from_enum(&deck_strategy).default_decklist();
+
+        let mut run = worker
+            .handle_command(Cmd::Begin {
+                run_id: 1,
+                strategy: deck_strategy,
+                decklist: decklist.to_string(),
+                simulations: 30,
+                success_turn_cap: None,
+            })
+            .unwrap()
+            .unwrap();
+
+        // Cancelled while the first batch is being simulated
+        worker.handle_command(Cmd::Cancel { run_id: 1 }).unwrap();
+
+        match run.next_batch(&worker.runs, &decklist) {
+            Status::Cancelled(1, progress, total, delta) => {
+                assert_eq!(MAX_BATCH_SIZE, progress);
+                assert_eq!(30, total);
+                assert_eq!(MAX_BATCH_SIZE, delta.summary.simulated_games);
+            }
+            status => panic!("expected cancelled status, got {status:?}"),
+        }
+    }
+
+    #[test]
+    fn it_completes_on_the_last_batch() {
+        assert!(matches!(
+            Goldfish::batch_status(1, false, 10, 10, SummaryDelta::default()),
+            Status::Complete(_, 10, _)
+        ));
+        assert!(matches!(
+            Goldfish::batch_status(1, false, 5, 10, SummaryDelta::default()),
+            Status::Progress(_, 5, 10, _)
+        ));
+    }
+
+    fn begin(run_id: RunId) -> Cmd {
+        Cmd::Begin {
+            run_id,
+            strategy: DeckStrategy::Aluren,
+            decklist: String::new(),
+            simulations: 10,
+            success_turn_cap: None,
+        }
+    }
+
+    #[test]
+    fn it_rejects_a_run_begun_with_the_id_of_a_run_in_progress() {
+        let worker = Goldfish {
+            runs: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        match worker.handle_command(begin(1)) {
+            Ok(Some(run)) => assert_eq!(1, run.run_id),
+            result => panic!("expected the run to start, got {result:?}"),
+        }
+
+        match worker.handle_command(begin(1)) {
+            Err(status) if matches!(*status, Status::Error(..)) => assert_eq!(1, status.run_id()),
+            result => panic!("expected an error status, got {result:?}"),
+        }
+
+        assert_eq!(Some(&RunState::Running), worker.runs.lock().unwrap().get(&1));
+    }
+
+    #[test]
+    fn it_only_cancels_the_run_with_the_given_id() {
+        let worker = Goldfish {
+            runs: Arc::new(Mutex::new(HashMap::new())),
+        };
+        worker.handle_command(begin(1)).unwrap();
+        worker.handle_command(begin(2)).unwrap();
+
+        assert!(worker.handle_command(Cmd::Cancel { run_id: 3 }).unwrap().is_none());
+        assert!(worker.handle_command(Cmd::Cancel { run_id: 2 }).unwrap().is_none());
+
+        let runs = worker.runs.lock().unwrap();
+        assert_eq!(Some(&RunState::Running), runs.get(&1));
+        assert_eq!(Some(&RunState::Cancelling), runs.get(&2));
+        assert!(!runs.contains_key(&3));
+    }
+
+    #[test]
+    fn it_responds_to_concurrent_runs_with_their_own_ids() {
+        let worker = Goldfish {
+            runs: Arc::new(Mutex::new(HashMap::new())),
+        };
+        let deck_strategy = DeckStrategy::Aluren;
+        let decklist = goldfisher::strategy::from_enum(&deck_strategy).default_decklist();
+
+        let begin_run = |run_id| {
+            worker
+                .handle_command(Cmd::Begin {
+                    run_id,
+                    strategy: deck_strategy.clone(),
+                    decklist: decklist.to_string(),
+                    simulations: 2 * MAX_BATCH_SIZE,
+                    success_turn_cap: None,
+                })
+                .unwrap()
+                .unwrap()
+        };
+        let mut first = begin_run(1);
+        let mut second = begin_run(2);
+
+        // Batches of the runs are interleaved
+        assert!(matches!(first.next_batch(&worker.runs, &decklist), Status::Progress(1, ..)));
+        assert!(matches!(second.next_batch(&worker.runs, &decklist), Status::Progress(2, ..)));
+
+        worker.handle_command(Cmd::Cancel { run_id: 1 }).unwrap();
+
+        assert!(matches!(second.next_batch(&worker.runs, &decklist), Status::Complete(2, ..)));
+        assert!(matches!(first.next_batch(&worker.runs, &decklist), Status::Cancelled(1, ..)));
+    }
+
+    #[test]
+    fn it_sums_deltas_into_the_full_summary() {
+        let deck_strategy = DeckStrategy::Aluren;
+        let decklist = goldfisher::strategy::from_enum(&deck_strategy).default_decklist();
+
+        let results = Goldfish::run_batch(&deck_strategy, &decklist, 20, None).unwrap();
+        let full_summary = Summary::new("", &results);
+
+        let mut sampled_turns = BTreeSet::new();
+        let mut summary = Summary::default();
+        let mut sample_games = BTreeMap::new();
+        for batch in results.chunks(6) {
+            let delta = SummaryDelta::new(batch.to_vec(), &mut sampled_turns);
+            for turn in delta.sample_games.keys() {
+                assert!(!sample_games.contains_key(turn), "turn {turn} sampled twice");
+            }
+
+            summary.merge(&delta.summary);
+            sample_games.extend(delta.sample_games);
+        }
+
+        assert_eq!(full_summary, summary);
+        assert_eq!(
+            full_summary.wins_by_turn.keys().collect::<Vec<_>>(),
+            sample_games.keys().collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,13 +1,47 @@
 use std::cell::RefCell;
 use std::collections::{HashSet, HashMap};
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
+use log::warn;
+
 use crate::effect::Effect;
-use crate::mana::{CostReduction, Mana};
+use crate::mana::{CostReduction, DynamicMana, Mana};
 
 pub type CardRef = Rc<RefCell<Card>>;
 
+/// Whether both references point to the same card, rather than to two copies of a card
+pub fn same_card(a: &CardRef, b: &CardRef) -> bool {
+    Rc::ptr_eq(a, b)
+}
+
+/// Card reference that is equal only to the references of the same card, for comparing cards
+/// with `==` and keeping them in sets
+#[derive(Clone, Debug)]
+pub struct CardIdentity(pub CardRef);
+
+impl PartialEq for CardIdentity {
+    fn eq(&self, other: &Self) -> bool {
+        same_card(&self.0, &other.0)
+    }
+}
+
+impl Eq for CardIdentity {}
+
+impl Hash for CardIdentity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rc::as_ptr(&self.0).hash(state);
+    }
+}
+
+impl From<CardRef> for CardIdentity {
+    fn from(card: CardRef) -> Self {
+        Self(card)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum CardType {
     Creature,
     Enchantment,
@@ -24,12 +58,14 @@ impl Default for CardType {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum SubType {
     Creature(CreatureType),
     Land(LandType)
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum Zone {
     Library,
     Hand,
@@ -55,12 +91,14 @@ impl Default for Zone {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum CreatureType {
     Harpy,
     Beast,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum LandType {
     Plains,
     Island,
@@ -70,6 +108,7 @@ pub enum LandType {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum SearchFilter {
     Creature,
     Wish(Vec<CardType>),
@@ -80,1061 +119,180 @@ pub enum SearchFilter {
     Land(Vec<LandType>),
 }
 
-#[derive(Clone, Debug, Default)]
+/// Common misspellings and alternative spellings of the card names, mapped to the canonical names
+const CARD_NAME_ALIASES: [(&str, &str); 3] = [
+    ("Llawnowar Wastes", "Llanowar Wastes"),
+    ("Lim-Dul's Vault", "Lim-Dûl's Vault"),
+    ("Birds of Paradice", "Birds of Paradise"),
+];
+
+/// Resolves the card name to its canonical name, ignoring the case, whitespace and punctuation,
+/// falling back to the known aliases
+pub fn resolve_alias(name: &str) -> Option<String> {
+    let normalized = normalize_card_name(name);
+
+    let canonical = CARD_DATABASE.with(|cards| {
+        cards
+            .keys()
+            .find(|card_name| normalize_card_name(card_name) == normalized)
+            .cloned()
+    });
+
+    canonical.or_else(|| {
+        CARD_NAME_ALIASES
+            .iter()
+            .find(|(alias, _)| normalize_card_name(alias) == normalized)
+            .map(|(_, canonical)| canonical.to_string())
+    })
+}
+
+fn normalize_card_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "snapshot", serde(default))]
 pub struct Card {
     pub name: String,
     pub card_types: HashSet<CardType>,
     pub sub_types: HashSet<SubType>,
     pub zone: Zone,
-    pub cost: HashMap<Mana, i32>,
+    pub cost: HashMap<Mana, u32>,
+    /// Color of the card when it has no mana cost to define it, like Dryad Arbor
+    pub color_indicator: Option<Mana>,
+    /// Has X in its mana cost, spending all of the remaining mana on X when cast
+    pub x_cost: bool,
+    /// The value of X the spell was cast with
+    pub x: u32,
     pub produced_mana: HashMap<Mana, u32>,
-    pub remaining_uses: Option<usize>,
+    /// Overrides the produced mana with an amount evaluated against the current game state
+    pub produced_mana_dynamic: Option<DynamicMana>,
+    /// Times the card can still be used for mana before it is sacrificed, `None` for no limit
+    pub charges: Option<u32>,
+    /// Used up the first time it is used for mana
+    pub is_one_shot: bool,
+    /// Whether the card is tapped to produce mana, or can keep producing it until it runs out of uses
+    pub taps_for_mana: bool,
+    /// Damage dealt to the controller whenever the card is used for mana
+    pub pain: i32,
+    /// Exiled from hand for mana instead of being used from the battlefield
+    pub is_pitch_for_mana: bool,
     pub is_sac_outlet: bool,
     pub is_summoning_sick: bool,
     pub is_tapped: bool,
     pub is_haste: bool,
+    pub is_echo: bool,
+    /// Not backed by a decklist entry, ceases to exist once it leaves the battlefield
+    pub is_token: bool,
+    pub power: i32,
+    pub toughness: i32,
+    /// Always connects when attacking, even against an opponent that blocks
+    pub is_unblockable: bool,
+    /// Keeps the opponent from countering spells while on the battlefield
+    pub prevents_counters: bool,
+    /// Additional mana the opponent has to pay to counter spells while this is on the battlefield
+    pub counter_tax: u32,
     pub on_resolve: Option<Effect>,
+    /// Resolved when the card is put into the graveyard from the battlefield
+    pub on_dies: Option<Effect>,
+    #[cfg_attr(feature = "snapshot", serde(skip))]
     pub attached_to: Option<CardRef>,
     pub cost_reduction: Option<CostReduction>,
 }
 
+impl Default for Card {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            card_types: HashSet::new(),
+            sub_types: HashSet::new(),
+            zone: Zone::default(),
+            cost: HashMap::new(),
+            color_indicator: None,
+            x_cost: false,
+            x: 0,
+            produced_mana: HashMap::new(),
+            produced_mana_dynamic: None,
+            charges: None,
+            is_one_shot: false,
+            taps_for_mana: true,
+            pain: 0,
+            is_pitch_for_mana: false,
+            is_sac_outlet: false,
+            is_summoning_sick: false,
+            is_tapped: false,
+            is_haste: false,
+            is_echo: false,
+            is_token: false,
+            power: 0,
+            toughness: 0,
+            is_unblockable: false,
+            prevents_counters: false,
+            counter_tax: 0,
+            on_resolve: None,
+            on_dies: None,
+            attached_to: None,
+            cost_reduction: None,
+        }
+    }
+}
+
 impl Card {
     pub fn new(card_name: &str) -> Result<Card, String> {
-        let name = card_name.to_owned();
+        let card = CARD_DATABASE.with(|cards| cards.get(card_name).cloned());
 
-        let card = match name.as_str() {
-            "Llanowar Elves" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                produced_mana: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Veteran Explorer" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Xantid Swarm" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Sylvan Safekeeper" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Fyndhorn Elves" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                produced_mana: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Birds of Paradise" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                produced_mana: HashMap::from([
-                    (Mana::White, 1),
-                    (Mana::Blue, 1),
-                    (Mana::Black, 1),
-                    (Mana::Red, 1),
-                    (Mana::Green, 1),
-                ]),
-                ..Default::default()
-            },
-            "Noble Hierarch" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                produced_mana: HashMap::from([(Mana::White, 1), (Mana::Blue, 1), (Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Carrion Feeder" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 1)]),
-                is_sac_outlet: true,
-                ..Default::default()
-            },
-            "Viscera Seer" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 1)]),
-                is_sac_outlet: true,
-                ..Default::default()
-            },
-            "Nantuko Husk" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 2)]),
-                is_sac_outlet: true,
-                ..Default::default()
-            },
-            "Phyrexian Ghoul" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 2)]),
-                is_sac_outlet: true,
-                ..Default::default()
-            },
-            "Pattern of Rebirth" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 3)]),
-                ..Default::default()
-            },
-            "Academy Rector" => Card {
-                name: card_name.to_owned(),
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 3)]),
-                ..Default::default()
-            },
-            "Mesmeric Fiend" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Iridescent Drake" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 3)]),
-                ..Default::default()
-            },
-            "Karmic Guide" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::White, 2), (Mana::Colorless, 3)]),
-                ..Default::default()
-            },
-            "Volrath's Shapeshifter" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Blue, 2), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Caller of the Claw" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Body Snatcher" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 2), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Akroma, Angel of Wrath" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::White, 3), (Mana::Colorless, 5)]),
-                ..Default::default()
-            },
-            "Phantom Nishoba" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Green, 1), (Mana::Colorless, 5)]),
-                ..Default::default()
-            },
-            "Worship" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 3)]),
-                ..Default::default()
-            },
-            "Pernicious Deed" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Black, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Recurring Nightmare" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Seal of Cleansing" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "City of Solitude" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Engineered Plague" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Circle of Protection: Red" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Warmth" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Goblin Bombardment" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::Red, 1), (Mana::Colorless, 1)]),
-                is_sac_outlet: true,
-                ..Default::default()
-            },
-            "Altar of Dementia" => Card {
-                name,
-                card_types: HashSet::from([CardType::Artifact]),
-                cost: HashMap::from([(Mana::Colorless, 2)]),
-                is_sac_outlet: true,
-                ..Default::default()
-            },
-            "Cabal Therapy" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Black, 1)]),
-                ..Default::default()
-            },
-            "Duress" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Black, 1)]),
-                ..Default::default()
-            },
-            "Swords to Plowshares" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::White, 1)]),
-                ..Default::default()
-            },
-            "Worldly Tutor" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                on_resolve: Some(Effect::SearchAndPutTopOfLibrary(Some(
-                    SearchFilter::Creature,
-                ))),
-                ..Default::default()
-            },
-            "Enlightened Tutor" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::White, 1)]),
-                on_resolve: Some(Effect::SearchAndPutTopOfLibrary(Some(
-                    SearchFilter::EnchantmentArtifact,
-                ))),
-                ..Default::default()
-            },
-            "Eladamri's Call" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Green, 1)]),
-                on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::Creature))),
-                ..Default::default()
-            },
-            "Vindicate" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Black, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Rofellos, Llanowar Emissary" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                // TODO: Actual produced mana
-                produced_mana: HashMap::from([(Mana::Green, 1)]),
-                cost: HashMap::from([(Mana::Green, 2)]),
-                ..Default::default()
-            },
-            "Wall of Roots" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                produced_mana: HashMap::from([(Mana::Green, 1)]),
-                remaining_uses: Some(5),
-                is_haste: true,
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Elvish Spirit Guide" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                produced_mana: HashMap::from([(Mana::Green, 1)]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 2)]),
-                remaining_uses: Some(1),
-                ..Default::default()
-            },
-            "Lotus Petal" => Card {
-                name,
-                card_types: HashSet::from([CardType::Artifact]),
-                produced_mana: HashMap::from([
-                    (Mana::White, 1),
-                    (Mana::Blue, 1),
-                    (Mana::Black, 1),
-                    (Mana::Red, 1),
-                    (Mana::Green, 1),
-                ]),
-                cost: HashMap::new(),
-                remaining_uses: Some(1),
-                ..Default::default()
-            },
-            "Soul Warden" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::White, 1)]),
-                ..Default::default()
-            },
-            "Unearth" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Black, 1)]),
-                on_resolve: Some(Effect::Unearth),
-                ..Default::default()
-            },
-            "Cavern Harpy" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                sub_types: HashSet::from([SubType::Creature(CreatureType::Harpy), SubType::Creature(CreatureType::Beast)]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Black, 1)]),
-                on_resolve: Some(Effect::CavernHarpy),
-                ..Default::default()
-            },
-            "Cloud of Faeries" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 1)]),
-                on_resolve: Some(Effect::UntapLands(Some(2))),
-                ..Default::default()
-            },
-            "Impulse" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 1)]),
-                on_resolve: Some(Effect::Impulse(4)),
-                ..Default::default()
-            },
-            "Living Wish" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 1)]),
-                on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::Wish(vec![
-                    CardType::Creature,
-                    CardType::Land,
-                ])))),
-                ..Default::default()
-            },
-            "Cunning Wish" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 2)]),
-                on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::Wish(vec![
-                    CardType::Instant,
-                ])))),
-                ..Default::default()
-            },
-            "Ray of Revelation" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Intuition" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 2)]),
-                on_resolve: Some(Effect::Intuition),
-                ..Default::default()
-            },
-            "Raven Familiar" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 2)]),
-                on_resolve: Some(Effect::Impulse(3)), // TODO: Separate effect
-                ..Default::default()
-            },
-            "Wirewood Savage" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Aluren" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 2), (Mana::Colorless, 2)]),
-                cost_reduction: Some(CostReduction::Aluren),
-                ..Default::default()
-            },
-            "Maggot Carrier" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 2)]),
-                on_resolve: Some(Effect::DamageEach(1)),
-                ..Default::default()
-            },
-            "Auramancer" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Monk Realist" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Plague Spitter" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Ravenous Baloth" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                sub_types: HashSet::from([SubType::Creature(CreatureType::Beast)]),
-                cost: HashMap::from([(Mana::Green, 2), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Naturalize" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Crippling Fatigue" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Black, 2), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Uktabi Orangutan" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Bone Shredder" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Hydroblast" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1)]),
-                ..Default::default()
-            },
-            "Blue Elemental Blast" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1)]),
-                ..Default::default()
-            },
-            "Mana Short" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Words of Wisdom" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 1)]),
-                on_resolve: Some(Effect::WordsOfWisdom),
-                ..Default::default()
-            },
-            "Snap" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 1)]),
-                on_resolve: Some(Effect::Snap),
-                ..Default::default()
-            },
-            "Brain Freeze" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 1)]),
-                on_resolve: Some(Effect::BrainFreeze),
-                ..Default::default()
-            },
-            "Frantic Search" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 2)]),
-                on_resolve: Some(Effect::FranticSearch),
-                ..Default::default()
-            },
-            "Meditate" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 2)]),
-                on_resolve: Some(Effect::Meditate),
-                ..Default::default()
-            },
-            "Merchant Scroll" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 1)]),
-                on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::BlueInstant))),
-                ..Default::default()
-            },
-            "Sleight of Hand" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Blue, 1)]),
-                on_resolve: Some(Effect::Impulse(2)),
-                ..Default::default()
-            },
-            "Helm of Awakening" => Card {
-                name,
-                card_types: HashSet::from([CardType::Artifact]),
-                cost: HashMap::from([(Mana::Colorless, 2)]),
-                cost_reduction: Some(CostReduction::All(Mana::Colorless, 1)),
-                ..Default::default()
-            },
-            "Sapphire Medallion" => Card {
-                name,
-                card_types: HashSet::from([CardType::Artifact]),
-                cost: HashMap::from([(Mana::Colorless, 2)]),
-                cost_reduction: Some(CostReduction::Color(Mana::Blue, (Mana::Colorless, 1))),
-                ..Default::default()
-            },
-            "Chain of Vapor" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1)]),
-                ..Default::default()
-            },
-            "Defense Grid" => Card {
-                name,
-                card_types: HashSet::from([CardType::Artifact]),
-                cost: HashMap::from([(Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Tormod's Crypt" => Card {
-                name,
-                card_types: HashSet::from([CardType::Artifact]),
-                cost: HashMap::new(),
-                ..Default::default()
-            },
-            "Hurkyl's Recall" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Turnabout" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 2), (Mana::Colorless, 2)]),
-                on_resolve: Some(Effect::UntapLands(None)),
-                ..Default::default()
-            },
-            "City of Brass" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                produced_mana: HashMap::from([
-                    (Mana::White, 1),
-                    (Mana::Blue, 1),
-                    (Mana::Black, 1),
-                    (Mana::Red, 1),
-                    (Mana::Green, 1),
-                ]),
-                ..Default::default()
-            },
-            "Llanowar Wastes" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                produced_mana: HashMap::from([
-                    (Mana::Black, 1),
-                    (Mana::Green, 1),
-                    (Mana::Colorless, 1),
-                ]),
-                ..Default::default()
-            },
-            "Brushland" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                produced_mana: HashMap::from([
-                    (Mana::White, 1),
-                    (Mana::Green, 1),
-                    (Mana::Colorless, 1),
-                ]),
-                ..Default::default()
-            },
-            "Yavimaya Coast" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                produced_mana: HashMap::from([
-                    (Mana::Blue, 1),
-                    (Mana::Green, 1),
-                    (Mana::Colorless, 1),
-                ]),
-                ..Default::default()
-            },
-            "Caves of Koilos" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                produced_mana: HashMap::from([
-                    (Mana::White, 1),
-                    (Mana::Black, 1),
-                    (Mana::Colorless, 1),
-                ]),
-                ..Default::default()
-            },
-            "Underground River" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                produced_mana: HashMap::from([
-                    (Mana::Blue, 1),
-                    (Mana::Black, 1),
-                    (Mana::Colorless, 1),
-                ]),
-                ..Default::default()
-            },
-            "Gemstone Mine" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                remaining_uses: Some(3),
-                produced_mana: HashMap::from([
-                    (Mana::White, 1),
-                    (Mana::Blue, 1),
-                    (Mana::Black, 1),
-                    (Mana::Red, 1),
-                    (Mana::Green, 1),
-                ]),
-                ..Default::default()
-            },
-            "Reflecting Pool" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                // TODO: dynamically figure out what mana this produces
-                produced_mana: HashMap::from([
-                    (Mana::White, 1),
-                    (Mana::Blue, 1),
-                    (Mana::Black, 1),
-                    (Mana::Red, 1),
-                    (Mana::Green, 1),
-                ]),
-                ..Default::default()
-            },
-            "Phyrexian Tower" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                // TODO: the black mana from sac
-                produced_mana: HashMap::from([(Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Ancient Tomb" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                produced_mana: HashMap::from([(Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Hickory Woodlot" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                produced_mana: HashMap::from([(Mana::Green, 2)]),
-                is_tapped: true,
-                remaining_uses: Some(2),
-                ..Default::default()
-            },
-            "Dryad Arbor" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                is_summoning_sick: true,
-                produced_mana: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Plains" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Plains)]),
-                produced_mana: HashMap::from([(Mana::White, 1)]),
-                ..Default::default()
-            },
-            "Island" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Island)]),
-                produced_mana: HashMap::from([(Mana::Blue, 1)]),
-                ..Default::default()
-            },
-            "Swamp" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Swamp)]),
-                produced_mana: HashMap::from([(Mana::Black, 1)]),
-                ..Default::default()
-            },
-            "Mountain" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Mountain)]),
-                produced_mana: HashMap::from([(Mana::Red, 1)]),
-                ..Default::default()
-            },
-            "Forest" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Forest)]),
-                produced_mana: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Tundra" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Plains), SubType::Land(LandType::Island)]),
-                produced_mana: HashMap::from([(Mana::Blue, 1), (Mana::White, 1)]),
-                ..Default::default()
-            },
-            "Underground Sea" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Island), SubType::Land(LandType::Swamp)]),
-                produced_mana: HashMap::from([(Mana::Blue, 1), (Mana::Black, 1)]),
-                ..Default::default()
-            },
-            "Volcanic Island" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Island), SubType::Land(LandType::Mountain)]),
-                produced_mana: HashMap::from([(Mana::Blue, 1), (Mana::Red, 1)]),
-                ..Default::default()
-            },
-            "Tropical Island" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Island), SubType::Land(LandType::Forest)]),
-                produced_mana: HashMap::from([(Mana::Blue, 1), (Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Scrubland" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Plains), SubType::Land(LandType::Swamp)]),
-                produced_mana: HashMap::from([(Mana::White, 1), (Mana::Black, 1)]),
-                ..Default::default()
-            },
-            "Badlands" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Swamp), SubType::Land(LandType::Mountain)]),
-                produced_mana: HashMap::from([(Mana::Black, 1), (Mana::Red, 1)]),
-                ..Default::default()
-            },
-            "Bayou" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Swamp), SubType::Land(LandType::Forest)]),
-                produced_mana: HashMap::from([(Mana::Black, 1), (Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Plateau" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Plains), SubType::Land(LandType::Mountain)]),
-                produced_mana: HashMap::from([(Mana::Red, 1), (Mana::White, 1)]),
-                ..Default::default()
-            },
-            "Savannah" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Plains), SubType::Land(LandType::Forest)]),
-                produced_mana: HashMap::from([(Mana::Green, 1), (Mana::White, 1)]),
-                ..Default::default()
-            },
-            "Taiga" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                sub_types: HashSet::from([SubType::Land(LandType::Forest), SubType::Land(LandType::Mountain)]),
-                produced_mana: HashMap::from([(Mana::Green, 1), (Mana::Red, 1)]),
-                ..Default::default()
-            },
-            "Flooded Strand" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Plains, LandType::Island]),
-                ))),
-                ..Default::default()
-            },
-            "Marsh Flats" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Plains, LandType::Swamp]),
-                ))),
-                ..Default::default()
-            },
-            "Windswept Heath" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Plains, LandType::Forest]),
-                ))),
-                ..Default::default()
-            },
-            "Arid Mesa" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Plains, LandType::Mountain]),
-                ))),
-                ..Default::default()
-            },
-            "Polluted Delta" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Island, LandType::Swamp]),
-                ))),
-                ..Default::default()
-            },
-            "Scalding Tarn" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Island, LandType::Mountain]),
-                ))),
-                ..Default::default()
-            },
-            "Misty Rainforest" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Island, LandType::Forest]),
-                ))),
-                ..Default::default()
-            },
-            "Verdant Catacombs" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Swamp, LandType::Forest]),
-                ))),
-                ..Default::default()
-            },
-            "Bloodstained Mire" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Swamp, LandType::Mountain]),
-                ))),
-                ..Default::default()
-            },
-            "Wooded Foothills" => Card {
-                name,
-                card_types: HashSet::from([CardType::Land]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::Land(vec![LandType::Forest, LandType::Mountain]),
-                ))),
-                ..Default::default()
-            },
-            "Reveillark" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::White, 1), (Mana::Colorless, 4)]),
-                // TODO: Effect
-                ..Default::default()
-            },
-            "Body Double" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 4)]),
-                // TODO: Effect
-                ..Default::default()
-            },
-            "Protean Hulk" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Green, 2), (Mana::Colorless, 5)]),
-                ..Default::default()
-            },
-            "Natural Order" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Green, 2), (Mana::Colorless, 2)]),
-                on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
-                    SearchFilter::GreenCreature,
-                ))),
-                ..Default::default()
-            },
-            "Gitaxian Probe" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                // TODO: Phyrexian mana, but just pay life for now
-                cost: HashMap::new(),
-                on_resolve: Some(Effect::Draw(1)),
-                ..Default::default()
-            },
-            "Mogg Fanatic" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([(Mana::Red, 1)]),
-                ..Default::default()
-            },
-            "Progenitus" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([
-                    (Mana::White, 2),
-                    (Mana::Blue, 2),
-                    (Mana::Black, 2),
-                    (Mana::Red, 2),
-                    (Mana::Green, 2),
-                ]),
-                ..Default::default()
-            },
-            "Witherbloom Apprentice" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([
-                    (Mana::Black, 1),
-                    (Mana::Green, 1),
-                ]),
-                ..Default::default()
-            },
-            "Sedgemoor Witch" => Card {
-                name,
-                card_types: HashSet::from([CardType::Creature]),
-                cost: HashMap::from([
-                    (Mana::Black, 1),
-                    (Mana::Colorless, 2),
-                ]),
-                ..Default::default()
-            },
-            "Summoner's Pact" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::new(),
-                // TODO: pay or lose game next turn
-                on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::GreenCreature))),
-                ..Default::default()
-            },
-            "Brainstorm" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1)]),
-                on_resolve: Some(Effect::Brainstorm),
-                ..Default::default()
-            },
-            "Preordain" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Blue, 1)]),
-                // TODO: Scrying
-                on_resolve: Some(Effect::Preordain),
-                ..Default::default()
-            },
-            "Ponder" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Blue, 1)]),
-                // TODO: Scrying
-                on_resolve: Some(Effect::Ponder),
-                ..Default::default()
-            },
-            "Dark Ritual" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Black, 1)]),
-                produced_mana: HashMap::from([(Mana::Black, 3)]),
-                remaining_uses: Some(1),
-                ..Default::default()
-            },
-            "Veil of Summer" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Thoughtseize" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Black, 1)]),
-                ..Default::default()
-            },
-            "Lim-Dûl's Vault" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Black, 1)]),
-                // TOOD: shuffle tutoring effect properly
-                on_resolve: Some(Effect::SearchAndPutTopOfLibrary(None)),
-                ..Default::default()
-            },
-            "Chain of Smog" => Card {
-                name,
-                card_types: HashSet::from([CardType::Sorcery]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            "Carpet of Flowers" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Surgical Extraction" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::new(),
-                ..Default::default()
-            },
-            "Flusterstorm" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Blue, 1)]),
-                ..Default::default()
-            },
-            "Abrupt Decay" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Assassin's Trophy" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Black, 1), (Mana::Green, 1)]),
-                ..Default::default()
-            },
-            "Energy Flux" => Card {
-                name,
-                card_types: HashSet::from([CardType::Enchantment]),
-                cost: HashMap::from([(Mana::Blue, 1), (Mana::Colorless, 2)]),
-                ..Default::default()
-            },
-            "Massacre" => Card {
-                name,
-                card_types: HashSet::from([CardType::Instant]),
-                cost: HashMap::from([(Mana::Black, 2), (Mana::Colorless, 1)]),
-                ..Default::default()
-            },
-            name => {
-                return Err(format!("unimplemented card: {name}"));
-            }
-        };
+        match card {
+            Some(card) => Ok(card),
+            None => match resolve_alias(card_name) {
+                Some(canonical) if canonical != card_name => {
+                    warn!("Resolved card name \"{card_name}\" to \"{canonical}\"");
+                    Card::new(&canonical)
+                }
+                _ => Err(format!("unimplemented card: {card_name}")),
+            },
+        }
+    }
+
+    /// Parses a user-defined card, leaving out the fields it doesn't need. The cost can be
+    /// given either by the type of mana or with mana symbols. For example:
+    ///
+    /// ```
+    /// use goldfisher::card::Card;
+    ///
+    /// let card = Card::from_json(r#"{
+    ///     "name": "Grizzly Bears",
+    ///     "card_types": ["Creature"],
+    ///     "cost": "1G",
+    ///     "power": 2,
+    ///     "toughness": 2
+    /// }"#)
+    /// .unwrap();
+    ///
+    /// assert_eq!(2, card.mana_value());
+    /// ```
+    #[cfg(feature = "custom_cards")]
+    pub fn from_json(json: &str) -> Result<Card, String> {
+        let mut card: serde_json::Value =
+            serde_json::from_str(json).map_err(|err| format!("invalid custom card: {err}"))?;
+
+        // The cost can also be written with mana symbols, see `parse_cost`
+        if let Some(cost) = card.get("cost").and_then(|cost| cost.as_str()) {
+            let cost = crate::mana::parse_cost(cost)?;
+            card["cost"] = serde_json::to_value(cost).map_err(|err| format!("invalid custom card: {err}"))?;
+        }
 
-        Ok(card)
+        serde_json::from_value(card).map_err(|err| format!("invalid custom card: {err}"))
+    }
+
+    /// Every implemented card sorted by name, for checking which cards can be used in a decklist
+    pub fn all() -> Vec<Card> {
+        let mut cards = CARD_DATABASE.with(|cards| cards.values().cloned().collect::<Vec<_>>());
+        cards.sort_by(|a, b| a.name.cmp(&b.name));
+        cards
     }
 
     pub fn new_as_ref(name: &str) -> CardRef {
@@ -1146,4 +304,1338 @@ impl Card {
         card.zone = zone;
         Rc::new(RefCell::new(card))
     }
+    /// Mana value of the card, the total amount of mana in its cost
+    pub fn mana_value(&self) -> u32 {
+        self.cost.values().sum()
+    }
+
+    /// Times the card can still be used for mana, `None` for no limit
+    pub fn remaining_uses(&self) -> Option<u32> {
+        if self.is_one_shot {
+            Some(1)
+        } else {
+            self.charges
+        }
+    }
+
+    /// Zone the card can be used for mana from
+    pub fn mana_zone(&self) -> Zone {
+        if self.is_pitch_for_mana {
+            Zone::Hand
+        } else {
+            Zone::Battlefield
+        }
+    }
+}
+
+thread_local! {
+    /// The implemented cards by name, built once per thread as the cards aren't `Sync`
+    static CARD_DATABASE: HashMap<String, Card> = card_definitions()
+        .into_iter()
+        .map(|card| (card.name.clone(), card))
+        .collect();
+}
+
+/// Definitions of the implemented cards, looked up by their names in `Card::new`
+fn card_definitions() -> Vec<Card> {
+    vec![
+        Card {
+            name: "Llanowar Elves".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            produced_mana: HashMap::from([(Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Veteran Explorer".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Xantid Swarm".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            // Attacking with it keeps the opponent from casting spells, simplified as a static ability
+            prevents_counters: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Sylvan Safekeeper".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Fyndhorn Elves".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            produced_mana: HashMap::from([(Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Birds of Paradise".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            produced_mana: HashMap::from([
+                (Mana::White, 1),
+                (Mana::Blue, 1),
+                (Mana::Black, 1),
+                (Mana::Red, 1),
+                (Mana::Green, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Noble Hierarch".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            produced_mana: HashMap::from([(Mana::White, 1), (Mana::Blue, 1), (Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Carrion Feeder".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 1)]),
+            is_sac_outlet: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Viscera Seer".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 1)]),
+            is_sac_outlet: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Nantuko Husk".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 2)]),
+            is_sac_outlet: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Phyrexian Ghoul".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 2)]),
+            is_sac_outlet: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Pattern of Rebirth".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 3)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Academy Rector".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 3)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Mesmeric Fiend".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Iridescent Drake".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 3)]),
+            on_resolve: Some(Effect::IridescentDrake),
+            ..Default::default()
+        },
+        Card {
+            name: "Karmic Guide".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::White, 2), (Mana::Generic, 3)]),
+            on_resolve: Some(Effect::KarmicGuide),
+            is_echo: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Volrath's Shapeshifter".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Blue, 2), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Caller of the Claw".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 2)]),
+            power: 2,
+            toughness: 2,
+            on_resolve: Some(Effect::CallerOfTheClaw),
+            ..Default::default()
+        },
+        Card {
+            name: "Body Snatcher".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 2), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::BodySnatcher),
+            ..Default::default()
+        },
+        Card {
+            name: "Akroma, Angel of Wrath".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::White, 3), (Mana::Generic, 5)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Phantom Nishoba".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Green, 1), (Mana::Generic, 5)]),
+            // Enters with seven +1/+1 counters
+            power: 7,
+            toughness: 7,
+            ..Default::default()
+        },
+        Card {
+            name: "Worship".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 3)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Pernicious Deed".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Black, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Recurring Nightmare".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Seal of Cleansing".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "City of Solitude".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Engineered Plague".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Circle of Protection: Red".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Warmth".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Goblin Bombardment".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::Red, 1), (Mana::Generic, 1)]),
+            is_sac_outlet: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Altar of Dementia".to_owned(),
+            card_types: HashSet::from([CardType::Artifact]),
+            cost: HashMap::from([(Mana::Generic, 2)]),
+            is_sac_outlet: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Cabal Therapy".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Black, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Duress".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Black, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Swords to Plowshares".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::White, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Worldly Tutor".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            on_resolve: Some(Effect::SearchAndPutTopOfLibrary(Some(
+                SearchFilter::Creature,
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Enlightened Tutor".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::White, 1)]),
+            on_resolve: Some(Effect::SearchAndPutTopOfLibrary(Some(
+                SearchFilter::EnchantmentArtifact,
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Entomb".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Black, 1)]),
+            on_resolve: Some(Effect::SearchAndPutGraveyard(None, 1)),
+            ..Default::default()
+        },
+        Card {
+            name: "Buried Alive".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::SearchAndPutGraveyard(Some(SearchFilter::Creature), 3)),
+            ..Default::default()
+        },
+        Card {
+            name: "Eladamri's Call".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Green, 1)]),
+            on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::Creature))),
+            ..Default::default()
+        },
+        Card {
+            name: "Vindicate".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Black, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Rofellos, Llanowar Emissary".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            produced_mana: HashMap::from([(Mana::Green, 1)]),
+            produced_mana_dynamic: Some(DynamicMana::PerLandType(Mana::Green, LandType::Forest)),
+            cost: HashMap::from([(Mana::Green, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Wall of Roots".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            produced_mana: HashMap::from([(Mana::Green, 1)]),
+            charges: Some(5),
+            taps_for_mana: false,
+            is_haste: true,
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Elvish Spirit Guide".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            produced_mana: HashMap::from([(Mana::Green, 1)]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 2)]),
+            is_one_shot: true,
+            is_pitch_for_mana: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Simian Spirit Guide".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            produced_mana: HashMap::from([(Mana::Red, 1)]),
+            cost: HashMap::from([(Mana::Red, 1), (Mana::Generic, 2)]),
+            is_one_shot: true,
+            is_pitch_for_mana: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Lotus Petal".to_owned(),
+            card_types: HashSet::from([CardType::Artifact]),
+            produced_mana: HashMap::from([
+                (Mana::White, 1),
+                (Mana::Blue, 1),
+                (Mana::Black, 1),
+                (Mana::Red, 1),
+                (Mana::Green, 1),
+            ]),
+            cost: HashMap::new(),
+            is_one_shot: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Soul Warden".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::White, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Unearth".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Black, 1)]),
+            on_resolve: Some(Effect::Unearth),
+            ..Default::default()
+        },
+        Card {
+            name: "Cavern Harpy".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            sub_types: HashSet::from([SubType::Creature(CreatureType::Harpy), SubType::Creature(CreatureType::Beast)]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Black, 1)]),
+            on_resolve: Some(Effect::CavernHarpy),
+            ..Default::default()
+        },
+        Card {
+            name: "Cloud of Faeries".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 1)]),
+            on_resolve: Some(Effect::UntapLands(Some(2))),
+            ..Default::default()
+        },
+        Card {
+            name: "Impulse".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 1)]),
+            on_resolve: Some(Effect::Impulse(4)),
+            ..Default::default()
+        },
+        Card {
+            name: "Living Wish".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 1)]),
+            on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::Wish(vec![
+                CardType::Creature,
+                CardType::Land,
+            ])))),
+            ..Default::default()
+        },
+        Card {
+            name: "Cunning Wish".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::Wish(vec![
+                CardType::Instant,
+            ])))),
+            ..Default::default()
+        },
+        Card {
+            name: "Ray of Revelation".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Intuition".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::Intuition),
+            ..Default::default()
+        },
+        Card {
+            name: "Raven Familiar".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::Impulse(3)), // TODO: Separate effect
+            ..Default::default()
+        },
+        Card {
+            name: "Wirewood Savage".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Aluren".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 2), (Mana::Generic, 2)]),
+            cost_reduction: Some(CostReduction::Aluren),
+            ..Default::default()
+        },
+        Card {
+            name: "Maggot Carrier".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::DamageEach(1)),
+            ..Default::default()
+        },
+        Card {
+            name: "Auramancer".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Monk Realist".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Plague Spitter".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Ravenous Baloth".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            sub_types: HashSet::from([SubType::Creature(CreatureType::Beast)]),
+            cost: HashMap::from([(Mana::Green, 2), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Naturalize".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Crippling Fatigue".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Black, 2), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Uktabi Orangutan".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Bone Shredder".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Hydroblast".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Blue Elemental Blast".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Mana Short".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Words of Wisdom".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 1)]),
+            on_resolve: Some(Effect::WordsOfWisdom),
+            ..Default::default()
+        },
+        Card {
+            name: "Snap".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 1)]),
+            on_resolve: Some(Effect::Snap),
+            ..Default::default()
+        },
+        Card {
+            name: "Brain Freeze".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 1)]),
+            on_resolve: Some(Effect::BrainFreeze),
+            ..Default::default()
+        },
+        Card {
+            name: "Frantic Search".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::FranticSearch),
+            ..Default::default()
+        },
+        Card {
+            name: "Meditate".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::Meditate),
+            ..Default::default()
+        },
+        Card {
+            name: "Merchant Scroll".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 1)]),
+            on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::BlueInstant))),
+            ..Default::default()
+        },
+        Card {
+            name: "Sleight of Hand".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Blue, 1)]),
+            on_resolve: Some(Effect::Impulse(2)),
+            ..Default::default()
+        },
+        Card {
+            name: "Helm of Awakening".to_owned(),
+            card_types: HashSet::from([CardType::Artifact]),
+            cost: HashMap::from([(Mana::Generic, 2)]),
+            cost_reduction: Some(CostReduction::All(Mana::Generic, 1)),
+            ..Default::default()
+        },
+        Card {
+            name: "Sapphire Medallion".to_owned(),
+            card_types: HashSet::from([CardType::Artifact]),
+            cost: HashMap::from([(Mana::Generic, 2)]),
+            cost_reduction: Some(CostReduction::Color(Mana::Blue, (Mana::Generic, 1))),
+            ..Default::default()
+        },
+        Card {
+            name: "Chain of Vapor".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Defense Grid".to_owned(),
+            card_types: HashSet::from([CardType::Artifact]),
+            cost: HashMap::from([(Mana::Generic, 2)]),
+            counter_tax: 3,
+            ..Default::default()
+        },
+        Card {
+            name: "Tormod's Crypt".to_owned(),
+            card_types: HashSet::from([CardType::Artifact]),
+            cost: HashMap::new(),
+            ..Default::default()
+        },
+        Card {
+            name: "Hurkyl's Recall".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Turnabout".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 2), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::UntapLands(None)),
+            ..Default::default()
+        },
+        Card {
+            name: "City of Brass".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            produced_mana: HashMap::from([
+                (Mana::White, 1),
+                (Mana::Blue, 1),
+                (Mana::Black, 1),
+                (Mana::Red, 1),
+                (Mana::Green, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Llanowar Wastes".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            produced_mana: HashMap::from([
+                (Mana::Black, 1),
+                (Mana::Green, 1),
+                (Mana::Colorless, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Brushland".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            produced_mana: HashMap::from([
+                (Mana::White, 1),
+                (Mana::Green, 1),
+                (Mana::Colorless, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Yavimaya Coast".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            produced_mana: HashMap::from([
+                (Mana::Blue, 1),
+                (Mana::Green, 1),
+                (Mana::Colorless, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Caves of Koilos".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            produced_mana: HashMap::from([
+                (Mana::White, 1),
+                (Mana::Black, 1),
+                (Mana::Colorless, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Underground River".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            produced_mana: HashMap::from([
+                (Mana::Blue, 1),
+                (Mana::Black, 1),
+                (Mana::Colorless, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Gemstone Mine".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            charges: Some(3),
+            produced_mana: HashMap::from([
+                (Mana::White, 1),
+                (Mana::Blue, 1),
+                (Mana::Black, 1),
+                (Mana::Red, 1),
+                (Mana::Green, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Reflecting Pool".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            // TODO: dynamically figure out what mana this produces
+            produced_mana: HashMap::from([
+                (Mana::White, 1),
+                (Mana::Blue, 1),
+                (Mana::Black, 1),
+                (Mana::Red, 1),
+                (Mana::Green, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Phyrexian Tower".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            // TODO: the black mana from sac
+            produced_mana: HashMap::from([(Mana::Colorless, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Ancient Tomb".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            produced_mana: HashMap::from([(Mana::Colorless, 2)]),
+            pain: 2,
+            ..Default::default()
+        },
+        Card {
+            name: "Hickory Woodlot".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            produced_mana: HashMap::from([(Mana::Green, 2)]),
+            is_tapped: true,
+            charges: Some(2),
+            ..Default::default()
+        },
+        Card {
+            name: "Dryad Arbor".to_owned(),
+            card_types: HashSet::from([CardType::Land, CardType::Creature]),
+            sub_types: HashSet::from([SubType::Land(LandType::Forest)]),
+            color_indicator: Some(Mana::Green),
+            is_summoning_sick: true,
+            produced_mana: HashMap::from([(Mana::Green, 1)]),
+            power: 1,
+            toughness: 1,
+            ..Default::default()
+        },
+        Card {
+            name: "Plains".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Plains)]),
+            produced_mana: HashMap::from([(Mana::White, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Island".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Island)]),
+            produced_mana: HashMap::from([(Mana::Blue, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Swamp".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Swamp)]),
+            produced_mana: HashMap::from([(Mana::Black, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Mountain".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Mountain)]),
+            produced_mana: HashMap::from([(Mana::Red, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Forest".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Forest)]),
+            produced_mana: HashMap::from([(Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Tundra".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Plains), SubType::Land(LandType::Island)]),
+            produced_mana: HashMap::from([(Mana::Blue, 1), (Mana::White, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Underground Sea".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Island), SubType::Land(LandType::Swamp)]),
+            produced_mana: HashMap::from([(Mana::Blue, 1), (Mana::Black, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Volcanic Island".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Island), SubType::Land(LandType::Mountain)]),
+            produced_mana: HashMap::from([(Mana::Blue, 1), (Mana::Red, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Tropical Island".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Island), SubType::Land(LandType::Forest)]),
+            produced_mana: HashMap::from([(Mana::Blue, 1), (Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Scrubland".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Plains), SubType::Land(LandType::Swamp)]),
+            produced_mana: HashMap::from([(Mana::White, 1), (Mana::Black, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Badlands".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Swamp), SubType::Land(LandType::Mountain)]),
+            produced_mana: HashMap::from([(Mana::Black, 1), (Mana::Red, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Bayou".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Swamp), SubType::Land(LandType::Forest)]),
+            produced_mana: HashMap::from([(Mana::Black, 1), (Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Plateau".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Plains), SubType::Land(LandType::Mountain)]),
+            produced_mana: HashMap::from([(Mana::Red, 1), (Mana::White, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Savannah".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Plains), SubType::Land(LandType::Forest)]),
+            produced_mana: HashMap::from([(Mana::Green, 1), (Mana::White, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Taiga".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            sub_types: HashSet::from([SubType::Land(LandType::Forest), SubType::Land(LandType::Mountain)]),
+            produced_mana: HashMap::from([(Mana::Green, 1), (Mana::Red, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Flooded Strand".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Plains, LandType::Island]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Marsh Flats".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Plains, LandType::Swamp]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Windswept Heath".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Plains, LandType::Forest]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Arid Mesa".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Plains, LandType::Mountain]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Polluted Delta".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Island, LandType::Swamp]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Scalding Tarn".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Island, LandType::Mountain]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Misty Rainforest".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Island, LandType::Forest]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Verdant Catacombs".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Swamp, LandType::Forest]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Bloodstained Mire".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Swamp, LandType::Mountain]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Wooded Foothills".to_owned(),
+            card_types: HashSet::from([CardType::Land]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::Land(vec![LandType::Forest, LandType::Mountain]),
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Reveillark".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::White, 1), (Mana::Generic, 4)]),
+            power: 4,
+            toughness: 3,
+            on_dies: Some(Effect::Reveillark),
+            ..Default::default()
+        },
+        Card {
+            name: "Body Double".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 4)]),
+            // TODO: Effect
+            ..Default::default()
+        },
+        Card {
+            name: "Protean Hulk".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Green, 2), (Mana::Generic, 5)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Natural Order".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Green, 2), (Mana::Generic, 2)]),
+            on_resolve: Some(Effect::SearchAndPutBattlefield(Some(
+                SearchFilter::GreenCreature,
+            ))),
+            ..Default::default()
+        },
+        Card {
+            name: "Gitaxian Probe".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            // TODO: Phyrexian mana, but just pay life for now
+            cost: HashMap::new(),
+            on_resolve: Some(Effect::Draw(1)),
+            ..Default::default()
+        },
+        Card {
+            name: "Mogg Fanatic".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Red, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Progenitus".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([
+                (Mana::White, 2),
+                (Mana::Blue, 2),
+                (Mana::Black, 2),
+                (Mana::Red, 2),
+                (Mana::Green, 2),
+            ]),
+            power: 10,
+            toughness: 10,
+            // Protection from everything
+            is_unblockable: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Witherbloom Apprentice".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([
+                (Mana::Black, 1),
+                (Mana::Green, 1),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Sedgemoor Witch".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([
+                (Mana::Black, 1),
+                (Mana::Generic, 2),
+            ]),
+            ..Default::default()
+        },
+        Card {
+            name: "Summoner's Pact".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::new(),
+            // TODO: pay or lose game next turn
+            on_resolve: Some(Effect::SearchAndPutHand(Some(SearchFilter::GreenCreature))),
+            ..Default::default()
+        },
+        Card {
+            name: "Brainstorm".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1)]),
+            on_resolve: Some(Effect::Brainstorm),
+            ..Default::default()
+        },
+        Card {
+            name: "Preordain".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Blue, 1)]),
+            // TODO: Scrying
+            on_resolve: Some(Effect::Preordain),
+            ..Default::default()
+        },
+        Card {
+            name: "Ponder".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Blue, 1)]),
+            // TODO: Scrying
+            on_resolve: Some(Effect::Ponder),
+            ..Default::default()
+        },
+        Card {
+            name: "Dark Ritual".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Black, 1)]),
+            produced_mana: HashMap::from([(Mana::Black, 3)]),
+            is_one_shot: true,
+            ..Default::default()
+        },
+        Card {
+            name: "Veil of Summer".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Thoughtseize".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Black, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Lim-Dûl's Vault".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Black, 1)]),
+            // TOOD: shuffle tutoring effect properly
+            on_resolve: Some(Effect::SearchAndPutTopOfLibrary(None)),
+            ..Default::default()
+        },
+        Card {
+            name: "Chain of Smog".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Carpet of Flowers".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Surgical Extraction".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::new(),
+            ..Default::default()
+        },
+        Card {
+            name: "Flusterstorm".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Blue, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Abrupt Decay".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Assassin's Trophy".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Black, 1), (Mana::Green, 1)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Energy Flux".to_owned(),
+            card_types: HashSet::from([CardType::Enchantment]),
+            cost: HashMap::from([(Mana::Blue, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        },
+        Card {
+            name: "Massacre".to_owned(),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Black, 2), (Mana::Generic, 1)]),
+            ..Default::default()
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_sums_all_pips_to_mana_value() {
+        assert_eq!(4, Card::new("Pattern of Rebirth").unwrap().mana_value());
+        assert_eq!(1, Card::new("Llanowar Elves").unwrap().mana_value());
+        assert_eq!(0, Card::new("Lotus Petal").unwrap().mana_value());
+        assert_eq!(0, Card::new("Forest").unwrap().mana_value());
+    }
+
+    #[test]
+    fn it_resolves_card_name_aliases() {
+        assert_eq!("Llanowar Wastes", Card::new("Llawnowar Wastes").unwrap().name);
+        assert_eq!("Eladamri's Call", Card::new("eladamris call").unwrap().name);
+        assert_eq!("Akroma, Angel of Wrath", Card::new("akroma angel of wrath").unwrap().name);
+        assert_eq!("Volrath's Shapeshifter", Card::new("VOLRATHS SHAPESHIFTER").unwrap().name);
+        assert_eq!("Lim-Dûl's Vault", Card::new("Lim-Dul's Vault").unwrap().name);
+        assert!(Card::new("Llanowar Waste").is_err());
+
+        for (alias, canonical) in CARD_NAME_ALIASES {
+            assert_eq!(canonical, Card::new(alias).unwrap().name);
+        }
+
+        // Otherwise the name a misspelling resolves to would depend on the order of the database
+        let normalized = Card::all()
+            .iter()
+            .map(|card| normalize_card_name(&card.name))
+            .collect::<HashSet<_>>();
+        assert_eq!(Card::all().len(), normalized.len());
+    }
+
+    #[test]
+    // The identities hash by the pointer, which the interior mutability of the cards doesn't change
+    #[allow(clippy::mutable_key_type)]
+    fn it_compares_cards_by_identity() {
+        let forest = Card::new_as_ref("Forest");
+        let other_forest = Card::new_as_ref("Forest");
+
+        assert!(same_card(&forest, &Rc::clone(&forest)));
+        assert!(!same_card(&forest, &other_forest));
+
+        assert_eq!(CardIdentity::from(Rc::clone(&forest)), CardIdentity::from(Rc::clone(&forest)));
+        assert_ne!(CardIdentity::from(Rc::clone(&forest)), CardIdentity::from(Rc::clone(&other_forest)));
+
+        let cards = HashSet::from([
+            CardIdentity::from(Rc::clone(&forest)),
+            CardIdentity::from(Rc::clone(&forest)),
+            CardIdentity::from(other_forest),
+        ]);
+        assert_eq!(2, cards.len());
+    }
+
+    #[test]
+    fn it_lists_every_implemented_card() {
+        let cards = Card::all();
+        let names = cards.iter().map(|card| card.name.as_str()).collect::<HashSet<_>>();
+
+        assert!(!cards.is_empty());
+        assert_eq!(cards.len(), names.len());
+        assert!(names.contains("Aluren"));
+    }
+
+    #[test]
+    fn it_builds_every_previously_implemented_card() {
+        let names = [
+            "Llanowar Elves",
+            "Veteran Explorer",
+            "Xantid Swarm",
+            "Sylvan Safekeeper",
+            "Fyndhorn Elves",
+            "Birds of Paradise",
+            "Noble Hierarch",
+            "Carrion Feeder",
+            "Viscera Seer",
+            "Nantuko Husk",
+            "Phyrexian Ghoul",
+            "Pattern of Rebirth",
+            "Academy Rector",
+            "Mesmeric Fiend",
+            "Iridescent Drake",
+            "Karmic Guide",
+            "Volrath's Shapeshifter",
+            "Caller of the Claw",
+            "Body Snatcher",
+            "Akroma, Angel of Wrath",
+            "Phantom Nishoba",
+            "Worship",
+            "Pernicious Deed",
+            "Recurring Nightmare",
+            "Seal of Cleansing",
+            "City of Solitude",
+            "Engineered Plague",
+            "Circle of Protection: Red",
+            "Warmth",
+            "Goblin Bombardment",
+            "Altar of Dementia",
+            "Cabal Therapy",
+            "Duress",
+            "Swords to Plowshares",
+            "Worldly Tutor",
+            "Enlightened Tutor",
+            "Entomb",
+            "Buried Alive",
+            "Eladamri's Call",
+            "Vindicate",
+            "Rofellos, Llanowar Emissary",
+            "Wall of Roots",
+            "Elvish Spirit Guide",
+            "Simian Spirit Guide",
+            "Lotus Petal",
+            "Soul Warden",
+            "Unearth",
+            "Cavern Harpy",
+            "Cloud of Faeries",
+            "Impulse",
+            "Living Wish",
+            "Cunning Wish",
+            "Ray of Revelation",
+            "Intuition",
+            "Raven Familiar",
+            "Wirewood Savage",
+            "Aluren",
+            "Maggot Carrier",
+            "Auramancer",
+            "Monk Realist",
+            "Plague Spitter",
+            "Ravenous Baloth",
+            "Naturalize",
+            "Crippling Fatigue",
+            "Uktabi Orangutan",
+            "Bone Shredder",
+            "Hydroblast",
+            "Blue Elemental Blast",
+            "Mana Short",
+            "Words of Wisdom",
+            "Snap",
+            "Brain Freeze",
+            "Frantic Search",
+            "Meditate",
+            "Merchant Scroll",
+            "Sleight of Hand",
+            "Helm of Awakening",
+            "Sapphire Medallion",
+            "Chain of Vapor",
+            "Defense Grid",
+            "Tormod's Crypt",
+            "Hurkyl's Recall",
+            "Turnabout",
+            "City of Brass",
+            "Llanowar Wastes",
+            "Brushland",
+            "Yavimaya Coast",
+            "Caves of Koilos",
+            "Underground River",
+            "Gemstone Mine",
+            "Reflecting Pool",
+            "Phyrexian Tower",
+            "Ancient Tomb",
+            "Hickory Woodlot",
+            "Dryad Arbor",
+            "Plains",
+            "Island",
+            "Swamp",
+            "Mountain",
+            "Forest",
+            "Tundra",
+            "Underground Sea",
+            "Volcanic Island",
+            "Tropical Island",
+            "Scrubland",
+            "Badlands",
+            "Bayou",
+            "Plateau",
+            "Savannah",
+            "Taiga",
+            "Flooded Strand",
+            "Marsh Flats",
+            "Windswept Heath",
+            "Arid Mesa",
+            "Polluted Delta",
+            "Scalding Tarn",
+            "Misty Rainforest",
+            "Verdant Catacombs",
+            "Bloodstained Mire",
+            "Wooded Foothills",
+            "Reveillark",
+            "Body Double",
+            "Protean Hulk",
+            "Natural Order",
+            "Gitaxian Probe",
+            "Mogg Fanatic",
+            "Progenitus",
+            "Witherbloom Apprentice",
+            "Sedgemoor Witch",
+            "Summoner's Pact",
+            "Brainstorm",
+            "Preordain",
+            "Ponder",
+            "Dark Ritual",
+            "Veil of Summer",
+            "Thoughtseize",
+            "Lim-Dûl's Vault",
+            "Chain of Smog",
+            "Carpet of Flowers",
+            "Surgical Extraction",
+            "Flusterstorm",
+            "Abrupt Decay",
+            "Assassin's Trophy",
+            "Energy Flux",
+            "Massacre",
+        ];
+
+        for name in names {
+            let card = Card::new(name).unwrap_or_else(|err| panic!("{err}"));
+            assert_eq!(name, card.name);
+        }
+
+        assert_eq!(names.len(), Card::all().len());
+    }
 }
@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::collections::vec_deque::Iter;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::error::Error;
 use std::fmt;
 use std::rc::Rc;
@@ -9,9 +9,12 @@ use std::str::FromStr;
 use serde::{Deserialize, Serialize};
 
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
 
-use crate::card::{Card, CardRef, Zone};
+use crate::card::{same_card, Card, CardRef, CardType, SearchFilter, Zone};
+use crate::effect::Effect;
+use crate::mana::Mana;
+use crate::utils::{is_basic, is_card_type, is_color, is_named, matches_search_filter};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Decklist {
@@ -95,6 +98,85 @@ impl FromStr for Decklist {
     }
 }
 
+/// Card names with a quantity of each
+pub type CardQuantities = Vec<(String, usize)>;
+
+impl Decklist {
+    /// Cards added to and removed from the maindeck to get from this decklist to the other one,
+    /// by name with the number of copies changed
+    pub fn diff(&self, other: &Decklist) -> (CardQuantities, CardQuantities) {
+        fn count_by_name(cards: &[(String, usize)]) -> BTreeMap<&str, usize> {
+            let mut counts = BTreeMap::new();
+            for (name, quantity) in cards {
+                *counts.entry(name.as_str()).or_insert(0) += quantity;
+            }
+            counts
+        }
+
+        let from = count_by_name(&self.maindeck);
+        let to = count_by_name(&other.maindeck);
+
+        let changes = |a: &BTreeMap<&str, usize>, b: &BTreeMap<&str, usize>| {
+            b.iter()
+                .filter_map(|(name, quantity)| {
+                    let difference = quantity.saturating_sub(*a.get(name).unwrap_or(&0));
+                    (difference > 0).then(|| (name.to_string(), difference))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        (changes(&from, &to), changes(&to, &from))
+    }
+}
+
+/// Constructed format the deck is played in.
+/// Only the deck construction rules are checked, not the banned lists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Format {
+    Premodern,
+    Legacy,
+}
+
+impl Format {
+    pub fn min_deck_size(&self) -> usize {
+        match self {
+            Format::Premodern | Format::Legacy => 60,
+        }
+    }
+
+    pub fn max_copies(&self) -> usize {
+        match self {
+            Format::Premodern | Format::Legacy => 4,
+        }
+    }
+
+    pub fn max_sideboard_size(&self) -> usize {
+        match self {
+            Format::Premodern | Format::Legacy => 15,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LegalityIssue {
+    /// The maindeck has fewer cards than the format allows
+    TooFewCards(usize),
+    /// More copies of a nonbasic card than the format allows, counting the sideboard
+    TooManyCopies(String, usize),
+    /// The sideboard has more cards than the format allows
+    TooLargeSideboard(usize),
+}
+
+impl fmt::Display for LegalityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LegalityIssue::TooFewCards(count) => write!(f, "too few cards in the deck: {count}"),
+            LegalityIssue::TooManyCopies(name, count) => write!(f, "too many copies of {name}: {count}"),
+            LegalityIssue::TooLargeSideboard(count) => write!(f, "too many cards in the sideboard: {count}"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Deck {
     pub maindeck: VecDeque<CardRef>,
@@ -112,12 +194,26 @@ impl FromStr for Deck {
 
 impl Deck {
     pub fn new(decklist: &Decklist) -> Result<Self, ParseDeckError> {
+        Self::with_custom_cards(decklist, &HashMap::new())
+    }
+
+    /// Creates the deck, using the given card definitions for the cards that aren't implemented
+    /// or to override the implemented ones
+    pub fn with_custom_cards(
+        decklist: &Decklist,
+        custom_cards: &HashMap<String, Card>,
+    ) -> Result<Self, ParseDeckError> {
+        let new_card = |card_name: &str| match custom_cards.get(card_name) {
+            Some(card) => Ok(card.clone()),
+            None => Card::new(card_name),
+        };
+
         let mut maindeck = Vec::with_capacity(60);
         let mut sideboard = Vec::with_capacity(15);
 
         for (card_name, quantity) in decklist.maindeck.iter() {
-            let card = Card::new(card_name)
-                .or_else(|msg| Err(ParseDeckError(format!("failed to create deck: {msg}"))))?;
+            let card = new_card(card_name)
+                .map_err(|msg| ParseDeckError(format!("failed to create deck: {msg}")))?;
 
             for _ in 0..*quantity {
                 maindeck.push(Rc::new(RefCell::new(card.clone())));
@@ -125,8 +221,8 @@ impl Deck {
         }
 
         for (card_name, quantity) in decklist.sideboard.iter() {
-            let mut card = Card::new(card_name)
-                .or_else(|msg| Err(ParseDeckError(format!("failed to create deck: {msg}"))))?;
+            let mut card = new_card(card_name)
+                .map_err(|msg| ParseDeckError(format!("failed to create deck: {msg}")))?;
             card.zone = Zone::Outside;
 
             for _ in 0..*quantity {
@@ -148,26 +244,27 @@ impl Deck {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.maindeck.len() > 0
+        self.maindeck.is_empty()
     }
 
-    pub fn shuffle(&mut self) {
+    /// Shuffles the library with the given random number generator
+    pub fn shuffle_with(&mut self, rng: &mut impl Rng) {
         let mut deck = Vec::from(self.maindeck.clone());
-        deck.shuffle(&mut thread_rng());
+        deck.shuffle(rng);
         self.maindeck = VecDeque::from(deck);
     }
 
     pub fn remove(&mut self, card: &CardRef) -> Option<CardRef> {
         self.maindeck
             .iter()
-            .position(|deck_card| Rc::ptr_eq(deck_card, card))
+            .position(|deck_card| same_card(deck_card, card))
             .and_then(|index| self.maindeck.remove(index))
     }
 
     pub fn remove_sideboard(&mut self, card: &CardRef) -> Option<CardRef> {
         self.sideboard
             .iter()
-            .position(|side_card| Rc::ptr_eq(side_card, card))
+            .position(|side_card| same_card(side_card, card))
             .map(|index| self.sideboard.remove(index))
     }
 
@@ -179,9 +276,137 @@ impl Deck {
         self.maindeck.push_back(card)
     }
 
+    /// Puts the cards on top of the library in the given order, so that the first card is drawn first
+    pub fn put_top_ordered(&mut self, cards: Vec<CardRef>) {
+        self.maindeck.extend(cards.into_iter().rev())
+    }
+
     pub fn iter(&self) -> Iter<'_, CardRef> {
         self.maindeck.iter()
     }
+
+    /// Returns the top `n` cards of the library without drawing them, the top card first
+    pub fn peek_top(&self, n: usize) -> Vec<CardRef> {
+        self.maindeck.iter().rev().take(n).cloned().collect()
+    }
+
+    /// Moves a copy of each of the named cards on top of the library, the first name on the top.
+    /// Panics if the library runs out of a named card.
+    #[cfg(test)]
+    pub(crate) fn stack_top(&mut self, card_names: &[&str]) {
+        for (stacked, card_name) in card_names.iter().rev().enumerate() {
+            let unstacked = self.maindeck.len() - stacked;
+            let index = self
+                .maindeck
+                .iter()
+                .take(unstacked)
+                .position(|card| card.borrow().name == *card_name)
+                .unwrap_or_else(|| panic!("no \"{card_name}\" left in the library to stack"));
+
+            let card = self.maindeck.remove(index).unwrap();
+            self.maindeck.push_back(card);
+        }
+    }
+
+    /// Returns the number of nonland cards in the library by their mana value
+    pub fn mana_curve(&self) -> BTreeMap<u32, usize> {
+        let mut curve = BTreeMap::new();
+
+        for card in self.maindeck.iter().filter(|card| !is_card_type(card, &CardType::Land)) {
+            *curve.entry(card.borrow().mana_value()).or_insert(0) += 1;
+        }
+
+        curve
+    }
+
+    /// Returns the number of cards in the library with each color in their cost
+    pub fn colors(&self) -> BTreeMap<Mana, usize> {
+        let mut colors = BTreeMap::new();
+
+        for color in [Mana::White, Mana::Blue, Mana::Black, Mana::Red, Mana::Green] {
+            let count = self.maindeck.iter().filter(|card| is_color(card, color)).count();
+            if count > 0 {
+                colors.insert(color, count);
+            }
+        }
+
+        colors
+    }
+
+    /// Returns the names of the cards in the library that can search for the given card,
+    /// either from the library or from the sideboard with a wish
+    pub fn tutors_for(&self, card_name: &str) -> Vec<String> {
+        let in_library = self.maindeck.iter().find(|card| is_named(card, card_name));
+        let in_sideboard = self.sideboard.iter().find(|card| is_named(card, card_name));
+
+        let mut tutors = Vec::new();
+
+        for card in self.maindeck.iter() {
+            let name = card.borrow().name.clone();
+            if name == card_name || tutors.contains(&name) {
+                continue;
+            }
+
+            let can_find = match &card.borrow().on_resolve {
+                Some(Effect::SearchAndPutHand(search_filter))
+                | Some(Effect::SearchAndPutTopOfLibrary(search_filter))
+                | Some(Effect::SearchAndPutBattlefield(search_filter))
+                | Some(Effect::SearchAndPutGraveyard(search_filter, _)) => {
+                    let target = match search_filter {
+                        Some(SearchFilter::Wish(_)) => in_sideboard,
+                        _ => in_library,
+                    };
+
+                    target.is_some_and(|target| matches_search_filter(&target, search_filter))
+                }
+                Some(Effect::Intuition) => in_library.is_some(),
+                _ => false,
+            };
+
+            if can_find {
+                tutors.push(name);
+            }
+        }
+
+        tutors.sort();
+        tutors
+    }
+
+    /// Checks the deck against the construction rules of the format, returning any issues found
+    pub fn check_format(&self, format: Format) -> Vec<LegalityIssue> {
+        let mut issues = Vec::new();
+
+        if self.maindeck.len() < format.min_deck_size() {
+            issues.push(LegalityIssue::TooFewCards(self.maindeck.len()));
+        }
+
+        if self.sideboard.len() > format.max_sideboard_size() {
+            issues.push(LegalityIssue::TooLargeSideboard(self.sideboard.len()));
+        }
+
+        let mut copies = BTreeMap::new();
+        for card in self.maindeck.iter().chain(self.sideboard.iter()) {
+            if !is_basic(&card) {
+                *copies.entry(card.borrow().name.clone()).or_insert(0) += 1;
+            }
+        }
+
+        for (name, count) in copies {
+            if count > format.max_copies() {
+                issues.push(LegalityIssue::TooManyCopies(name, count));
+            }
+        }
+
+        issues
+    }
+
+    /// Returns the number of cards with the given name left in the library
+    pub fn count(&self, name: &str) -> usize {
+        self.maindeck
+            .iter()
+            .filter(|card| card.borrow().name == name)
+            .count()
+    }
 }
 
 #[cfg(test)]
@@ -225,6 +450,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_diffs_decklists() {
+        let decklist = "4 Llanowar Elves\n2 Forest\n1 Swamp\n".parse::<Decklist>().unwrap();
+        let other = "4 Llanowar Elves\n2 Forest\n1 Island\n".parse::<Decklist>().unwrap();
+
+        let (added, removed) = decklist.diff(&other);
+        assert_eq!(vec![(String::from("Island"), 1)], added);
+        assert_eq!(vec![(String::from("Swamp"), 1)], removed);
+
+        let (added, removed) = other.diff(&decklist);
+        assert_eq!(vec![(String::from("Swamp"), 1)], added);
+        assert_eq!(vec![(String::from("Island"), 1)], removed);
+
+        let (added, removed) = decklist.diff(&decklist);
+        assert!(added.is_empty());
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn it_diffs_changed_quantities_across_lines() {
+        let decklist = "4 Llanowar Elves\n2 Forest\n".parse::<Decklist>().unwrap();
+        let other = "2 Llanowar Elves\n1 Forest\n2 Forest\n".parse::<Decklist>().unwrap();
+
+        let (added, removed) = decklist.diff(&other);
+        assert_eq!(vec![(String::from("Forest"), 1)], added);
+        assert_eq!(vec![(String::from("Llanowar Elves"), 2)], removed);
+    }
+
     #[test]
     fn it_handles_malformed_lines() {
         let decklist = "1 Birds of Paradise\n\
@@ -271,6 +524,30 @@ mod tests {
         assert_eq!(true, result.is_ok());
     }
 
+    #[test]
+    fn it_counts_cards_by_name() {
+        let deck = "4 Llanowar Elves\n\
+            2 Forest"
+            .parse::<Deck>()
+            .unwrap();
+
+        assert_eq!(4, deck.count("Llanowar Elves"));
+        assert_eq!(2, deck.count("Forest"));
+        assert_eq!(0, deck.count("Swamp"));
+    }
+
+    #[test]
+    fn it_is_empty_only_after_drawing_every_card() {
+        let mut deck = "2 Forest".parse::<Deck>().unwrap();
+        assert!(!deck.is_empty());
+
+        deck.draw();
+        assert!(!deck.is_empty());
+
+        deck.draw();
+        assert!(deck.is_empty());
+    }
+
     #[test]
     fn it_handles_deck_parse_errors() {
         let decklist = "1 Unknown Card\n\
@@ -284,4 +561,101 @@ mod tests {
             result.err()
         );
     }
+
+    #[test]
+    fn it_finds_too_many_copies_of_nonbasics() {
+        let deck = "5 Aluren\n\
+            4 Cavern Harpy\n\
+            51 Forest\n\
+            \n\
+            // Sideboard\n\
+            1 Cavern Harpy\n"
+            .parse::<Deck>()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                LegalityIssue::TooManyCopies("Aluren".to_owned(), 5),
+                LegalityIssue::TooManyCopies("Cavern Harpy".to_owned(), 5),
+            ],
+            deck.check_format(Format::Premodern)
+        );
+    }
+
+    #[test]
+    fn it_finds_undersized_decks_and_oversized_sideboards() {
+        let deck = "4 Llanowar Elves\n\
+            20 Forest\n\
+            \n\
+            // Sideboard\n\
+            16 Forest\n"
+            .parse::<Deck>()
+            .unwrap();
+
+        assert_eq!(
+            vec![LegalityIssue::TooFewCards(24), LegalityIssue::TooLargeSideboard(16)],
+            deck.check_format(Format::Legacy)
+        );
+    }
+
+    #[test]
+    fn it_accepts_default_decklists() {
+        for deck_strategy in crate::strategy::STRATEGIES {
+            let strategy = crate::strategy::from_enum(deck_strategy);
+            let deck = Deck::new(&strategy.default_decklist()).unwrap();
+
+            assert!(deck.check_format(deck_strategy.format()).is_empty(), "{}", strategy.name());
+        }
+    }
+
+    #[test]
+    fn it_counts_mana_curve_and_colors() {
+        let deck = "4 Llanowar Elves\n\
+            2 Cavern Harpy\n\
+            3 Aluren\n\
+            20 Forest"
+            .parse::<Deck>()
+            .unwrap();
+
+        assert_eq!(BTreeMap::from([(1, 4), (2, 2), (4, 3)]), deck.mana_curve());
+        assert_eq!(
+            BTreeMap::from([(Mana::Blue, 2), (Mana::Black, 2), (Mana::Green, 7)]),
+            deck.colors()
+        );
+    }
+
+    #[test]
+    fn it_finds_tutors_for_a_card() {
+        let deck = "4 Eladamri's Call\n\
+            4 Living Wish\n\
+            4 Cavern Harpy\n\
+            \n\
+            1 Cavern Harpy"
+            .parse::<Deck>()
+            .unwrap();
+
+        assert_eq!(vec!["Eladamri's Call", "Living Wish"], deck.tutors_for("Cavern Harpy"));
+        assert!(deck.tutors_for("Eladamri's Call").is_empty());
+    }
+
+    #[test]
+    fn it_finds_tutors_for_aluren_in_the_aluren_deck() {
+        let strategy = crate::strategy::from_enum(&crate::strategy::DeckStrategy::Aluren);
+        let deck = Deck::new(&strategy.default_decklist()).unwrap();
+
+        assert_eq!(vec!["Intuition"], deck.tutors_for("Aluren"));
+    }
+
+    #[test]
+    fn it_draws_the_cards_put_on_top_in_order() {
+        let mut deck = "2 Forest".parse::<Deck>().unwrap();
+        let a = Card::new_with_zone("Island", Zone::Library);
+        let b = Card::new_with_zone("Swamp", Zone::Library);
+
+        deck.put_top_ordered(vec![a.clone(), b.clone()]);
+
+        assert!(Rc::ptr_eq(&a, &deck.draw().unwrap()));
+        assert!(Rc::ptr_eq(&b, &deck.draw().unwrap()));
+        assert_eq!("Forest", deck.draw().unwrap().borrow().name);
+    }
 }
@@ -1,29 +1,53 @@
 use log::{warn};
-use std::rc::Rc;
+use std::collections::HashSet;
 
-use crate::card::{CardRef, CardType, SearchFilter, Zone};
-use crate::game::Game;
+use crate::card::{same_card, CardRef, CardType, SearchFilter, Zone};
+use crate::game::{Game, Phase};
+use crate::mana::{Mana, PaymentAndFloating};
 use crate::strategy::Strategy;
 use crate::utils::*;
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum Effect {
     Mill(usize),
     Draw(usize),
+    /// Draws X cards, with the X the spell was cast with
+    DrawX,
+    /// Draws the first amount of cards, then discards the second amount
+    Loot(usize, usize),
     UntapLands(Option<usize>),
     DamageEach(i32),
     SearchAndPutHand(Option<SearchFilter>),
     SearchAndPutTopOfLibrary(Option<SearchFilter>),
     SearchAndPutBattlefield(Option<SearchFilter>),
+    /// Searches for up to the amount of cards and puts them into the graveyard, like Entomb
+    SearchAndPutGraveyard(Option<SearchFilter>, usize),
     Impulse(usize),
+    /// Looks at the amount of cards, keeping the wanted ones on top and putting the rest on the bottom
+    Scry(usize),
+    /// Looks at the amount of cards, keeping the wanted ones on top and putting the rest into the graveyard
+    Surveil(usize),
+    /// Reveals the amount of cards, casts the first nonland card matching the filter
+    /// without paying its mana cost and puts the rest on the bottom of the library
+    DigAndCast(usize, Option<SearchFilter>),
     Intuition,
     CavernHarpy,
+    BodySnatcher,
+    IridescentDrake,
+    KarmicGuide,
+    /// Creates a 2/2 Bear token for each creature that died this turn
+    CallerOfTheClaw,
+    /// Returns up to two creatures with power 2 or less from the graveyard to the battlefield
+    Reveillark,
     Unearth,
     WordsOfWisdom,
     Snap,
     FranticSearch,
     BrainFreeze,
     Meditate,
+    /// Takes the given amount of extra turns after this one
+    ExtraTurn(usize),
     Brainstorm,
     Ponder,
     Preordain,
@@ -39,9 +63,25 @@ impl Effect {
             Effect::SearchAndPutTopOfLibrary(search_filter) => {
                 self.search_top_of_library(game, source, strategy, search_filter)
             },
+            Effect::SearchAndPutBattlefield(search_filter) => {
+                self.search_battlefield(game, source, strategy, search_filter)
+            },
+            Effect::SearchAndPutGraveyard(search_filter, amount) => {
+                self.search_graveyard(game, source, strategy, search_filter, *amount)
+            },
             Effect::Impulse(amount) => self.impulse(game, source, strategy, *amount),
+            Effect::Scry(amount) => self.scry(game, source, strategy, *amount, Zone::Library),
+            Effect::Surveil(amount) => self.scry(game, source, strategy, *amount, Zone::Graveyard),
+            Effect::DigAndCast(amount, search_filter) => {
+                self.dig_and_cast(game, source, strategy, *amount, search_filter)
+            },
             Effect::Intuition => self.intuition(game, source, strategy),
             Effect::CavernHarpy => self.cavern_harpy(game, source, strategy),
+            Effect::BodySnatcher => self.body_snatcher(game, source, strategy),
+            Effect::IridescentDrake => self.iridescent_drake(game, source, strategy),
+            Effect::KarmicGuide => self.karmic_guide(game, source, strategy),
+            Effect::Reveillark => self.reveillark(game, source, strategy),
+            Effect::CallerOfTheClaw => self.caller_of_the_claw(game, source, strategy),
             Effect::Unearth => self.unearth(game, source, strategy),
             Effect::UntapLands(amount) => self.untap_lands(game, source, strategy, *amount),
             Effect::DamageEach(amount) => self.damage_each(game, source, strategy, *amount),
@@ -54,58 +94,58 @@ impl Effect {
                 let cloud_of_faeries_to_return = game.game_objects.iter().find(|card| {
                     let card = card.borrow();
                     card.zone == Zone::Battlefield && card.name == "Cloud of Faeries"
-                });
+                }).cloned();
         
                 if let Some(card) = cloud_of_faeries_to_return {
-                    game.log(format!(
-                        "[Turn {turn:002}][Action]: Bouncing \"Cloud of Faeries\" back to hand.",
-                        turn = game.turn
-                    ));
-                    card.borrow_mut().zone = Zone::Hand;
+                    game.log(Phase::Action, "Bouncing \"Cloud of Faeries\" back to hand.".to_owned());
+                    game.move_to_zone(&card, Zone::Hand);
                 }
 
                 self.untap_lands(game, source, strategy, Some(2));
             },
+            Effect::Loot(draw, discard) => game.loot(strategy, *draw, *discard),
             Effect::FranticSearch => {
-                let hand_size = game.game_objects.iter().filter(is_hand).count();
-                game.draw_n(2);
-                let cards_to_discard = strategy.discard_to_hand_size(game, hand_size);
-                for card in cards_to_discard {
-                    game.discard(card);
-                }
+                game.loot(strategy, 2, 2);
                 self.untap_lands(game, source, strategy, Some(3));
             },
             Effect::Meditate => {
                 game.draw_n(4);
                 game.turns_to_skip += 1;
             },
+            Effect::ExtraTurn(amount) => {
+                game.extra_turns += amount;
+            },
             Effect::Mill(amount) => {
                 game.opponent_library -= *amount as i32;
             },
             Effect::Draw(amount) => {
                 game.draw_n(*amount);
             },
+            Effect::DrawX => {
+                let x = source.borrow().x as usize;
+                game.draw_n(x);
+            },
             Effect::BrainFreeze => {
                 // TODO: Make this target
                 let cards_to_mill = 3 * game.storm as i32;
 
-                game.log(format!(
-                    "[Turn {turn:002}][Action]: Brain Freeze with Storm {storm}: milling opponent for {cards_to_mill}",
-                    turn = game.turn,
+                game.log(Phase::Action, format!(
+                    "Brain Freeze with Storm {storm}: milling opponent for {cards_to_mill}",
                     storm = game.storm,
                 ));
 
                 game.opponent_library -= cards_to_mill;
             },
             Effect::Brainstorm => {
-                // At this time the card is on graveyard already
-                let hand_size = game.game_objects.iter().filter(is_hand).count();
                 game.draw_n(3);
-                let cards_to_discard = strategy.discard_to_hand_size(game, hand_size + 1);
-                for card in cards_to_discard {
-                    card.borrow_mut().zone = Zone::Library;
-                    game.deck.put_top(card);
+                let cards_to_put_back = game.choose_discard(strategy, 2);
+                for card in cards_to_put_back.iter() {
+                    game.move_to_zone(card, Zone::Library);
                 }
+
+                // Draw the better one of the cards put back first
+                let ordered = self.order_by_preference(game, strategy, cards_to_put_back);
+                game.deck.put_top_ordered(ordered);
             },
             Effect::Ponder => {
                 // TODO: actual ponder
@@ -115,7 +155,6 @@ impl Effect {
                 // TODO: actual Preordain
                 self.impulse(game, source, strategy, 2)
             },
-            _ => unimplemented!(),
         }
     }
 
@@ -127,17 +166,114 @@ impl Effect {
         search_filter: &Option<SearchFilter>,
     ) {
         let searchable = apply_search_filter(game, search_filter);
-        if let Some(found) = strategy.select_best(game, group_by_name(searchable)) {
-            game.log(format!("[Turn {turn:002}][Action]: Searched for \"{card_name}\" and put it on top of the library.",
-                turn = game.turn,
+        if let Some(found) = game.select_best(strategy, group_by_name(searchable)) {
+            game.log(Phase::Action, format!("Searched for \"{card_name}\" and put it on top of the library.",
                 card_name = found.borrow().name));
 
             game.deck.remove(&found);
-            game.deck.shuffle();
+            game.deck.shuffle_with(&mut game.shuffle_rng);
             game.deck.put_top(found);
         }
     }
 
+    fn search_battlefield(
+        &self,
+        game: &mut Game,
+        _source: &CardRef,
+        strategy: &impl Strategy,
+        search_filter: &Option<SearchFilter>,
+    ) {
+        let searchable = apply_search_filter(game, search_filter);
+        if let Some(found) = game.select_best(strategy, group_by_name(searchable)) {
+            game.log(Phase::Action, format!("Searched for \"{card_name}\" and put it on the battlefield.",
+                card_name = found.borrow().name));
+
+            game.deck.remove(&found);
+            game.move_to_zone(&found, Zone::Battlefield);
+            game.deck.shuffle_with(&mut game.shuffle_rng);
+
+            // Including the creature lands like Dryad Arbor, which can't be tapped for mana this turn
+            if is_card_type(&&found, &CardType::Creature) {
+                let is_haste = found.borrow().is_haste;
+                found.borrow_mut().is_summoning_sick = !is_haste;
+            }
+        } else {
+            game.log(Phase::Action, "Failed to find.".to_owned());
+        }
+    }
+
+    fn search_graveyard(
+        &self,
+        game: &mut Game,
+        _source: &CardRef,
+        strategy: &impl Strategy,
+        search_filter: &Option<SearchFilter>,
+        amount: usize,
+    ) {
+        for _ in 0..amount {
+            // The cards already put into the graveyard are no longer searchable
+            let searchable = apply_search_filter(game, search_filter);
+            match game.select_best(strategy, group_by_name(searchable)) {
+                Some(found) => {
+                    game.log(Phase::Action, format!("Searched for \"{card_name}\" and put it into the graveyard.",
+                        card_name = found.borrow().name));
+
+                    game.deck.remove(&found);
+                    game.move_to_zone(&found, Zone::Graveyard);
+                }
+                None => {
+                    game.log(Phase::Action, "Failed to find.".to_owned());
+                    break;
+                }
+            }
+        }
+
+        game.deck.shuffle_with(&mut game.shuffle_rng);
+    }
+
+    fn dig_and_cast(
+        &self,
+        game: &mut Game,
+        _source: &CardRef,
+        strategy: &impl Strategy,
+        amount_to_reveal: usize,
+        search_filter: &Option<SearchFilter>,
+    ) {
+        let mut cards = Vec::with_capacity(amount_to_reveal);
+        for _ in 0..amount_to_reveal {
+            if let Some(card) = game.deck.draw() {
+                cards.push(card);
+            }
+        }
+
+        let revealed_str = cards
+            .iter()
+            .map(|card| format!("\"{}\"", card.borrow().name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        game.log(Phase::Action, format!("Revealing cards: {revealed_str}"));
+
+        let free_spell = cards
+            .iter()
+            .find(|card| !is_card_type(card, &CardType::Land) && matches_search_filter(card, search_filter))
+            .cloned();
+
+        for card in cards {
+            if free_spell.as_ref().is_none_or(|spell| !same_card(spell, &card)) {
+                game.deck.put_bottom(card);
+            }
+        }
+
+        if let Some(spell) = free_spell {
+            // Casting without paying the mana cost leaves the floating mana as it was
+            let payment = PaymentAndFloating {
+                payment: Vec::new(),
+                floating: game.floating_mana.clone(),
+            };
+            game.cast_spell(strategy, &spell, &payment, None);
+        }
+    }
+
     fn search_hand(
         &self,
         game: &mut Game,
@@ -146,35 +282,30 @@ impl Effect {
         search_filter: &Option<SearchFilter>,
     ) {
         let searchable = apply_search_filter(game, search_filter);
-        if let Some(found) = strategy.select_best(game, group_by_name(searchable)) {
+        if let Some(found) = game.select_best(strategy, group_by_name(searchable)) {
             if let Some(SearchFilter::Wish(_card_types)) = search_filter {
-                game.log(format!("[Turn {turn:002}][Action]: Searched for \"{card_name}\" from sideboard and put it in hand.",
-                            turn = game.turn,
+                game.log(Phase::Action, format!("Searched for \"{card_name}\" from sideboard and put it in hand.",
                             card_name = found.borrow().name));
 
                 game.deck.remove_sideboard(&found);
-                found.borrow_mut().zone = Zone::Hand;
+                game.move_to_zone(&found, Zone::Hand);
                 game.game_objects.push(found);
             } else {
-                game.log(format!(
-                    "[Turn {turn:002}][Action]: Searched for \"{card_name}\" and put it in hand.",
-                    turn = game.turn,
+                game.log(Phase::Action, format!(
+                    "Searched for \"{card_name}\" and put it in hand.",
                     card_name = found.borrow().name
                 ));
 
                 game.deck.remove(&found);
-                found.borrow_mut().zone = Zone::Hand;
-                game.deck.shuffle();
+                game.move_to_zone(&found, Zone::Hand);
+                game.deck.shuffle_with(&mut game.shuffle_rng);
             }
         } else {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Failed to find.",
-                turn = game.turn
-            ));
+            game.log(Phase::Action, "Failed to find.".to_owned());
         }
 
         if let Some(SearchFilter::Wish(_card_types)) = search_filter {
-            source.borrow_mut().zone = Zone::Exile;
+            game.move_to_zone(source, Zone::Exile);
         }
     }
 
@@ -206,20 +337,18 @@ impl Effect {
             .map(|card| format!("\"{}\"", card.borrow().name.clone()))
             .collect::<Vec<_>>()
             .join(", ");
-        game.log(format!(
-            "[Turn {turn:002}][Action]: Looking at cards: {revealed_str}",
-            turn = game.turn
+        game.log(Phase::Action, format!(
+            "Looking at cards: {revealed_str}"
         ));
 
-        if let Some(selected) = strategy.select_best(game, group_by_name(cards.clone())) {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Selected \"{card_name}\" and put it in hand.",
-                turn = game.turn,
+        if let Some(selected) = game.select_best(strategy, group_by_name(cards.clone())) {
+            game.log(Phase::Action, format!(
+                "Selected \"{card_name}\" and put it in hand.",
                 card_name = selected.borrow().name
             ));
-            cards.retain(|card| !Rc::ptr_eq(card, &selected));
+            cards.retain(|card| !same_card(card, &selected));
 
-            selected.borrow_mut().zone = Zone::Hand;
+            game.move_to_zone(&selected, Zone::Hand);
         }
 
         for card in cards {
@@ -227,6 +356,66 @@ impl Effect {
         }
     }
 
+    /// Orders the cards from the most wanted to the least wanted by the strategy
+    fn order_by_preference(&self, game: &Game, strategy: &impl Strategy, mut cards: Vec<CardRef>) -> Vec<CardRef> {
+        let mut ordered = Vec::with_capacity(cards.len());
+
+        while !cards.is_empty() {
+            match game.select_best(strategy, group_by_name(cards.clone())) {
+                Some(best) if cards.iter().any(|card| same_card(card, &best)) => {
+                    cards.retain(|card| !same_card(card, &best));
+                    ordered.push(best);
+                }
+                _ => break,
+            }
+        }
+
+        // Keep any cards the strategy didn't want at all in their current order
+        ordered.extend(cards);
+        ordered
+    }
+
+    fn scry(
+        &self,
+        game: &mut Game,
+        _source: &CardRef,
+        strategy: &impl Strategy,
+        amount_to_look_at: usize,
+        unwanted_zone: Zone,
+    ) {
+        let mut cards = Vec::with_capacity(amount_to_look_at);
+        for _ in 0..amount_to_look_at {
+            if let Some(card) = game.deck.draw() {
+                cards.push(card);
+            }
+        }
+
+        let revealed_str = cards
+            .iter()
+            .map(|card| format!("\"{}\"", card.borrow().name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        game.log(Phase::Action, format!("Looking at cards: {revealed_str}"));
+
+        let kept = game.choose_scry(strategy, cards.clone());
+
+        for card in cards {
+            if kept.iter().any(|kept| same_card(kept, &card)) {
+                continue;
+            }
+
+            if unwanted_zone == Zone::Graveyard {
+                game.log(Phase::Action, format!("Put \"{card_name}\" to graveyard.", card_name = card.borrow().name));
+                game.move_to_zone(&card, Zone::Graveyard);
+            } else {
+                game.log(Phase::Action, format!("Put \"{card_name}\" on the bottom.", card_name = card.borrow().name));
+                game.deck.put_bottom(card);
+            }
+        }
+
+        game.deck.put_top_ordered(kept);
+    }
+
     fn reanimate(
         &self,
         game: &mut Game,
@@ -234,13 +423,16 @@ impl Effect {
         strategy: &impl Strategy,
         possible_targets: Vec<CardRef>,
     ) {
-        if let Some(target) = strategy.select_best(game, group_by_name(possible_targets)) {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Returning \"{card_name}\" on the battlefield.",
-                turn = game.turn,
+        if let Some(target) = game.select_best(strategy, group_by_name(possible_targets)) {
+            game.log(Phase::Action, format!(
+                "Returning \"{card_name}\" on the battlefield.",
                 card_name = target.borrow().name
             ));
-            target.borrow_mut().zone = Zone::Battlefield;
+            game.move_to_zone(&target, Zone::Battlefield);
+
+            let is_haste = target.borrow().is_haste;
+            target.borrow_mut().is_summoning_sick = !is_haste;
+
             game.handle_on_resolve_effects(&target, strategy)
         }
     }
@@ -253,7 +445,7 @@ impl Effect {
                 let card = card.borrow();
                 card.zone == Zone::Graveyard
                     && card.card_types.contains(&CardType::Creature)
-                    && card.cost.values().sum::<i32>() <= 3
+                    && card.mana_value() <= 3
             })
             .cloned()
             .collect();
@@ -268,38 +460,53 @@ impl Effect {
         _strategy: &impl Strategy,
         lands_to_untap: Option<usize>,
     ) {
-        let untap = 
-            match lands_to_untap {
-                Some(count) => count,
-                None => {
-                    // Untap all lands
-                    game
-                        .game_objects
-                        .iter()
-                        .filter(|card| {
-                            is_battlefield(card) && is_card_type(card, &CardType::Land) && is_tapped(card)
-                        })
-                        .count()
-                }
-            };
+        // Lands with no uses left can't produce mana anymore, so untapping them would refund nothing
+        let is_untappable = |card: &&CardRef| {
+            is_battlefield(card)
+                && is_card_type(card, &CardType::Land)
+                && is_tapped(card)
+                && card.borrow().remaining_uses() != Some(0)
+        };
+
+        let untap = match lands_to_untap {
+            Some(count) => count,
+            // Untap all lands
+            None => game.game_objects.iter().filter(is_untappable).count(),
+        };
+
+        // Prefer the lands producing the colors the spells in hand still need
+        let needed_mana = game
+            .hand()
+            .flat_map(|card| card.borrow().cost.keys().cloned().collect::<Vec<_>>())
+            .filter(|mana| *mana != Mana::Generic)
+            .collect::<HashSet<_>>();
+
+        let useful_mana = |card: &CardRef| {
+            card.borrow()
+                .produced_mana
+                .keys()
+                .filter(|mana| needed_mana.contains(mana))
+                .count()
+        };
 
         for _ in 0..untap {
             let mut tapped_lands = game
                 .game_objects
                 .iter()
-                .filter(|card| {
-                    is_battlefield(card) && is_card_type(card, &CardType::Land) && is_tapped(card)
-                })
+                .filter(is_untappable)
                 .cloned()
                 .collect::<Vec<_>>();
 
-            tapped_lands.sort_by(sort_by_best_mana_to_play);
+            tapped_lands.sort_by(|a, b| {
+                useful_mana(a)
+                    .cmp(&useful_mana(b))
+                    .then_with(|| sort_by_best_mana_to_play(a, b))
+            });
 
             if let Some(card) = tapped_lands.last() {
-                game.log(format!(
-                    "[Turn {turn:002}][Action]: Untapping \"{card_name}\".",
-                    card_name = card.borrow().name,
-                    turn = game.turn
+                game.log(Phase::Action, format!(
+                    "Untapping \"{card_name}\".",
+                    card_name = card.borrow().name
                 ));
                 card.borrow_mut().is_tapped = false;
             }
@@ -310,14 +517,11 @@ impl Effect {
         let maggot_carrier_to_return = game.game_objects.iter().find(|card| {
             let card = card.borrow();
             card.zone == Zone::Battlefield && card.name == "Maggot Carrier"
-        });
+        }).cloned();
 
         if let Some(card) = maggot_carrier_to_return {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Bouncing \"Maggot Carrier\" back to hand.",
-                turn = game.turn
-            ));
-            card.borrow_mut().zone = Zone::Hand;
+            game.log(Phase::Action, "Bouncing \"Maggot Carrier\" back to hand.".to_owned());
+            game.move_to_zone(&card, Zone::Hand);
             return;
         }
 
@@ -330,49 +534,125 @@ impl Effect {
             })
             .count();
 
-        if etb_draw_triggers > 0 && game.deck.len() > 1 {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Bouncing \"Cavern Harpy\" back to hand.",
-                turn = game.turn
-            ));
-            source.borrow_mut().zone = Zone::Hand;
+        if etb_draw_triggers > 0 && game.can_safely_draw(1) {
+            game.log(Phase::Action, "Bouncing \"Cavern Harpy\" back to hand.".to_owned());
+            game.move_to_zone(source, Zone::Hand);
             return;
         }
 
         let cloud_of_faeries_to_return = game.game_objects.iter().find(|card| {
             let card = card.borrow();
             card.zone == Zone::Battlefield && card.name == "Cloud of Faeries"
-        });
+        }).cloned();
 
         if let Some(card) = cloud_of_faeries_to_return {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Bouncing \"Cloud of Faeries\" back to hand.",
-                turn = game.turn
-            ));
-            card.borrow_mut().zone = Zone::Hand;
+            game.log(Phase::Action, "Bouncing \"Cloud of Faeries\" back to hand.".to_owned());
+            game.move_to_zone(&card, Zone::Hand);
             return;
         }
 
         let raven_familiar_to_return = game.game_objects.iter().find(|card| {
             let card = card.borrow();
             card.zone == Zone::Battlefield && card.name == "Raven Familiar"
-        });
+        }).cloned();
 
         if let Some(card) = raven_familiar_to_return {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Bouncing \"Raven Familiar\" back to hand.",
-                turn = game.turn
-            ));
-            card.borrow_mut().zone = Zone::Hand;
+            game.log(Phase::Action, "Bouncing \"Raven Familiar\" back to hand.".to_owned());
+            game.move_to_zone(&card, Zone::Hand);
             return;
         }
 
         // Otherwise we must bounce the Harpy back to hand
-        game.log(format!(
-            "[Turn {turn:002}][Action]: Bouncing \"Cavern Harpy\" back to hand.",
-            turn = game.turn
-        ));
-        source.borrow_mut().zone = Zone::Hand;
+        game.log(Phase::Action, "Bouncing \"Cavern Harpy\" back to hand.".to_owned());
+        game.move_to_zone(source, Zone::Hand);
+    }
+
+    fn body_snatcher(&self, game: &mut Game, source: &CardRef, strategy: &impl Strategy) {
+        let creatures_in_hand = game
+            .game_objects
+            .iter()
+            .filter(|card| {
+                is_hand(card) && is_card_type(card, &CardType::Creature) && !same_card(card, source)
+            })
+            .cloned()
+            .collect();
+
+        if let Some(discarded) = game.select_best(strategy, group_by_name(creatures_in_hand)) {
+            game.discard(discarded);
+
+            let possible_targets = game
+                .game_objects
+                .iter()
+                .filter(|card| {
+                    is_graveyard(card) && is_card_type(card, &CardType::Creature) && !same_card(card, source)
+                })
+                .cloned()
+                .collect();
+
+            self.reanimate(game, source, strategy, possible_targets);
+        } else {
+            // Exile Body Snatcher unless we discard a creature card
+            game.log(Phase::Action, "Exiling \"Body Snatcher\" without a creature card to discard.".to_owned());
+            game.move_to_zone(source, Zone::Exile);
+        }
+    }
+
+    fn iridescent_drake(&self, game: &mut Game, source: &CardRef, _strategy: &impl Strategy) {
+        let pattern_of_rebirth = game
+            .game_objects
+            .iter()
+            .find(|card| is_graveyard(card) && is_pattern(card))
+            .cloned();
+
+        if let Some(pattern) = pattern_of_rebirth {
+            game.log(Phase::Action, "Returning \"Pattern of Rebirth\" on the battlefield attached to \"Iridescent Drake\".".to_owned());
+            game.move_to_zone(&pattern, Zone::Battlefield);
+            pattern.borrow_mut().attached_to = Some(source.clone());
+        }
+    }
+
+    fn karmic_guide(&self, game: &mut Game, source: &CardRef, strategy: &impl Strategy) {
+        let possible_targets = game
+            .game_objects
+            .iter()
+            .filter(|card| {
+                is_graveyard(card) && is_card_type(card, &CardType::Creature) && !same_card(card, source)
+            })
+            .cloned()
+            .collect();
+
+        self.reanimate(game, source, strategy, possible_targets);
+    }
+
+    fn reveillark(&self, game: &mut Game, source: &CardRef, strategy: &impl Strategy) {
+        for _ in 0..2 {
+            let possible_targets: Vec<_> = game
+                .game_objects
+                .iter()
+                .filter(|card| {
+                    is_graveyard(card)
+                        && is_card_type(card, &CardType::Creature)
+                        && card.borrow().power <= 2
+                        && !same_card(card, source)
+                })
+                .cloned()
+                .collect();
+
+            if possible_targets.is_empty() {
+                break;
+            }
+
+            self.reanimate(game, source, strategy, possible_targets);
+        }
+    }
+
+    fn caller_of_the_claw(&self, game: &mut Game, _source: &CardRef, _strategy: &impl Strategy) {
+        let bears = game.creatures_died_this_turn.len();
+        game.log(Phase::Action, format!("Creating {bears} \"Bear\" tokens."));
+
+        for _ in 0..bears {
+            game.create_token("Bear", 2, 2, HashSet::from([CardType::Creature]));
+        }
     }
 
     fn damage_each(
@@ -386,38 +666,506 @@ impl Effect {
     }
 
     fn intuition(&self, game: &mut Game, _source: &CardRef, strategy: &impl Strategy) {
-        let mut found = strategy.select_intuition(game);
+        let mut found = game.select_intuition(strategy);
         let found_str = found
             .iter()
             .map(|card| format!("\"{}\"", card.borrow().name))
             .collect::<Vec<_>>()
             .join(", ");
 
-        game.log(format!(
-            "[Turn {turn:002}][Action]: Searched for cards: {found_str} with Intuition.",
-            turn = game.turn
+        game.log(Phase::Action, format!(
+            "Searched for cards: {found_str} with Intuition."
         ));
 
         if let Some(card) = found.pop() {
             game.deck.remove(&card);
-            card.borrow_mut().zone = Zone::Hand;
+            game.move_to_zone(&card, Zone::Hand);
 
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Put \"{card_name}\" to hand.",
-                card_name = card.borrow().name,
-                turn = game.turn
+            game.log(Phase::Action, format!(
+                "Put \"{card_name}\" to hand.",
+                card_name = card.borrow().name
             ));
         }
 
         for card in found.into_iter() {
             game.deck.remove(&card);
-            card.borrow_mut().zone = Zone::Graveyard;
+            game.move_to_zone(&card, Zone::Graveyard);
 
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Put \"{card_name}\" to graveyard.",
-                card_name = card.borrow().name,
-                turn = game.turn
+            game.log(Phase::Action, format!(
+                "Put \"{card_name}\" to graveyard.",
+                card_name = card.borrow().name
             ));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+    use crate::deck::{Deck, Decklist};
+    use crate::strategy::pattern_combo::PatternCombo;
+    use rand_chacha::ChaCha12Rng;
+    use rand::SeedableRng;
+    use std::collections::{BTreeMap, HashMap, VecDeque};
+
+    #[test]
+    fn it_exiles_body_snatcher_without_creature_to_discard() {
+        let body_snatcher = Card::new_with_zone("Body Snatcher", Zone::Battlefield);
+
+        let mut game = Game {
+            game_objects: vec![body_snatcher.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        Effect::BodySnatcher.resolve(&mut game, &body_snatcher, &PatternCombo {});
+
+        assert_eq!(Zone::Exile, body_snatcher.borrow().zone);
+    }
+
+    #[test]
+    fn it_reanimates_creature_discarded_to_body_snatcher() {
+        let body_snatcher = Card::new_with_zone("Body Snatcher", Zone::Battlefield);
+        let protean_hulk = Card::new_with_zone("Protean Hulk", Zone::Hand);
+
+        let mut game = Game {
+            game_objects: vec![body_snatcher.clone(), protean_hulk.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        Effect::BodySnatcher.resolve(&mut game, &body_snatcher, &PatternCombo {});
+
+        assert_eq!(Zone::Battlefield, body_snatcher.borrow().zone);
+        assert_eq!(Zone::Battlefield, protean_hulk.borrow().zone);
+    }
+
+    #[test]
+    fn it_attaches_graveyard_pattern_of_rebirth_to_iridescent_drake() {
+        let iridescent_drake = Card::new_with_zone("Iridescent Drake", Zone::Battlefield);
+        let pattern_of_rebirth = Card::new_with_zone("Pattern of Rebirth", Zone::Graveyard);
+
+        let mut game = Game {
+            game_objects: vec![iridescent_drake.clone(), pattern_of_rebirth.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        Effect::IridescentDrake.resolve(&mut game, &iridescent_drake, &PatternCombo {});
+
+        assert_eq!(Zone::Battlefield, pattern_of_rebirth.borrow().zone);
+        let attached_to = pattern_of_rebirth.borrow().attached_to.clone();
+        assert!(attached_to.is_some_and(|target| same_card(&target, &iridescent_drake)));
+    }
+
+    #[test]
+    fn it_reanimates_creature_with_karmic_guide() {
+        let karmic_guide = Card::new_with_zone("Karmic Guide", Zone::Battlefield);
+        let akroma = Card::new_with_zone("Akroma, Angel of Wrath", Zone::Graveyard);
+
+        let mut game = Game {
+            game_objects: vec![karmic_guide.clone(), akroma.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        Effect::KarmicGuide.resolve(&mut game, &karmic_guide, &PatternCombo {});
+
+        assert_eq!(Zone::Battlefield, akroma.borrow().zone);
+    }
+
+    #[test]
+    fn it_leaves_creatures_with_power_over_two_in_the_graveyard_with_reveillark() {
+        let reveillark = Card::new_with_zone("Reveillark", Zone::Graveyard);
+        let veteran_explorer = Card::new_with_zone("Veteran Explorer", Zone::Graveyard);
+        let phantom_nishoba = Card::new_with_zone("Phantom Nishoba", Zone::Graveyard);
+
+        let mut game = Game {
+            game_objects: vec![reveillark.clone(), veteran_explorer.clone(), phantom_nishoba.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        Effect::Reveillark.resolve(&mut game, &reveillark, &PatternCombo {});
+
+        assert_eq!(Zone::Battlefield, veteran_explorer.borrow().zone);
+        assert_eq!(Zone::Graveyard, phantom_nishoba.borrow().zone);
+        assert_eq!(Zone::Graveyard, reveillark.borrow().zone);
+    }
+
+    #[test]
+    fn it_creates_a_bear_for_each_creature_that_died_this_turn_with_caller_of_the_claw() {
+        let caller_of_the_claw = Card::new_with_zone("Caller of the Claw", Zone::Battlefield);
+        let creatures = vec![
+            Card::new_with_zone("Veteran Explorer", Zone::Battlefield),
+            Card::new_with_zone("Academy Rector", Zone::Battlefield),
+        ];
+
+        let mut game = Game {
+            game_objects: [vec![caller_of_the_claw.clone()], creatures.clone()].concat(),
+            life_total: 20,
+            ..Default::default()
+        };
+
+        for creature in creatures.iter() {
+            game.sacrifice(creature, &PatternCombo {});
+        }
+
+        Effect::CallerOfTheClaw.resolve(&mut game, &caller_of_the_claw, &PatternCombo {});
+
+        assert_eq!(2, game.creatures_died_this_turn.len());
+        assert_eq!(2, game.count_in_zone("Bear", Zone::Battlefield));
+    }
+
+    #[test]
+    fn it_loots_keeping_hand_size() {
+        let source = Card::new_with_zone("Frantic Search", Zone::Graveyard);
+        let hand = vec![
+            Card::new_with_zone("Forest", Zone::Hand),
+            Card::new_with_zone("Island", Zone::Hand),
+        ];
+        let library = vec![Card::new_with_zone("Swamp", Zone::Library)];
+
+        let mut game = Game {
+            game_objects: [vec![source.clone()], hand, library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            turn: 2,
+            life_total: 20,
+            ..Default::default()
+        };
+
+        Effect::Loot(1, 1).resolve(&mut game, &source, &PatternCombo {});
+
+        assert_eq!(2, game.game_objects.iter().filter(is_hand).count());
+        assert_eq!(2, game.game_objects.iter().filter(is_graveyard).count());
+        assert_eq!(0, game.deck.len());
+    }
+
+    #[test]
+    fn it_puts_tutored_card_on_top_of_the_library_after_shuffling() {
+        let source = Card::new_with_zone("Worldly Tutor", Zone::Graveyard);
+        let cavern_harpy = Card::new_with_zone("Cavern Harpy", Zone::Library);
+        let library = [
+            vec![cavern_harpy.clone()],
+            (0..20).map(|_| Card::new_with_zone("Swamp", Zone::Library)).collect(),
+        ]
+        .concat();
+
+        for seed in 0..10 {
+            let mut game = Game {
+                game_objects: [vec![source.clone()], library.clone()].concat(),
+                deck: Deck {
+                    maindeck: VecDeque::from(library.clone()),
+                    sideboard: vec![],
+                },
+                turn: 2,
+                life_total: 20,
+                rng: ChaCha12Rng::seed_from_u64(seed),
+                ..Default::default()
+            };
+
+            Effect::SearchAndPutTopOfLibrary(Some(SearchFilter::Creature)).resolve(&mut game, &source, &PatternCombo {});
+
+            game.draw();
+
+            assert_eq!(Zone::Hand, cavern_harpy.borrow().zone);
+            cavern_harpy.borrow_mut().zone = Zone::Library;
+        }
+    }
+
+    #[test]
+    fn it_casts_the_first_matching_revealed_card_for_free() {
+        let source = Card::new_with_zone("Impulse", Zone::Graveyard);
+        let island = Card::new_with_zone("Island", Zone::Library);
+        let llanowar_elves = Card::new_with_zone("Llanowar Elves", Zone::Library);
+        let forest = Card::new_with_zone("Forest", Zone::Library);
+        let library = vec![island.clone(), llanowar_elves.clone(), forest.clone()];
+
+        let mut game = Game {
+            game_objects: [vec![source.clone()], library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            floating_mana: HashMap::from([(Mana::Blue, 1)]),
+            turn: 2,
+            life_total: 20,
+            ..Default::default()
+        };
+
+        Effect::DigAndCast(3, Some(SearchFilter::Creature)).resolve(&mut game, &source, &PatternCombo {});
+
+        assert_eq!(Zone::Battlefield, llanowar_elves.borrow().zone);
+        assert_eq!(1, game.storm);
+        assert_eq!(Some(&1), game.floating_mana.get(&Mana::Blue));
+
+        assert_eq!(2, game.deck.len());
+        assert_eq!(Zone::Library, forest.borrow().zone);
+        assert_eq!(Zone::Library, island.borrow().zone);
+    }
+
+    #[test]
+    fn it_untaps_five_color_land_over_basic() {
+        let source = Card::new_with_zone("Snap", Zone::Graveyard);
+        let forest = Card::new_with_zone("Forest", Zone::Battlefield);
+        let city_of_brass = Card::new_with_zone("City of Brass", Zone::Battlefield);
+        forest.borrow_mut().is_tapped = true;
+        city_of_brass.borrow_mut().is_tapped = true;
+
+        let mut game = Game {
+            game_objects: vec![source.clone(), forest.clone(), city_of_brass.clone()],
+            ..Default::default()
+        };
+
+        Effect::UntapLands(Some(1)).resolve(&mut game, &source, &PatternCombo {});
+
+        assert!(!city_of_brass.borrow().is_tapped);
+        assert!(forest.borrow().is_tapped);
+    }
+
+    #[test]
+    fn it_untaps_lands_with_uses_left_producing_the_needed_colors() {
+        let source = Card::new_with_zone("Snap", Zone::Graveyard);
+        let llanowar_elves = Card::new_with_zone("Llanowar Elves", Zone::Hand);
+        let forest = Card::new_with_zone("Forest", Zone::Battlefield);
+        let island = Card::new_with_zone("Island", Zone::Battlefield);
+        let gemstone_mine = Card::new_with_zone("Gemstone Mine", Zone::Battlefield);
+        gemstone_mine.borrow_mut().charges = Some(0);
+        for land in [&forest, &island, &gemstone_mine] {
+            land.borrow_mut().is_tapped = true;
+        }
+
+        let mut game = Game {
+            game_objects: vec![source.clone(), llanowar_elves, forest.clone(), island.clone(), gemstone_mine.clone()],
+            ..Default::default()
+        };
+
+        Effect::UntapLands(Some(1)).resolve(&mut game, &source, &PatternCombo {});
+
+        assert!(!forest.borrow().is_tapped);
+        assert!(island.borrow().is_tapped);
+        assert!(gemstone_mine.borrow().is_tapped);
+
+        // Untapping every land still leaves the spent one tapped
+        Effect::UntapLands(None).resolve(&mut game, &source, &PatternCombo {});
+
+        assert!(!island.borrow().is_tapped);
+        assert!(gemstone_mine.borrow().is_tapped);
+    }
+
+    #[test]
+    fn it_resolves_intuition_with_fewer_cards_in_library() {
+        let source = Card::new_with_zone("Intuition", Zone::Graveyard);
+        let aluren = Card::new_with_zone("Aluren", Zone::Library);
+        let forest = Card::new_with_zone("Forest", Zone::Library);
+        let library = vec![aluren.clone(), forest.clone()];
+
+        let mut game = Game {
+            game_objects: [vec![source.clone()], library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            ..Default::default()
+        };
+
+        Effect::Intuition.resolve(&mut game, &source, &PatternCombo {});
+
+        assert!(game.deck.is_empty());
+        let zones = [aluren.borrow().zone.clone(), forest.borrow().zone.clone()];
+        assert!(zones.contains(&Zone::Hand));
+        assert!(zones.contains(&Zone::Graveyard));
+    }
+
+    #[test]
+    fn it_looks_at_the_stacked_cards_with_impulse() {
+        let decklist = "4 Forest\n4 Island\n4 Llanowar Elves\n4 Brainstorm\n".parse().unwrap();
+        let mut game = Game::with_seed(&decklist, 0).unwrap();
+        let source = Card::new_with_zone("Impulse", Zone::Graveyard);
+
+        let stacked = ["Llanowar Elves", "Island", "Brainstorm", "Island"];
+        game.deck.stack_top(&stacked);
+
+        let top = game.deck.peek_top(4);
+        assert_eq!(stacked.to_vec(), top.iter().map(|card| card.borrow().name.clone()).collect::<Vec<_>>());
+
+        Effect::Impulse(4).resolve(&mut game, &source, &PatternCombo {});
+
+        let output = game.output.lock().unwrap();
+        assert!(output.iter().any(|event| {
+            event.message == "Looking at cards: \"Llanowar Elves\", \"Island\", \"Brainstorm\", \"Island\""
+        }));
+        assert_eq!(1, top.iter().filter(is_hand).count());
+        assert_eq!(15, game.deck.len());
+    }
+
+    #[test]
+    fn it_searches_dryad_arbor_with_fetchlands_and_natural_order() {
+        for searcher in ["Wooded Foothills", "Natural Order"] {
+            let source = Card::new_with_zone(searcher, Zone::Graveyard);
+            let dryad_arbor = Card::new_with_zone("Dryad Arbor", Zone::Library);
+            let island = Card::new_with_zone("Island", Zone::Library);
+            let library = vec![dryad_arbor.clone(), island.clone()];
+
+            let mut game = Game {
+                game_objects: [vec![source.clone()], library.clone()].concat(),
+                deck: Deck {
+                    maindeck: VecDeque::from(library),
+                    sideboard: vec![],
+                },
+                ..Default::default()
+            };
+
+            let effect = source.borrow().on_resolve.clone().unwrap();
+            effect.resolve(&mut game, &source, &PatternCombo {});
+
+            assert_eq!(Zone::Battlefield, dryad_arbor.borrow().zone, "{searcher}");
+            assert!(dryad_arbor.borrow().is_summoning_sick);
+            assert_eq!(Zone::Library, island.borrow().zone);
+            assert_eq!(1, game.deck.len());
+        }
+    }
+
+    /// Wants the named cards in the given order over anything else
+    struct Wants(&'static [&'static str]);
+
+    impl Strategy for Wants {
+        fn name(&self) -> String {
+            format!("Wants {}", self.0.join(", "))
+        }
+
+        fn description(&self) -> &'static str {
+            "Picks the wanted cards first"
+        }
+
+        fn default_decklist(&self) -> Decklist {
+            Decklist { maindeck: vec![], sideboard: vec![] }
+        }
+
+        fn is_keepable_hand(&self, _game: &Game, _mulligan_count: usize) -> bool {
+            true
+        }
+
+        fn take_game_action(&mut self, _game: &mut Game) -> bool {
+            false
+        }
+
+        fn select_best(&self, _game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
+            self.0
+                .iter()
+                .find_map(|name| find_named(&cards, name))
+                .or_else(|| cards.into_values().flatten().next())
+        }
+    }
+
+    fn game_with_library(library: &[CardRef]) -> Game {
+        Game {
+            game_objects: library.to_vec(),
+            deck: Deck {
+                maindeck: VecDeque::from(library.to_vec()),
+                sideboard: vec![],
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_scries_the_unwanted_card_to_the_bottom() {
+        let source = Card::new_with_zone("Preordain", Zone::Graveyard);
+        let swamp = Card::new_with_zone("Swamp", Zone::Library);
+        let island = Card::new_with_zone("Island", Zone::Library);
+        let forest = Card::new_with_zone("Forest", Zone::Library);
+        // Forest on top, Island below it
+        let mut game = game_with_library(&[swamp.clone(), island.clone(), forest.clone()]);
+
+        Effect::Scry(2).resolve(&mut game, &source, &Wants(&["Island"]));
+
+        assert!(same_card(&island, &game.deck.peek_top(1)[0]));
+        assert!(same_card(&forest, game.deck.maindeck.front().unwrap()));
+        assert_eq!(3, game.deck.len());
+        assert_eq!(Zone::Library, forest.borrow().zone);
+    }
+
+    #[test]
+    fn it_surveils_the_unwanted_card_to_the_graveyard() {
+        let source = Card::new_with_zone("Preordain", Zone::Graveyard);
+        let island = Card::new_with_zone("Island", Zone::Library);
+        let forest = Card::new_with_zone("Forest", Zone::Library);
+        let mut game = game_with_library(&[island.clone(), forest.clone()]);
+
+        Effect::Surveil(2).resolve(&mut game, &source, &Wants(&["Island"]));
+
+        assert_eq!(1, game.deck.len());
+        assert!(same_card(&island, &game.deck.peek_top(1)[0]));
+        assert_eq!(Zone::Graveyard, forest.borrow().zone);
+    }
+
+    #[test]
+    fn it_entombs_the_chosen_card_and_shuffles_the_library() {
+        let source = Card::new_with_zone("Entomb", Zone::Graveyard);
+        let protean_hulk = Card::new_with_zone("Protean Hulk", Zone::Library);
+        let karmic_guide = Card::new_with_zone("Karmic Guide", Zone::Library);
+        let rest = ["Plains", "Island", "Swamp", "Mountain", "Forest"]
+            .map(|name| Card::new_with_zone(name, Zone::Library));
+        let library = [vec![protean_hulk.clone(), karmic_guide.clone()], rest.to_vec()].concat();
+        let mut game = game_with_library(&library);
+
+        let effect = source.borrow().on_resolve.clone().unwrap();
+        effect.resolve(&mut game, &source, &Wants(&["Protean Hulk"]));
+
+        assert_eq!(Zone::Graveyard, protean_hulk.borrow().zone);
+        assert_eq!(6, game.deck.len());
+        assert!(game.deck.maindeck.iter().all(|card| !same_card(card, &protean_hulk)));
+        assert!(!game.deck.maindeck.iter().zip(library[1..].iter()).all(|(a, b)| same_card(a, b)));
+    }
+
+    #[test]
+    fn it_buries_only_the_creatures_it_finds() {
+        let source = Card::new_with_zone("Buried Alive", Zone::Graveyard);
+        let protean_hulk = Card::new_with_zone("Protean Hulk", Zone::Library);
+        let karmic_guide = Card::new_with_zone("Karmic Guide", Zone::Library);
+        let forest = Card::new_with_zone("Forest", Zone::Library);
+        let mut game = game_with_library(&[protean_hulk.clone(), forest.clone(), karmic_guide.clone()]);
+
+        let effect = source.borrow().on_resolve.clone().unwrap();
+        effect.resolve(&mut game, &source, &Wants(&[]));
+
+        assert_eq!(Zone::Graveyard, protean_hulk.borrow().zone);
+        assert_eq!(Zone::Graveyard, karmic_guide.borrow().zone);
+        assert_eq!(Zone::Library, forest.borrow().zone);
+        assert_eq!(1, game.deck.len());
+    }
+
+    #[test]
+    fn it_brainstorms_keeping_the_best_card_and_putting_two_back_in_order() {
+        let source = Card::new_with_zone("Brainstorm", Zone::Graveyard);
+        let plains = Card::new_with_zone("Plains", Zone::Library);
+        let swamp = Card::new_with_zone("Swamp", Zone::Library);
+        let forest = Card::new_with_zone("Forest", Zone::Library);
+        let island = Card::new_with_zone("Island", Zone::Library);
+
+        let mut game = Game {
+            game_objects: vec![forest.clone(), swamp.clone(), island.clone(), plains.clone()],
+            deck: Deck {
+                // Island on top, Plains at the bottom
+                maindeck: VecDeque::from(vec![plains.clone(), swamp.clone(), forest.clone(), island.clone()]),
+                sideboard: vec![],
+            },
+            ..Default::default()
+        };
+
+        Effect::Brainstorm.resolve(&mut game, &source, &Wants(&["Island", "Swamp", "Forest"]));
+
+        assert_eq!(Zone::Hand, island.borrow().zone);
+        assert_eq!(1, game.hand().count());
+
+        let top = game.deck.peek_top(3);
+        assert!(same_card(&swamp, &top[0]));
+        assert!(same_card(&forest, &top[1]));
+        assert!(same_card(&plains, &top[2]));
+    }
+}
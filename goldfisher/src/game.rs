@@ -1,12 +1,20 @@
+use rand_chacha::ChaCha12Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{thread_rng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::rc::Rc;
 use std::sync::Mutex;
 
-use crate::card::{CardRef, CardType, CreatureType, SubType, Zone};
+use crate::card::{same_card, Card, CardRef, CardType, CreatureType, SubType, Zone};
 use crate::deck::{Deck, Decklist, ParseDeckError};
 use crate::mana::find_payment_for;
-use crate::mana::{Mana, PaymentAndFloating};
+use crate::mana::{DynamicMana, Mana, PaymentAndFloating};
+use crate::observer::{GameObserver, LoggingObserver};
+use crate::replay::{Decision, Replay};
 use crate::strategy::Strategy;
 use crate::utils::*;
 
@@ -22,15 +30,219 @@ pub enum GameStatus {
     Finished(Outcome),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    Info,
+    Game,
+    Action,
+    Strategy,
+    Hand,
+    Library,
+    Battlefield,
+    Graveyard,
+    Exile,
+}
+
+/// A single line of the game log, kept structured so that consumers can
+/// filter and format the output themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEvent {
+    pub turn: usize,
+    pub phase: Phase,
+    pub message: String,
+}
+
+impl fmt::Display for LogEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.phase {
+            Phase::Info => write!(f, "{}", self.message),
+            _ => write!(
+                f,
+                "[Turn {turn:002}][{phase:?}]: {message}",
+                turn = self.turn,
+                phase = self.phase,
+                message = self.message
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameResult {
     pub result: Outcome,
     pub mulligan_count: usize,
     pub turn: usize,
-    pub output: Vec<String>
+    pub final_life: i32,
+    pub damage_dealt: i32,
+    pub max_storm: usize,
+    /// Cards drawn after the opening hand, minus the cards discarded
+    pub net_cards_drawn: i32,
+    /// Likely reason for losing the game, `None` unless the game was lost
+    pub loss_reason: Option<LossReason>,
+    /// First turn the strategy had assembled its combo, `None` if it never did
+    pub first_winnable_turn: Option<usize>,
+    /// Cards left in the library at the end of each turn
+    pub library_size_by_turn: BTreeMap<usize, usize>,
+    pub output: Vec<LogEvent>
+}
+
+/// Likely reason for losing a game, classified from the end state of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LossReason {
+    /// Drew fewer lands than there were turns to play them on
+    ManaScrew,
+    /// Most of the cards drawn were lands
+    ManaFlood,
+    /// Had enough mana, but never assembled the combo
+    NoCombo,
+    /// Ran out of cards in the library
+    DeckedOut,
+    /// Won, but only after the success turn cap
+    TooSlow,
+}
+
+impl fmt::Display for LossReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LossReason::ManaScrew => write!(f, "mana screw"),
+            LossReason::ManaFlood => write!(f, "mana flood"),
+            LossReason::NoCombo => write!(f, "no combo"),
+            LossReason::DeckedOut => write!(f, "decked out"),
+            LossReason::TooSlow => write!(f, "a slow win"),
+        }
+    }
+}
+
+impl GameResult {
+    /// Turns taken to win after the combo was first assembled, `None` unless the game was won.
+    /// Measures how much slower the strategy executed the win than it could have.
+    pub fn win_delay(&self) -> Option<usize> {
+        match self.result {
+            Outcome::Win => self.first_winnable_turn.map(|turn| self.turn.saturating_sub(turn)),
+            Outcome::Lose | Outcome::Draw => None,
+        }
+    }
+
+    /// Renders the game log into the same lines as printed during the game.
+    pub fn output_lines(&self) -> Vec<String> {
+        self.output.iter().map(|event| event.to_string()).collect()
+    }
+}
+
+/// Result of a game with only the outcome and the turn set, the rest of the fields can be
+/// overridden with the struct update syntax
+pub fn game_result(result: Outcome, turn: usize) -> GameResult {
+    GameResult {
+        result,
+        mulligan_count: 0,
+        turn,
+        final_life: 20,
+        damage_dealt: 0,
+        max_storm: 0,
+        net_cards_drawn: 0,
+        loss_reason: None,
+        first_winnable_turn: None,
+        library_size_by_turn: BTreeMap::new(),
+        output: Vec::new(),
+    }
+}
+
+/// Settings for setting up a new game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameConfig {
+    /// Seed for the random choices made during the game, picked at random when not given
+    pub seed: Option<u64>,
+    /// Seed for only shuffling the library, keeping the rest of the game on `seed`. Defaults to `seed`
+    pub shuffle_seed: Option<u64>,
+    pub is_first_player: bool,
+    /// Flip a coin for each game to decide whether we are on the play, instead of using `is_first_player`
+    pub random_play_draw: bool,
+    pub life_total: i32,
+    pub opponent_library: i32,
+    /// Forward the game actions to the `log` crate as they happen
+    pub log_actions: bool,
+    /// Disruption the opponent applies during the game, if any
+    pub disruption: Option<DisruptionModel>,
+    /// How eagerly to mulligan the opening hands
+    pub mulligan_policy: MulliganPolicy,
+    /// Cards to leave in the library when drawing is optional, so that the turns can still be passed
+    pub min_cards_left_in_library: usize,
+    /// Last turn a win counts as a win on, the later wins are classified as losses
+    pub success_turn_cap: Option<usize>,
+}
+
+/// The kind of interaction a disrupting opponent uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Disruption {
+    /// Discards a random card from the hand, like Thoughtseize
+    Discard,
+    /// Counters the next spell cast, like Force of Will
+    Counter,
+}
+
+/// Opponent that disrupts the game once on the given turn, for stress testing the strategies
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisruptionModel {
+    pub turn: usize,
+    pub disruption: Disruption,
+    /// Turns the opponent kills us on with their weights, one of them picked for each game.
+    /// Without any kill turns the opponent never wins.
+    #[serde(default)]
+    pub kill_turns: Vec<(usize, u32)>,
+}
+
+impl DisruptionModel {
+    /// Picks the turn the opponent kills us on from the weighted kill turns
+    pub fn sample_kill_turn(&self, rng: &mut ChaCha12Rng) -> Option<usize> {
+        let weights = WeightedIndex::new(self.kill_turns.iter().map(|(_, weight)| *weight)).ok()?;
+        Some(self.kill_turns[weights.sample(rng)].0)
+    }
+}
+
+/// How eagerly the opening hands are mulliganed, on top of the keep decisions of the strategy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MulliganPolicy {
+    /// Leave the keep decisions to the strategy
+    #[default]
+    Strategy,
+    /// Also mulligan the hands the strategy would keep if they have fewer than two or more than four lands
+    Aggressive,
+    /// Keep any hand with two to five lands, and never mulligan more than once
+    Conservative,
+    /// Always keep the opening seven
+    KeepAll,
+}
+
+impl MulliganPolicy {
+    /// Number of mulligans after which any hand is kept, `None` to leave it to the strategy
+    pub fn max_mulligans(&self) -> Option<usize> {
+        match self {
+            MulliganPolicy::Strategy => None,
+            MulliganPolicy::Aggressive => Some(3),
+            MulliganPolicy::Conservative => Some(1),
+            MulliganPolicy::KeepAll => Some(0),
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            shuffle_seed: None,
+            is_first_player: true,
+            random_play_draw: false,
+            life_total: 20,
+            opponent_library: 60,
+            log_actions: false,
+            disruption: None,
+            mulligan_policy: MulliganPolicy::default(),
+            min_cards_left_in_library: 1,
+            success_turn_cap: None,
+        }
+    }
 }
 
-#[derive(Default)]
 pub struct Game {
     pub turn: usize,
     pub game_objects: Vec<CardRef>,
@@ -43,46 +255,213 @@ pub struct Game {
     pub is_first_player: bool,
     pub mulligan_count: usize,
     pub turns_to_skip: usize,
+    pub extra_turns: usize,
     pub storm: usize,
-    pub output: Rc<Mutex<Vec<String>>>,
+    pub max_storm: usize,
+    /// Creatures put into the graveyard from the battlefield this turn
+    pub creatures_died_this_turn: Vec<CardRef>,
+    pub output: Rc<Mutex<Vec<LogEvent>>>,
+    pub observer: Option<Box<dyn GameObserver>>,
+    pub seed: u64,
+    /// The generator behind `StdRng`, used directly so that snapshots can keep its position
+    pub rng: ChaCha12Rng,
+    pub shuffle_seed: u64,
+    /// Used only for shuffling the library, so that the shuffles can be seeded separately
+    pub shuffle_rng: ChaCha12Rng,
+    pub decisions: Rc<Mutex<Vec<Decision>>>,
+    pub replaying: Option<Rc<Mutex<VecDeque<Decision>>>>,
+    /// The most mana of each type the untapped sources could produce at any point of each turn
+    pub mana_available_by_turn: BTreeMap<usize, HashMap<Mana, u32>>,
+    pub combo_progress_by_turn: BTreeMap<usize, f32>,
+    /// Cards drawn on each turn, not counting the opening hands
+    pub cards_drawn_by_turn: BTreeMap<usize, usize>,
+    pub cards_discarded_by_turn: BTreeMap<usize, usize>,
+    /// Floating mana left unused at the end of each turn
+    pub wasted_mana_by_turn: BTreeMap<usize, u32>,
+    /// Cards in the library before drawing the opening hand
+    pub library_start_size: usize,
+    /// Cards left in the library at the end of each turn
+    pub library_size_by_turn: BTreeMap<usize, usize>,
+    pub disruption: Option<DisruptionModel>,
+    /// Turn the opponent kills us on in this game, picked from the kill turns of the disruption
+    pub kill_turn: Option<usize>,
+    pub counter_next_spell: bool,
+    /// Set when a card is drawn from an empty library, losing the game once the current action is over
+    pub drew_from_empty_library: bool,
+    /// Hand size to discard down to during cleanup, `None` for no maximum hand size
+    pub max_hand_size: Option<usize>,
+    pub mulligan_policy: MulliganPolicy,
+    /// Cards to leave in the library when drawing is optional, see `can_safely_draw`
+    pub min_cards_left_in_library: usize,
+    /// Last turn a win counts as a win on, see `GameConfig::success_turn_cap`
+    pub success_turn_cap: Option<usize>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self {
+            turn: 0,
+            game_objects: Vec::new(),
+            available_land_drops: 0,
+            deck: Deck::default(),
+            life_total: 0,
+            damage_dealt: 0,
+            opponent_library: 0,
+            floating_mana: HashMap::new(),
+            is_first_player: false,
+            mulligan_count: 0,
+            turns_to_skip: 0,
+            extra_turns: 0,
+            storm: 0,
+            max_storm: 0,
+            creatures_died_this_turn: Vec::new(),
+            output: Rc::new(Mutex::new(Vec::new())),
+            observer: None,
+            seed: 0,
+            rng: ChaCha12Rng::seed_from_u64(0),
+            shuffle_seed: 0,
+            shuffle_rng: ChaCha12Rng::seed_from_u64(0),
+            decisions: Rc::new(Mutex::new(Vec::new())),
+            replaying: None,
+            mana_available_by_turn: BTreeMap::new(),
+            combo_progress_by_turn: BTreeMap::new(),
+            cards_drawn_by_turn: BTreeMap::new(),
+            cards_discarded_by_turn: BTreeMap::new(),
+            wasted_mana_by_turn: BTreeMap::new(),
+            library_start_size: 0,
+            library_size_by_turn: BTreeMap::new(),
+            disruption: None,
+            kill_turn: None,
+            counter_next_spell: false,
+            drew_from_empty_library: false,
+            max_hand_size: Some(7),
+            mulligan_policy: MulliganPolicy::default(),
+            min_cards_left_in_library: 1,
+            success_turn_cap: None,
+        }
+    }
 }
 
 impl Game {
     /// Creates a new game with given decklist
     pub fn new(decklist: &Decklist) -> Result<Self, ParseDeckError> {
-        let mut deck = Deck::new(decklist)?;
-        let deck_size = deck.len();
-        let side_size = deck.sideboard.len();
+        Self::with_config(decklist, &GameConfig::default())
+    }
+
+    /// Creates a new game with given decklist, shuffling with the given seed
+    pub fn with_seed(decklist: &Decklist, seed: u64) -> Result<Self, ParseDeckError> {
+        Self::with_config(decklist, &GameConfig {
+            seed: Some(seed),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new game with given decklist and settings
+    pub fn with_config(decklist: &Decklist, config: &GameConfig) -> Result<Self, ParseDeckError> {
+        Self::with_custom_cards(decklist, config, &HashMap::new())
+    }
+
+    /// Creates a new game with given decklist and settings, building the cards named in
+    /// `custom_cards` from the given definitions instead, see `Deck::with_custom_cards`
+    pub fn with_custom_cards(
+        decklist: &Decklist,
+        config: &GameConfig,
+        custom_cards: &HashMap<String, Card>,
+    ) -> Result<Self, ParseDeckError> {
+        let seed = config.seed.unwrap_or_else(|| thread_rng().gen());
+        let shuffle_seed = config.shuffle_seed.unwrap_or(seed);
+
+        let mut deck = Deck::with_custom_cards(decklist, custom_cards)?;
 
         let mut game_objects = Vec::with_capacity(deck.len());
         for card in deck.iter() {
             game_objects.push(card.clone())
         }
 
-        deck.shuffle();
+        let mut shuffle_rng = ChaCha12Rng::seed_from_u64(shuffle_seed);
+        deck.shuffle_with(&mut shuffle_rng);
+
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+
+        let kill_turn = config
+            .disruption
+            .as_ref()
+            .and_then(|model| model.sample_kill_turn(&mut rng));
+
+        let library_start_size = deck.len();
+
+        let is_first_player = if config.random_play_draw {
+            rng.gen_bool(0.5)
+        } else {
+            config.is_first_player
+        };
 
         let game = Self {
             deck,
             game_objects,
             turn: 0,
-            life_total: 20,
+            life_total: config.life_total,
             damage_dealt: 0,
-            opponent_library: 60,
+            opponent_library: config.opponent_library,
             floating_mana: HashMap::new(),
-            is_first_player: true,
+            is_first_player,
             available_land_drops: 1,
             mulligan_count: 0,
             turns_to_skip: 0,
+            extra_turns: 0,
             storm: 0,
+            max_storm: 0,
+            creatures_died_this_turn: Vec::new(),
             output: Rc::new(Mutex::new(Vec::new())),
+            observer: if config.log_actions {
+                Some(Box::new(LoggingObserver {}))
+            } else {
+                None
+            },
+            seed,
+            rng,
+            shuffle_seed,
+            shuffle_rng,
+            decisions: Rc::new(Mutex::new(Vec::new())),
+            replaying: None,
+            mana_available_by_turn: BTreeMap::new(),
+            combo_progress_by_turn: BTreeMap::new(),
+            cards_drawn_by_turn: BTreeMap::new(),
+            cards_discarded_by_turn: BTreeMap::new(),
+            wasted_mana_by_turn: BTreeMap::new(),
+            library_start_size,
+            library_size_by_turn: BTreeMap::new(),
+            disruption: config.disruption.clone(),
+            kill_turn,
+            counter_next_spell: false,
+            drew_from_empty_library: false,
+            max_hand_size: Some(7),
+            mulligan_policy: config.mulligan_policy,
+            min_cards_left_in_library: config.min_cards_left_in_library,
+            success_turn_cap: config.success_turn_cap,
         };
 
-        game.log(format!("Deck: {deck_size} cards"));
-        game.log(format!("Sideboard: {side_size} cards"));
+        Ok(game)
+    }
+
+    /// Creates a new game that re-runs the decisions of the replay instead of
+    /// asking the strategy, as long as there are decisions left. The seed alone
+    /// reproduces the game, as the strategies break their ties deterministically.
+    pub fn replay(decklist: &Decklist, replay: &Replay) -> Result<Self, ParseDeckError> {
+        let mut game = Self::with_seed(decklist, replay.seed)?;
+        game.replaying = Some(Rc::new(Mutex::new(replay.decisions.iter().cloned().collect())));
 
         Ok(game)
     }
 
+    /// Returns the seed and the decisions made so far as a replay
+    pub fn recorded_replay(&self) -> Replay {
+        Replay {
+            seed: self.seed,
+            decisions: self.decisions.lock().unwrap().clone(),
+        }
+    }
+
     /// Runs the game to completion.
     ///
     /// ```
@@ -95,58 +474,308 @@ impl Game {
     /// game.run(&mut strategy);
     /// ```
     pub fn run(&mut self, strategy: &mut Box<dyn Strategy>) -> GameResult {
-        self.log(format!("====================[ START OF GAME ]======================="));
+        let result = loop {
+            if let GameStatus::Finished(outcome) = self.step_turn(strategy) {
+                break outcome;
+            }
+        };
 
-        self.find_starting_hand(strategy);
+        self.finish(strategy, result)
+    }
 
-        let result = loop {
-            self.begin_turn();
+    /// Plays the game until the end of the next turn, taking the starting hand first if the game
+    /// hasn't started yet. Once this returns a finished status, `finish` builds the result of the game.
+    ///
+    /// ```
+    /// use goldfisher::strategy::{pattern_combo, Strategy};
+    /// use goldfisher::game::{Game, GameStatus};
+    ///
+    /// let mut strategy: Box<dyn Strategy> = Box::new(pattern_combo::PatternCombo {});
+    /// let mut game = Game::new(&strategy.default_decklist()).unwrap();
+    ///
+    /// let outcome = loop {
+    ///     if let GameStatus::Finished(outcome) = game.step_turn(&mut strategy) {
+    ///         break outcome;
+    ///     }
+    /// };
+    ///
+    /// let result = game.finish(&mut strategy, outcome);
+    /// ```
+    pub fn step_turn(&mut self, strategy: &mut Box<dyn Strategy>) -> GameStatus {
+        if self.turn == 0 {
+            self.log(Phase::Info, format!("Deck: {deck_size} cards", deck_size = self.deck.len()));
+            self.log(Phase::Info, format!("Sideboard: {side_size} cards", side_size = self.deck.sideboard.len()));
+            self.log(Phase::Info, "====================[ START OF GAME ]=======================".to_owned());
 
-            self.log(format!(
-                "======================[ TURN {turn:002} ]===========================",
-                turn = self.turn
-            ));
+            self.find_starting_hand(strategy);
+        }
 
-            self.untap();
+        if let status @ GameStatus::Finished(_) = self.begin_turn() {
+            return status;
+        }
 
-            if let GameStatus::Finished(outcome) = self.draw() {
-                break outcome;
-            }
+        self.log(Phase::Info, format!(
+            "======================[ TURN {turn:002} ]===========================",
+            turn = self.turn
+        ));
 
-            self.print_game_state();
+        if self.is_killed_by_opponent() {
+            return GameStatus::Finished(Outcome::Lose);
+        }
 
-            if let GameStatus::Finished(outcome) = self.take_game_actions(strategy) {
-                break outcome;
-            }
+        self.untap();
 
-            if let GameStatus::Finished(outcome) = self.cleanup(strategy) {
-                break outcome;
+        if let status @ GameStatus::Finished(_) = self.draw() {
+            return status;
+        }
+
+        self.print_game_state();
+
+        if let status @ GameStatus::Finished(_) = self.take_game_actions(strategy) {
+            return status;
+        }
+
+        if let status @ GameStatus::Finished(_) = self.combat(strategy) {
+            return status;
+        }
+
+        self.cleanup(strategy)
+    }
+
+    /// Steps the turns until the strategy has assembled its combo or the game is over,
+    /// for skipping the uneventful setup turns of a sample game.
+    pub fn skip_to_combo(&mut self, strategy: &mut Box<dyn Strategy>) -> GameStatus {
+        loop {
+            let status = self.step_turn(strategy);
+            if matches!(status, GameStatus::Finished(_)) || strategy.combo_progress(self) >= 1.0 {
+                return status;
             }
-        };
+        }
+    }
 
-        self.log(format!("=====================[ END OF GAME ]========================"));
-        self.log(format!(
+    /// Ends the game with the outcome, resetting the strategy for the next game and building the result.
+    pub fn finish(&mut self, strategy: &mut Box<dyn Strategy>, result: Outcome) -> GameResult {
+        self.log(Phase::Info, "=====================[ END OF GAME ]========================".to_owned());
+        self.log(Phase::Info, format!(
             "                    {result:?} on turn {turn}!",
             turn = self.turn
         ));
-        self.log(format!("============================================================"));
+        let success_turn_cap = self.success_turn_cap.filter(|cap| result == Outcome::Win && self.turn > *cap);
+        if let Some(cap) = success_turn_cap {
+            self.log(Phase::Info, format!("        Counted as a loss, past the turn cap of {cap}"));
+        }
+        self.log(Phase::Info, "============================================================".to_owned());
         self.print_game_state();
 
-        GameResult {
+        // Reset any state the strategy kept for the turn, so that it can be reused for the next game
+        strategy.cleanup();
+
+        // A win on the turn the combo was assembled doesn't get to record its progress in the cleanup
+        let first_winnable_turn = self
+            .combo_progress_by_turn
+            .iter()
+            .find(|(_, progress)| **progress >= 1.0)
+            .map(|(turn, _)| *turn)
+            .or_else(|| (result == Outcome::Win).then_some(self.turn));
+
+        let (result, loss_reason) = match result {
+            Outcome::Win if success_turn_cap.is_some() => (Outcome::Lose, Some(LossReason::TooSlow)),
+            Outcome::Win => (Outcome::Win, None),
+            Outcome::Lose => (result, Some(self.classify_loss())),
+            Outcome::Draw => (result, None),
+        };
+
+        let game_result = GameResult {
             result,
             turn: self.turn,
             mulligan_count: self.mulligan_count,
+            final_life: self.life_total,
+            damage_dealt: self.damage_dealt,
+            max_storm: self.max_storm,
+            net_cards_drawn: self.net_cards_drawn(),
+            loss_reason,
+            first_winnable_turn,
+            library_size_by_turn: self.library_size_by_turn.clone(),
             output: std::mem::take(&mut self.output.lock().unwrap()),
+        };
+
+        if let Some(observer) = &self.observer {
+            observer.on_game_end(&game_result);
+        }
+
+        game_result
+    }
+
+    pub fn log(&self, phase: Phase, message: String) {
+        let event = LogEvent {
+            turn: self.turn,
+            phase,
+            message,
+        };
+
+        if let Some(observer) = &self.observer {
+            observer.on_action(self.turn, &event.to_string());
+        }
+        self.output.lock().unwrap().push(event);
+    }
+
+    /// Moves the game object to the given zone, notifying the observer of the change.
+    /// Tokens leaving the battlefield are moved outside of the game instead.
+    pub fn move_to_zone(&mut self, card: &CardRef, zone: Zone) {
+        if zone == Zone::Graveyard && is_battlefield(&card) && is_card_type(&card, &CardType::Creature) {
+            self.creatures_died_this_turn.push(card.clone());
+        }
+
+        let zone = if card.borrow().is_token && zone != Zone::Battlefield {
+            Zone::Outside
+        } else {
+            zone
+        };
+
+        let from = std::mem::replace(&mut card.borrow_mut().zone, zone.clone());
+
+        if let Some(observer) = &self.observer {
+            observer.on_zone_change(card, &from, &zone);
         }
     }
 
-    pub fn log(&self, message: String) {
-        log::debug!("{message}");
-        self.output.lock().unwrap().push(message.to_owned());
+    /// Asks the strategy whether to keep the current hand within the limits of the
+    /// mulligan policy, recording the decision.
+    pub fn is_keepable_hand(&self, strategy: &(impl Strategy + ?Sized)) -> bool {
+        let keep = match self.next_replayed_decision() {
+            Some(Decision::Keep(keep)) => keep,
+            _ => {
+                let lands = self
+                    .game_objects
+                    .iter()
+                    .filter(|card| is_hand(card) && is_card_type(card, &CardType::Land))
+                    .count();
+
+                let max_mulligans_taken = self
+                    .mulligan_policy
+                    .max_mulligans()
+                    .is_some_and(|max_mulligans| self.mulligan_count >= max_mulligans);
+
+                max_mulligans_taken
+                    || match self.mulligan_policy {
+                        MulliganPolicy::Strategy => strategy.is_keepable_hand(self, self.mulligan_count),
+                        MulliganPolicy::Aggressive => {
+                            (2..=4).contains(&lands) && strategy.is_keepable_hand(self, self.mulligan_count)
+                        }
+                        MulliganPolicy::Conservative => {
+                            (2..=5).contains(&lands) || strategy.is_keepable_hand(self, self.mulligan_count)
+                        }
+                        MulliganPolicy::KeepAll => true,
+                    }
+            }
+        };
+
+        self.record_decision(Decision::Keep(keep));
+        keep
+    }
+
+    /// Asks the strategy to select the best card of the given ones, recording the decision.
+    pub fn select_best(
+        &self,
+        strategy: &(impl Strategy + ?Sized),
+        cards: BTreeMap<String, Vec<CardRef>>,
+    ) -> Option<CardRef> {
+        let selected = match self.next_replayed_decision() {
+            Some(Decision::Select(index)) => index.and_then(|index| self.object_at(index)),
+            _ => strategy.select_best(self, cards),
+        };
+
+        let index = selected.as_ref().and_then(|card| self.index_of(card));
+        self.record_decision(Decision::Select(index));
+        selected
+    }
+
+    /// Asks the strategy for the Intuition pile, recording the decision.
+    pub fn select_intuition(&self, strategy: &(impl Strategy + ?Sized)) -> Vec<CardRef> {
+        let found = match self.next_replayed_decision() {
+            Some(Decision::Cards(indices)) => self.objects_at(&indices),
+            _ => strategy.select_intuition(self),
+        };
+
+        self.record_decision(Decision::Cards(self.indices_of(&found)));
+        found
+    }
+
+    /// Asks the strategy which cards to discard down to hand size, recording the decision.
+    pub fn discard_to_hand_size(
+        &self,
+        strategy: &(impl Strategy + ?Sized),
+        hand_size: usize,
+    ) -> Vec<CardRef> {
+        let discarded = match self.next_replayed_decision() {
+            Some(Decision::Cards(indices)) => self.objects_at(&indices),
+            _ => strategy.discard_to_hand_size(self, hand_size),
+        };
+
+        self.record_decision(Decision::Cards(self.indices_of(&discarded)));
+        discarded
+    }
+
+    /// Asks the strategy which cards to discard to an effect, recording the decision.
+    pub fn choose_discard(&self, strategy: &(impl Strategy + ?Sized), count: usize) -> Vec<CardRef> {
+        let discarded = match self.next_replayed_decision() {
+            Some(Decision::Cards(indices)) => self.objects_at(&indices),
+            _ => strategy.choose_discard(self, count),
+        };
+
+        self.record_decision(Decision::Cards(self.indices_of(&discarded)));
+        discarded
+    }
+
+    /// Asks the strategy which of the cards looked at to keep on top of the library, recording the decision.
+    pub fn choose_scry(&self, strategy: &(impl Strategy + ?Sized), cards: Vec<CardRef>) -> Vec<CardRef> {
+        let kept = match self.next_replayed_decision() {
+            Some(Decision::Cards(indices)) => self.objects_at(&indices),
+            _ => strategy.choose_scry(self, cards),
+        };
+
+        self.record_decision(Decision::Cards(self.indices_of(&kept)));
+        kept
+    }
+
+    fn next_replayed_decision(&self) -> Option<Decision> {
+        self.replaying
+            .as_ref()
+            .and_then(|decisions| decisions.lock().unwrap().pop_front())
+    }
+
+    fn record_decision(&self, decision: Decision) {
+        self.decisions.lock().unwrap().push(decision);
+    }
+
+    /// Index of the card in the game objects, followed by the sideboard
+    fn index_of(&self, card: &CardRef) -> Option<usize> {
+        self.game_objects
+            .iter()
+            .chain(self.deck.sideboard.iter())
+            .position(|game_object| same_card(game_object, card))
+    }
+
+    fn indices_of(&self, cards: &[CardRef]) -> Vec<usize> {
+        cards.iter().flat_map(|card| self.index_of(card)).collect()
+    }
+
+    fn object_at(&self, index: usize) -> Option<CardRef> {
+        self.game_objects
+            .iter()
+            .chain(self.deck.sideboard.iter())
+            .nth(index)
+            .cloned()
+    }
+
+    fn objects_at(&self, indices: &[usize]) -> Vec<CardRef> {
+        indices.iter().flat_map(|index| self.object_at(*index)).collect()
     }
 
     /// Finds all castable game objects with their payments and floating mana left over afterwards.
     pub fn find_castable(&self) -> Vec<(CardRef, PaymentAndFloating)> {
+        self.update_dynamic_mana();
+
         let nonlands_in_hand = self.game_objects.iter().filter(|card| {
             let card = card.borrow();
             card.zone == Zone::Hand && !card.card_types.contains(&CardType::Land)
@@ -157,17 +786,9 @@ impl Game {
             .iter()
             .filter(|card| {
                 let card = card.borrow();
-                let is_untapped_mana_source = card.zone == Zone::Battlefield
+                card.zone == card.mana_zone()
                     && !card.produced_mana.is_empty()
-                    && !card.is_summoning_sick
-                    && !card.is_tapped
-                    && card.name != "Elvish Spirit Guide";
-
-                if is_untapped_mana_source {
-                    return true;
-                }
-
-                return card.name == "Elvish Spirit Guide" && card.zone == Zone::Hand;
+                    && (card.is_pitch_for_mana || (!card.is_summoning_sick && !card.is_tapped))
             })
             .map(Rc::clone)
             .collect();
@@ -197,18 +818,28 @@ impl Game {
     pub fn play_land(&mut self, land_card: CardRef) {
         if self.available_land_drops > 0 {
             self.available_land_drops -= 1;
-            let mut card = land_card.borrow_mut();
 
-            self.log(format!(
-                "[Turn {turn:002}][Action]: Playing land: \"{name}\"",
-                turn = self.turn,
-                name = card.name
+            self.log(Phase::Action, format!(
+                "Playing land: \"{name}\"",
+                name = land_card.borrow().name
             ));
 
-            card.zone = Zone::Battlefield;
+            self.move_to_zone(&land_card, Zone::Battlefield);
+
+            // Creature lands like Dryad Arbor can't tap for mana on the turn they enter
+            let mut land = land_card.borrow_mut();
+            if land.card_types.contains(&CardType::Creature) {
+                land.is_summoning_sick = !land.is_haste;
+            }
         }
     }
 
+    /// Whether drawing the amount of cards still leaves the minimum amount of cards in the library,
+    /// for deciding on optional draws
+    pub fn can_safely_draw(&self, amount: usize) -> bool {
+        self.deck.len() >= amount + self.min_cards_left_in_library
+    }
+
     /// Draw `amount` cards from the library.
     /// If there aren't enough cards to draw the game ends in a loss.
     pub fn draw_n(&mut self, amount: usize) -> GameStatus {
@@ -227,25 +858,56 @@ impl Game {
     pub fn draw(&mut self) -> GameStatus {
         if self.turn == 0 || (self.turn == 1 && !self.is_first_player) || self.turn > 1 {
             if let Some(card) = self.deck.draw() {
-                let mut card = card.borrow_mut();
-
-                card.zone = Zone::Hand;
-                self.log(format!(
-                    "[Turn {turn:002}][Action]: Drew card: \"{name}\", {library} cards remaining.",
-                    turn = self.turn,
-                    name = card.name,
+                self.move_to_zone(&card, Zone::Hand);
+                if self.turn > 0 {
+                    *self.cards_drawn_by_turn.entry(self.turn).or_insert(0) += 1;
+                }
+                self.log(Phase::Action, format!(
+                    "Drew card: \"{name}\", {library} cards remaining.",
+                    name = card.borrow().name,
                     library = self.deck.len(),
                 ));
                 return GameStatus::Continue;
             } else {
+                self.drew_from_empty_library = true;
+                self.log(Phase::Action, "Tried to draw from an empty library.".to_owned());
                 return GameStatus::Finished(Outcome::Lose);
             }
         }
         GameStatus::Continue
     }
 
+    /// Draws the given amount of cards, then asks the strategy which cards to discard
+    pub fn loot(&mut self, strategy: &(impl Strategy + ?Sized), draw: usize, discard: usize) {
+        self.draw_n(draw);
+
+        let cards_to_discard = self.choose_discard(strategy, discard);
+        for card in cards_to_discard {
+            self.discard(card);
+        }
+    }
+
     /// Untaps all the lands and clears summoning sickness
     pub fn untap(&mut self) {
+        // Echo is never paid, so the creatures with echo are sacrificed on the first upkeep after they entered
+        let unpaid_echoes = self
+            .game_objects
+            .iter()
+            .filter(|card| {
+                let card = card.borrow();
+                card.zone == Zone::Battlefield && card.is_echo && card.is_summoning_sick
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for card in unpaid_echoes {
+            self.log(Phase::Action, format!(
+                "Sacrificing \"{card_name}\" instead of paying its echo.",
+                card_name = card.borrow().name
+            ));
+            self.move_to_zone(&card, Zone::Graveyard);
+        }
+
         for card in self.game_objects.iter() {
             let mut card = card.borrow_mut();
 
@@ -258,8 +920,22 @@ impl Game {
 
     /// Takes game actions until no further actions are taken or game ends
     pub fn take_game_actions(&mut self, strategy: &mut Box<dyn Strategy>) -> GameStatus {
+        self.apply_disruption();
+
         loop {
+            self.record_available_mana();
+
+            if strategy.should_float_mana(self) {
+                self.float_mana();
+            }
+
             let action_taken = strategy.take_game_action(self);
+
+            // Effects ignore the status of their draws, so check whether any of them drew from an empty library
+            if self.drew_from_empty_library {
+                return GameStatus::Finished(Outcome::Lose);
+            }
+
             match strategy.game_status(self) {
                 GameStatus::Continue => {
                     if !action_taken {
@@ -271,25 +947,107 @@ impl Game {
         }
     }
 
-    /// Casts the spell, paying its cost with the payment.
-    /// The payment has to be fresh, as this function trusts that it is a valid payment
-    /// at the time the spell is cast.
-    pub fn cast_spell(
-        &mut self,
-        strategy: &impl Strategy,
-        source: &CardRef,
-        PaymentAndFloating{ payment, floating }: &PaymentAndFloating,
-        attach_to: Option<CardRef>,
-    ) {
-        self.storm += 1;
+    /// Combat phase, attacks with the creatures chosen by the strategy. The goldfished opponent
+    /// never blocks, so each attacker deals damage equal to its power.
+    pub fn combat(&mut self, strategy: &mut Box<dyn Strategy>) -> GameStatus {
+        let attackers = strategy.attackers(self);
 
-        let target_str = match attach_to.as_ref() {
-            Some(target) => format!(" on target \"{}\"", target.borrow().name.clone()),
-            None => "".to_owned(),
+        if attackers.is_empty() {
+            return GameStatus::Continue;
+        }
+
+        for attacker in attackers {
+            let power = attacker.borrow().power;
+            attacker.borrow_mut().is_tapped = true;
+
+            self.log(Phase::Action, format!(
+                "Attacking with \"{card_name}\" for {power} damage.",
+                card_name = attacker.borrow().name
+            ));
+            self.deal_damage(power);
+        }
+
+        strategy.game_status(self)
+    }
+
+    /// Checks whether the opponent has killed us before the current turn. On the draw the opponent
+    /// takes their turn first, killing us before our turn of the same number.
+    pub fn is_killed_by_opponent(&self) -> bool {
+        let Some(kill_turn) = self.kill_turn else {
+            return false;
         };
 
-        let floating_mana = floating
-            .iter()
+        let last_turn = if self.is_first_player { kill_turn } else { kill_turn.saturating_sub(1) };
+        if self.turn <= last_turn {
+            return false;
+        }
+
+        self.log(Phase::Game, format!("Opponent kills us on their turn {kill_turn}."));
+        true
+    }
+
+    /// Applies the disruption of the opponent if it is due this turn. Each disruption happens only once.
+    /// Counter taxes on the battlefield delay countering by a turn per mana, as the opponent is assumed
+    /// to get one more mana each turn.
+    pub fn apply_disruption(&mut self) {
+        let counter_tax = self
+            .battlefield()
+            .map(|card| card.borrow().counter_tax as usize)
+            .sum::<usize>();
+
+        let is_due = self.disruption.as_ref().is_some_and(|model| match model.disruption {
+            Disruption::Discard => self.turn >= model.turn,
+            Disruption::Counter => self.turn >= model.turn + counter_tax,
+        });
+
+        if !is_due {
+            return;
+        }
+
+        match self.disruption.take().map(|model| model.disruption) {
+            Some(Disruption::Discard) => {
+                let hand = self.hand().collect::<Vec<_>>();
+
+                if !hand.is_empty() {
+                    let card = hand[self.rng.gen_range(0..hand.len())].clone();
+                    self.log(Phase::Action, format!(
+                        "Opponent makes us discard \"{card_name}\".",
+                        card_name = card.borrow().name
+                    ));
+                    self.discard(card);
+                }
+            }
+            Some(Disruption::Counter) => {
+                self.log(Phase::Action, "Opponent is ready to counter the next spell.".to_owned());
+                self.counter_next_spell = true;
+            }
+            None => {}
+        }
+    }
+
+    /// Casts the spell, paying its cost with the payment.
+    /// The payment has to be fresh, as this function trusts that it is a valid payment
+    /// at the time the spell is cast.
+    pub fn cast_spell(
+        &mut self,
+        strategy: &impl Strategy,
+        source: &CardRef,
+        PaymentAndFloating{ payment, floating }: &PaymentAndFloating,
+        attach_to: Option<CardRef>,
+    ) {
+        self.storm += 1;
+        self.max_storm = self.max_storm.max(self.storm);
+
+        let target_str = match attach_to.as_ref() {
+            Some(target) => format!(" on target \"{}\"", target.borrow().name.clone()),
+            None => "".to_owned(),
+        };
+
+        let mut floating_mana = floating.iter().collect::<Vec<_>>();
+        floating_mana.sort();
+
+        let floating_mana = floating_mana
+            .into_iter()
             .flat_map(|(mana, amount)| {
                 if *amount > 0 {
                     return Some(format!("{amount} {mana:?}"));
@@ -318,11 +1076,21 @@ impl Game {
             }
         };
 
-        self.log(format!("[Turn {turn:002}][Action]: Casting card: \"{card_name}\"{target_str}{mana_sources_str}",
-            turn = self.turn,
+        self.log(Phase::Action, format!("Casting card: \"{card_name}\"{target_str}{mana_sources_str}",
             card_name = source.borrow().name));
 
-        let new_zone = if source.borrow().card_types.contains(&CardType::Instant)
+        // The opponent holds on to the counterspell while it can't be cast
+        let counters_prevented = self.battlefield().any(|card| card.borrow().prevents_counters);
+        let is_countered = self.counter_next_spell && !counters_prevented;
+
+        if is_countered {
+            self.counter_next_spell = false;
+        } else if self.counter_next_spell {
+            self.log(Phase::Action, "Opponent can't counter the spell.".to_owned());
+        }
+
+        let new_zone = if is_countered
+            || source.borrow().card_types.contains(&CardType::Instant)
             || source.borrow().card_types.contains(&CardType::Sorcery)
         {
             Zone::Graveyard
@@ -330,10 +1098,13 @@ impl Game {
             Zone::Battlefield
         };
 
-        source.borrow_mut().zone = new_zone;
-        source.borrow_mut().attached_to = attach_to;
+        self.move_to_zone(source, new_zone);
+
+        if !is_countered {
+            source.borrow_mut().attached_to = attach_to;
+        }
 
-        if source.borrow().card_types.contains(&CardType::Creature) {
+        if !is_countered && source.borrow().card_types.contains(&CardType::Creature) {
             let has_haste = source.borrow().is_haste;
             source.borrow_mut().is_summoning_sick = !has_haste;
 
@@ -352,8 +1123,7 @@ impl Game {
                     .count();
 
                 for _ in 0..etb_draw_triggers {
-                    // Leave one card so that turn can be passed
-                    if self.deck.len() > 1 {
+                    if self.can_safely_draw(1) {
                         self.draw();
                     }
                 }
@@ -375,36 +1145,140 @@ impl Game {
 
         self.floating_mana = floating.to_owned();
         for mana_source in payment {
-            let mut source = mana_source.borrow_mut();
-
-            if let Some(uses) = source.remaining_uses {
-                if uses > 1 {
-                    source.remaining_uses = Some(uses - 1);
-                    source.is_tapped = true;
-                } else {
-                    source.remaining_uses = Some(0);
-                    if source.name == "Elvish Spirit Guide" {
-                        source.zone = Zone::Exile;
-                    } else {
-                        source.zone = Zone::Graveyard;
-                    }
-                }
+            self.use_mana_source(mana_source);
+        }
+
+        if source.borrow().x_cost {
+            let x = self.floating_mana.values().sum::<u32>();
+            self.floating_mana.clear();
+            source.borrow_mut().x = x;
+
+            self.log(Phase::Action, format!("Spending {x} mana on X."));
+        }
+
+        if is_countered {
+            self.log(Phase::Action, format!(
+                "Opponent counters \"{card_name}\".",
+                card_name = source.borrow().name
+            ));
+            return;
+        }
+
+        self.handle_on_resolve_effects(source, strategy);
+    }
+
+    /// Taps the mana source for the given color, adding the mana it produces to the floating mana.
+    /// Returns false without tapping if the source can't produce the color right now.
+    pub fn tap_for_mana(&mut self, mana_source: &CardRef, color: Mana) -> bool {
+        self.update_dynamic_mana();
+
+        let amount = {
+            let source = mana_source.borrow();
+            let can_tap = source.zone == source.mana_zone()
+                && source.charges != Some(0)
+                && (source.is_pitch_for_mana
+                    || (!source.is_tapped
+                        && (!source.is_summoning_sick
+                            || source.is_haste
+                            || !source.card_types.contains(&CardType::Creature))));
+
+            match source.produced_mana.get(&color) {
+                Some(amount) if can_tap && *amount > 0 => *amount,
+                _ => return false,
+            }
+        };
+
+        self.log(Phase::Action, format!(
+            "Tapping \"{card_name}\" for {amount} {color:?} mana.",
+            card_name = mana_source.borrow().name
+        ));
+
+        *self.floating_mana.entry(color).or_insert(0) += amount;
+        self.use_mana_source(mana_source);
+
+        true
+    }
+
+    /// Taps the mana source, uses up one of its charges or uses it up entirely,
+    /// dealing any pain of the source to self
+    fn use_mana_source(&mut self, mana_source: &CardRef) {
+        let (is_one_shot, charges) = {
+            let source = mana_source.borrow();
+            (source.is_one_shot, source.charges)
+        };
+
+        if is_one_shot {
+            if mana_source.borrow().is_pitch_for_mana {
+                self.move_to_zone(mana_source, Zone::Exile);
+            } else {
+                self.move_to_zone(mana_source, Zone::Graveyard);
+            }
+        } else if let Some(charges) = charges {
+            if charges > 1 {
+                let mut source = mana_source.borrow_mut();
+                source.charges = Some(charges - 1);
+                source.is_tapped = source.taps_for_mana;
             } else {
-                source.is_tapped = true;
+                mana_source.borrow_mut().charges = Some(0);
+                self.move_to_zone(mana_source, Zone::Graveyard);
             }
+        } else {
+            mana_source.borrow_mut().is_tapped = true;
         }
 
-        self.handle_on_resolve_effects(source, strategy);
+        let pain = mana_source.borrow().pain;
+        if pain > 0 {
+            self.take_damage(pain);
+        }
     }
 
     /// Applies any effects to the game the game object resolving might cause
     pub fn handle_on_resolve_effects(&mut self, source: &CardRef, strategy: &impl Strategy) {
+        // The game is already lost, there is no point in resolving anything further
+        if self.drew_from_empty_library {
+            return;
+        }
+
         let on_resolve = source.borrow().on_resolve.clone();
         if let Some(effect) = on_resolve {
             effect.resolve(self, source, strategy)
         }
     }
 
+    /// Returns the number of game objects with the given name in the zone
+    pub fn count_in_zone(&self, name: &str, zone: Zone) -> usize {
+        self.game_objects
+            .iter()
+            .filter(|card| {
+                let card = card.borrow();
+                card.zone == zone && card.name == name
+            })
+            .count()
+    }
+
+    /// Returns the game objects in the given zone
+    pub fn cards_in_zone(&self, zone: Zone) -> impl Iterator<Item = CardRef> + '_ {
+        self.game_objects
+            .iter()
+            .filter(move |card| card.borrow().zone == zone)
+            .cloned()
+    }
+
+    /// Returns the game objects on the battlefield
+    pub fn battlefield(&self) -> impl Iterator<Item = CardRef> + '_ {
+        self.cards_in_zone(Zone::Battlefield)
+    }
+
+    /// Returns the cards in hand
+    pub fn hand(&self) -> impl Iterator<Item = CardRef> + '_ {
+        self.cards_in_zone(Zone::Hand)
+    }
+
+    /// Returns the cards in the graveyard
+    pub fn graveyard(&self) -> impl Iterator<Item = CardRef> + '_ {
+        self.cards_in_zone(Zone::Graveyard)
+    }
+
     /// Returns the count of available mana sources on battlefield
     pub fn mana_sources_count(&self) -> usize {
         self.game_objects
@@ -416,66 +1290,210 @@ impl Game {
             .count()
     }
 
+    /// Updates the produced mana of the sources whose production depends on the game state,
+    /// such as "Rofellos, Llanowar Emissary" producing mana for each Forest.
+    pub fn update_dynamic_mana(&self) {
+        for card in self.game_objects.iter() {
+            let dynamic = card.borrow().produced_mana_dynamic.clone();
+
+            if let Some(DynamicMana::PerLandType(mana, land_type)) = dynamic {
+                let land_type = SubType::Land(land_type);
+                let amount = self
+                    .game_objects
+                    .iter()
+                    .filter(|land| is_battlefield(land) && is_sub_type(land, &land_type))
+                    .count();
+
+                card.borrow_mut().produced_mana = HashMap::from([(mana, amount as u32)]);
+            }
+        }
+    }
+
+    /// Returns the most mana of each type the untapped mana sources on battlefield could produce.
+    /// Sources producing multiple colors count for each.
+    pub fn available_mana(&self) -> HashMap<Mana, u32> {
+        self.update_dynamic_mana();
+
+        let mut available = HashMap::new();
+
+        for card in self.game_objects.iter() {
+            let card = card.borrow();
+            if card.zone != Zone::Battlefield || card.is_summoning_sick || card.is_tapped {
+                continue;
+            }
+
+            for (mana, amount) in card.produced_mana.iter() {
+                *available.entry(*mana).or_insert(0) += amount;
+            }
+        }
+
+        available
+    }
+
+    /// Records the most mana of each type available so far this turn, before the sources get tapped
+    fn record_available_mana(&mut self) {
+        let available = self.available_mana();
+        let recorded = self.mana_available_by_turn.entry(self.turn).or_default();
+
+        for (mana, amount) in available {
+            let most = recorded.entry(mana).or_insert(0);
+            *most = (*most).max(amount);
+        }
+    }
+
+    /// Creates a token on the battlefield, entering with summoning sickness like any other creature
+    pub fn create_token(
+        &mut self,
+        name: &str,
+        power: i32,
+        toughness: i32,
+        card_types: HashSet<CardType>,
+    ) -> CardRef {
+        let token = Rc::new(RefCell::new(Card {
+            name: name.to_owned(),
+            card_types,
+            zone: Zone::Outside,
+            power,
+            toughness,
+            is_token: true,
+            is_summoning_sick: true,
+            ..Default::default()
+        }));
+
+        self.game_objects.push(token.clone());
+        self.move_to_zone(&token, Zone::Battlefield);
+
+        token
+    }
+
+    /// Sacrifices the permanent, resolving its dies trigger if it has one
+    pub fn sacrifice(&mut self, card: &CardRef, strategy: &impl Strategy) {
+        self.move_to_zone(card, Zone::Graveyard);
+
+        let on_dies = card.borrow().on_dies.clone();
+        if let Some(effect) = on_dies {
+            effect.resolve(self, card, strategy)
+        }
+    }
+
     pub fn discard(&mut self, card: CardRef) {
-        self.log(format!(
-            "[Turn {turn:002}][Action]: Discarding card {card_name}",
-            turn = self.turn,
+        self.log(Phase::Action, format!(
+            "Discarding card {card_name}",
             card_name = card.borrow().name,
         ));
-        card.borrow_mut().zone = Zone::Graveyard;
+        self.move_to_zone(&card, Zone::Graveyard);
+        *self.cards_discarded_by_turn.entry(self.turn).or_insert(0) += 1;
+    }
+
+    /// Returns the cards drawn after the opening hand minus the cards discarded
+    pub fn net_cards_drawn(&self) -> i32 {
+        let drawn = self.cards_drawn_by_turn.values().sum::<usize>();
+        let discarded = self.cards_discarded_by_turn.values().sum::<usize>();
+
+        drawn as i32 - discarded as i32
+    }
+
+    /// Cards that have left the library since the start of the game, whether drawn, searched for or milled
+    pub fn library_thinning(&self) -> usize {
+        self.library_start_size.saturating_sub(self.deck.len())
+    }
+
+    /// Classifies the likely reason for losing from the lands and spells drawn during the game
+    pub fn classify_loss(&self) -> LossReason {
+        if self.deck.is_empty() {
+            return LossReason::DeckedOut;
+        }
+
+        let (lands, spells): (Vec<_>, Vec<_>) = self
+            .game_objects
+            .iter()
+            .filter(|card| !is_library(card))
+            .partition(|card| is_card_type(card, &CardType::Land));
+
+        // Four lands are enough to cast most of the spells, even on the later turns
+        if lands.len() < self.turn.min(4) {
+            return LossReason::ManaScrew;
+        }
+
+        if 2 * lands.len() > 3 * spells.len() {
+            return LossReason::ManaFlood;
+        }
+
+        LossReason::NoCombo
     }
 
     /// Cleanup phase, discards cards to hand size
     pub fn cleanup(&mut self, strategy: &mut Box<dyn Strategy>) -> GameStatus {
-        let cards_to_discard = strategy.discard_to_hand_size(self, 7);
-        if !cards_to_discard.is_empty() {
-            self.log(format!(
-                "[Turn {turn:002}][Action]: Discarding to hand size",
-                turn = self.turn,
-            ));
+        self.combo_progress_by_turn.insert(self.turn, strategy.combo_progress(self));
+
+        if let Some(hand_size) = self.max_hand_size {
+            let cards_to_discard = self.discard_to_hand_size(strategy.as_ref(), hand_size);
+            if !cards_to_discard.is_empty() {
+                self.log(Phase::Action, "Discarding to hand size".to_owned());
+            }
+
+            for card in cards_to_discard {
+                self.discard(card);
+            }
         }
 
-        for card in cards_to_discard {
-            self.discard(card);
+        let wasted_mana = self.floating_mana.values().sum::<u32>();
+        if wasted_mana > 0 {
+            self.log(Phase::Action, format!("Losing {wasted_mana} unused floating mana."));
         }
+        *self.wasted_mana_by_turn.entry(self.turn).or_insert(0) += wasted_mana;
+        self.library_size_by_turn.insert(self.turn, self.deck.len());
 
         self.floating_mana.clear();
         strategy.cleanup();
 
+        // Extra turns are taken before the opponent gets to take theirs
+        if self.extra_turns > 0 {
+            self.extra_turns -= 1;
+            self.log(Phase::Game, "Taking an extra turn.".to_owned());
+            return GameStatus::Continue;
+        }
+
         // Opponent is taking a turn an drawing from potentially empty library.
         // Count this as a win on this turn.
         self.opponent_library -= 1;
         if self.opponent_library < 0 {
-            self.log(format!(
-                "[Turn {turn:002}][Game]: Opponent began their turn and drew from empty library",
-                turn = self.turn
-            ));
+            self.log(Phase::Game, "Opponent began their turn and drew from empty library".to_owned());
             return GameStatus::Finished(Outcome::Win);
         }
 
         GameStatus::Continue
     }
 
-    /// Begins the turn, resetting land drops and advancing turn counter
-    pub fn begin_turn(&mut self) {
+    /// Begins the turn, resetting land drops and advancing turn counter.
+    /// The game is won if the opponent draws from an empty library on a skipped turn.
+    pub fn begin_turn(&mut self) -> GameStatus {
         self.available_land_drops = 1;
         self.storm = 0;
+        self.creatures_died_this_turn.clear();
 
         // Skip any turns due to cards like "Meditate"
         for _ in 0..self.turns_to_skip {
             self.turn += 1;
-            self.log(format!(
+            self.log(Phase::Info, format!(
                 "======================[ TURN {turn:002} ]===========================",
                 turn = self.turn
             ));
-            self.log(format!(
-                "[Turn {turn:002}][Game]: Skipping the turn.",
-                turn = self.turn,
-            ));
+            self.log(Phase::Game, "Skipping the turn.".to_owned());
+
+            // Opponent takes another turn in a row, drawing again
+            self.opponent_library -= 1;
+            if self.opponent_library < 0 {
+                self.log(Phase::Game, "Opponent began their turn and drew from empty library".to_owned());
+                self.turns_to_skip = 0;
+                return GameStatus::Finished(Outcome::Win);
+            }
         }
 
         self.turns_to_skip = 0;
         self.turn += 1;
+
+        GameStatus::Continue
     }
 
     /// Takes starting hands and decides whether to keep or mulligan them based on the strategy.
@@ -487,13 +1505,12 @@ impl Game {
             // Draw the starting hand
             self.draw_n(7);
             self.print_hand();
-            if strategy.is_keepable_hand(self, self.mulligan_count) {
-                self.log(format!(
-                    "[Turn {turn:002}][Action]: Keeping a hand of {cards} cards.",
-                    turn = self.turn,
+            if self.is_keepable_hand(strategy.as_ref()) {
+                self.log(Phase::Action, format!(
+                    "Keeping a hand of {cards} cards.",
                     cards = 7 - self.mulligan_count
                 ));
-                let bottomed = strategy.discard_to_hand_size(self, 7 - self.mulligan_count);
+                let bottomed = self.discard_to_hand_size(strategy.as_ref(), 7 - self.mulligan_count);
 
                 if !bottomed.is_empty() {
                     let bottomed_str = bottomed
@@ -501,37 +1518,30 @@ impl Game {
                         .map(|card| card.borrow().name.clone())
                         .collect::<Vec<_>>()
                         .join(", ");
-                    self.log(format!("[Turn {turn:002}][Action]: Putting {count} cards on bottom: {bottomed_str}",
-                        count = bottomed.len(),
-                        turn = self.turn));
+                    self.log(Phase::Action, format!("Putting {count} cards on bottom: {bottomed_str}",
+                        count = bottomed.len()));
                 }
 
                 for card in bottomed {
                     // Return the cards to library
-                    card.borrow_mut().zone = Zone::Library;
+                    self.move_to_zone(&card, Zone::Library);
                     self.deck.put_bottom(card.clone())
                 }
                 break;
             } else {
-                let hand = self
-                    .game_objects
-                    .iter()
-                    .filter(is_hand)
-                    .map(Rc::clone)
-                    .collect::<Vec<_>>();
+                let hand = self.hand().collect::<Vec<_>>();
 
                 for card in hand {
-                    card.borrow_mut().zone = Zone::Library;
+                    self.move_to_zone(&card, Zone::Library);
                     self.deck.put_bottom(card.clone());
                 }
 
-                self.deck.shuffle();
+                self.deck.shuffle_with(&mut self.shuffle_rng);
             }
             self.mulligan_count += 1;
-            self.log(format!(
-                "[Turn {turn:002}][Action]: Taking a mulligan number {mulligan_count}.",
-                mulligan_count = self.mulligan_count,
-                turn = self.turn
+            self.log(Phase::Action, format!(
+                "Taking a mulligan number {mulligan_count}.",
+                mulligan_count = self.mulligan_count
             ));
         }
     }
@@ -560,23 +1570,31 @@ impl Game {
         // Produce colors in this priority order for now, producing 2 of each color first
         // TODO: Consider life loss here
         let colors = [Mana::Green, Mana::Blue, Mana::Black, Mana::White, Mana::Red];
+        let mut pain = 0;
+
+        let mut lands = self
+            .game_objects
+            .iter()
+            .filter(|card| is_battlefield(card) && is_card_type(card, &CardType::Land) && !is_tapped(card))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Tap the lands producing more than one mana first, leaving the rest to top up the colors
+        lands.sort_by_key(|land| Reverse(land.borrow().produced_mana.values().max().copied().unwrap_or(0)));
 
-        for land in self.game_objects.iter().filter(|card| {
-            is_battlefield(card) && is_card_type(card, &CardType::Land) && !is_tapped(card)
-        }) {
+        for land in lands.iter() {
             let mut land_used = false;
-            // First try to produce colors we have the least of
+            // First try to produce colors we have the least of, without going over 2 of the color
             for color in colors.iter() {
                 let land_name = land.borrow().name.clone();
                 let floating = self.floating_mana.entry(*color).or_insert(0);
 
-                if *floating < 2 {
-                    if let Some(mana) = land.borrow().produced_mana.get(color) {
+                if let Some(mana) = land.borrow().produced_mana.get(color) {
+                    if *floating + mana <= 2 {
                         *floating += mana;
 
-                        self.log(format!(
-                            "[Turn {turn:002}][Action]: Floating {mana} {color:?} mana from \"{land_name}\".",
-                            turn = self.turn
+                        self.log(Phase::Action, format!(
+                            "Floating {mana} {color:?} mana from \"{land_name}\"."
                         ));
                         land_used = true;
                         break;
@@ -585,14 +1603,16 @@ impl Game {
             }
             if !land_used {
                 // Then fall back to just producing some mana the land produces
-                for (color, mana) in land.borrow().produced_mana.iter() {
+                let mut produced_mana = land.borrow().produced_mana.clone().into_iter().collect::<Vec<_>>();
+                produced_mana.sort();
+
+                for (color, mana) in produced_mana {
                     let land_name = land.borrow().name.clone();
-                    let floating = self.floating_mana.entry(*color).or_insert(0);
+                    let floating = self.floating_mana.entry(color).or_insert(0);
                     *floating += mana;
 
-                    self.log(format!(
-                        "[Turn {turn:002}][Action]: Floating {mana} {color:?} mana from \"{land_name}\".",
-                        turn = self.turn
+                    self.log(Phase::Action, format!(
+                        "Floating {mana} {color:?} mana from \"{land_name}\"."
                     ));
 
                     land_used = true;
@@ -601,6 +1621,13 @@ impl Game {
             }
 
             land.borrow_mut().is_tapped = land_used;
+            if land_used {
+                pain += land.borrow().pain;
+            }
+        }
+
+        if pain > 0 {
+            self.take_damage(pain);
         }
     }
 
@@ -613,12 +1640,11 @@ impl Game {
     }
 
     pub fn print_life(&self) {
-        self.log(format!(
-            "[Turn {turn:002}][Game]: Life total: {life}, Damage dealt: {damage}, Opponent's library: {library}",
+        self.log(Phase::Game, format!(
+            "Life total: {life}, Damage dealt: {damage}, Opponent's library: {library}",
             life = self.life_total,
             damage = self.damage_dealt,
             library = self.opponent_library,
-            turn = self.turn,
         ));
     }
 
@@ -630,10 +1656,7 @@ impl Game {
             .map(|card| card.borrow().name.clone())
             .collect::<Vec<_>>()
             .join(", ");
-        self.log(format!(
-            "[Turn {turn:002}][Battlefield]: {battlefield_str}",
-            turn = self.turn
-        ));
+        self.log(Phase::Battlefield, battlefield_str);
     }
 
     fn print_graveyard(&self) {
@@ -644,10 +1667,7 @@ impl Game {
             .map(|card| card.borrow().name.clone())
             .collect::<Vec<_>>()
             .join(", ");
-        self.log(format!(
-            "[Turn {turn:002}][Graveyard]: {graveyard_str}",
-            turn = self.turn
-        ));
+        self.log(Phase::Graveyard, graveyard_str);
 
         let exile_str = self
             .game_objects
@@ -658,14 +1678,13 @@ impl Game {
             .join(", ");
 
         if !exile_str.is_empty() {
-            self.log(format!("[Turn {turn:002}][Exile]: {exile_str}", turn = self.turn));
+            self.log(Phase::Exile, exile_str);
         }
     }
 
     fn print_library(&self) {
-        self.log(format!(
-            "[Turn {turn:002}][Library]: {deck} cards remaining.",
-            turn = self.turn,
+        self.log(Phase::Library, format!(
+            "{deck} cards remaining.",
             deck = self.deck.len()
         ));
     }
@@ -678,7 +1697,7 @@ impl Game {
             .map(|card| card.borrow().name.clone())
             .collect::<Vec<_>>()
             .join(", ");
-        self.log(format!("[Turn {turn:002}][Hand]: {hand_str}", turn = self.turn));
+        self.log(Phase::Hand, hand_str);
     }
 }
 
@@ -687,8 +1706,64 @@ impl Game {
 mod tests {
     use super::*;
     use crate::card::{Card};
+    use crate::effect::Effect;
+    use crate::strategy::pattern_combo::PatternCombo;
+    use crate::strategy::{from_enum, DeckStrategy, ScriptStep, Scripted, STRATEGIES};
     use rand::seq::SliceRandom;
     use rand::thread_rng;
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+
+    struct CapturingObserver {
+        actions: Rc<RefCell<Vec<String>>>,
+        zone_changes: Rc<RefCell<Vec<(String, Zone, Zone)>>>,
+    }
+
+    impl GameObserver for CapturingObserver {
+        fn on_action(&self, _turn: usize, message: &str) {
+            self.actions.borrow_mut().push(message.to_owned());
+        }
+
+        fn on_zone_change(&self, card: &CardRef, from: &Zone, to: &Zone) {
+            self.zone_changes.borrow_mut().push((card.borrow().name.clone(), from.clone(), to.clone()));
+        }
+    }
+
+    #[test]
+    fn it_uses_any_pitch_for_mana_card_from_hand_like_elvish_spirit_guide() {
+        let simian_spirit_guide = Card::new_with_zone("Simian Spirit Guide", Zone::Hand);
+        let shock = Rc::new(RefCell::new(Card {
+            name: String::from("Shock"),
+            card_types: HashSet::from([CardType::Instant]),
+            cost: HashMap::from([(Mana::Red, 1)]),
+            zone: Zone::Hand,
+            ..Default::default()
+        }));
+
+        let mut game = Game {
+            game_objects: vec![simian_spirit_guide.clone(), shock.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        let castable = game.find_castable();
+        let (_, payment) = castable
+            .iter()
+            .find(|(card, _)| Rc::ptr_eq(card, &shock))
+            .expect("should be castable by pitching Simian Spirit Guide");
+
+        assert_eq!(1, payment.payment.len());
+        assert!(Rc::ptr_eq(&simian_spirit_guide, &payment.payment[0]));
+
+        game.cast_spell(&PatternCombo {}, &shock, payment, None);
+
+        assert_eq!(Zone::Exile, simian_spirit_guide.borrow().zone);
+
+        // Only usable for mana from hand, not as a creature on the battlefield
+        let cast_simian_spirit_guide = Card::new_with_zone("Simian Spirit Guide", Zone::Battlefield);
+        game.game_objects.push(cast_simian_spirit_guide.clone());
+        assert!(!game.tap_for_mana(&cast_simian_spirit_guide, Mana::Red));
+    }
 
     #[test]
     fn it_avoids_using_limited_use_lands() {
@@ -767,4 +1842,1400 @@ mod tests {
         let castable = game.find_castable();
         assert_eq!(true, castable.is_empty());
     }
+
+    #[cfg(feature = "custom_cards")]
+    #[test]
+    fn it_casts_a_custom_card_loaded_from_json() {
+        let grizzly_bears = Card::from_json(r#"{
+            "name": "Grizzly Bears",
+            "card_types": ["Creature"],
+            "cost": { "Green": 1, "Generic": 1 },
+            "power": 2,
+            "toughness": 2
+        }"#)
+        .unwrap();
+
+        let custom_cards = HashMap::from([(grizzly_bears.name.clone(), grizzly_bears)]);
+        let decklist = "2 Forest\n1 Grizzly Bears".parse::<Decklist>().unwrap();
+
+        let mut game = Game::with_custom_cards(&decklist, &GameConfig::default(), &custom_cards).unwrap();
+        for card in game.game_objects.clone() {
+            let zone = if is_named(&&card, "Forest") { Zone::Battlefield } else { Zone::Hand };
+            game.move_to_zone(&card, zone);
+        }
+
+        let castable = game.find_castable();
+        let (card, payment) = castable.first().unwrap();
+        game.cast_spell(&PatternCombo {}, card, payment, None);
+
+        assert_eq!(1, game.count_in_zone("Grizzly Bears", Zone::Battlefield));
+        assert_eq!(2, card.borrow().power);
+    }
+
+    #[test]
+    fn it_notifies_observer_of_cast_spells() {
+        let forest = Card::new_with_zone("Forest", Zone::Battlefield);
+        let llanowar_elves = Card::new_with_zone("Llanowar Elves", Zone::Hand);
+
+        let actions = Rc::new(RefCell::new(Vec::new()));
+        let zone_changes = Rc::new(RefCell::new(Vec::new()));
+
+        let mut game = Game {
+            game_objects: vec![forest, llanowar_elves],
+            life_total: 20,
+            is_first_player: true,
+            available_land_drops: 1,
+            observer: Some(Box::new(CapturingObserver {
+                actions: actions.clone(),
+                zone_changes: zone_changes.clone(),
+            })),
+            ..Default::default()
+        };
+
+        let castable = game.find_castable();
+        let (card, payment) = castable.first().unwrap();
+        game.cast_spell(&PatternCombo {}, card, payment, None);
+
+        assert!(actions.borrow().iter().any(|action| action.contains("Casting card: \"Llanowar Elves\"")));
+        assert_eq!(
+            vec![(String::from("Llanowar Elves"), Zone::Hand, Zone::Battlefield)],
+            *zone_changes.borrow()
+        );
+    }
+
+    #[test]
+    fn it_renders_log_events_as_game_output() {
+        let forest = Card::new_with_zone("Forest", Zone::Hand);
+
+        let mut game = Game {
+            game_objects: vec![forest.clone()],
+            available_land_drops: 1,
+            ..Default::default()
+        };
+
+        game.log(Phase::Info, "=====================[ END OF GAME ]========================".to_owned());
+        game.play_land(forest);
+
+        let result = GameResult {
+            output: game.output.lock().unwrap().clone(),
+            ..game_result(Outcome::Win, 0)
+        };
+
+        assert_eq!(
+            Phase::Action,
+            result.output[1].phase
+        );
+        assert_eq!(
+            vec![
+                String::from("=====================[ END OF GAME ]========================"),
+                String::from("[Turn 00][Action]: Playing land: \"Forest\""),
+            ],
+            result.output_lines()
+        );
+    }
+
+    #[test]
+    fn it_steps_turns_to_the_same_result_as_running() {
+        for deck_strategy in STRATEGIES.iter() {
+            let decklist = from_enum(deck_strategy).default_decklist();
+
+            for seed in 0..10 {
+                let mut game = Game::with_seed(&decklist, seed).unwrap();
+                let result = game.run(&mut from_enum(deck_strategy));
+
+                let mut strategy = from_enum(deck_strategy);
+                let mut stepped = Game::with_seed(&decklist, seed).unwrap();
+                let mut turns = 0;
+                let outcome = loop {
+                    turns += 1;
+                    if let GameStatus::Finished(outcome) = stepped.step_turn(&mut strategy) {
+                        break outcome;
+                    }
+                };
+                let stepped_result = stepped.finish(&mut strategy, outcome);
+
+                assert_eq!(result.result, stepped_result.result);
+                assert_eq!(result.turn, stepped_result.turn);
+                assert_eq!(result.output, stepped_result.output);
+                assert!(turns <= stepped_result.turn);
+            }
+        }
+    }
+
+    #[test]
+    fn it_skips_to_the_turn_the_combo_is_ready() {
+        let forests = (0..4)
+            .map(|_| Card::new_with_zone("Forest", Zone::Battlefield))
+            .collect::<Vec<_>>();
+        let aluren = Card::new_with_zone("Aluren", Zone::Hand);
+        let pieces = ["Cavern Harpy", "Maggot Carrier", "Soul Warden"]
+            .iter()
+            .map(|name| Card::new_with_zone(name, Zone::Hand))
+            .collect::<Vec<_>>();
+        let library = (0..5)
+            .map(|_| Card::new_with_zone("Island", Zone::Library))
+            .collect::<Vec<_>>();
+
+        let mut game = Game {
+            game_objects: [forests, vec![aluren.clone()], pieces, library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            turn: 1,
+            life_total: 20,
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        // Aluren only gets cast on the second turn stepped
+        let mut strategy: Box<dyn Strategy> = Box::new(Scripted::new(
+            vec![
+                ScriptStep::Pass,
+                ScriptStep::Cast("Aluren".to_owned()),
+                ScriptStep::Pass,
+            ],
+            from_enum(&DeckStrategy::Aluren),
+        ));
+
+        assert!(matches!(game.skip_to_combo(&mut strategy), GameStatus::Continue));
+        assert_eq!(3, game.turn);
+        assert_eq!(Zone::Battlefield, aluren.borrow().zone);
+        assert_eq!(1.0, strategy.combo_progress(&game));
+    }
+
+    #[test]
+    fn it_replays_recorded_game_to_identical_state() {
+        for deck_strategy in STRATEGIES.iter() {
+            let decklist = from_enum(deck_strategy).default_decklist();
+
+            for seed in 0..10 {
+                let mut game = Game::with_seed(&decklist, seed).unwrap();
+                let result = game.run(&mut from_enum(deck_strategy));
+                let replay = game.recorded_replay();
+
+                let mut replayed = Game::replay(&decklist, &replay).unwrap();
+                let replayed_result = replayed.run(&mut from_enum(deck_strategy));
+
+                let zones = |game: &Game| {
+                    game.game_objects
+                        .iter()
+                        .map(|card| (card.borrow().name.clone(), card.borrow().zone.clone()))
+                        .collect::<Vec<_>>()
+                };
+
+                assert_eq!(result.result, replayed_result.result);
+                assert_eq!(result.turn, replayed_result.turn);
+                assert_eq!(result.output, replayed_result.output);
+                assert_eq!(zones(&game), zones(&replayed));
+                assert_eq!(replay, replayed.recorded_replay());
+            }
+        }
+    }
+
+    #[test]
+    fn it_reproduces_the_game_from_the_seed_alone() {
+        for deck_strategy in STRATEGIES.iter() {
+            let decklist = from_enum(deck_strategy).default_decklist();
+
+            for seed in 0..10 {
+                let mut game = Game::with_seed(&decklist, seed).unwrap();
+                let result = game.run(&mut from_enum(deck_strategy));
+
+                let seed_only = Replay {
+                    seed,
+                    decisions: Vec::new(),
+                };
+                let mut replayed = Game::replay(&decklist, &seed_only).unwrap();
+                let replayed_result = replayed.run(&mut from_enum(deck_strategy));
+
+                assert_eq!(result, replayed_result);
+                assert_eq!(game.recorded_replay(), replayed.recorded_replay());
+            }
+        }
+    }
+
+    #[test]
+    fn it_reports_mana_available_by_turn() {
+        let forest_1 = Card::new_with_zone("Forest", Zone::Hand);
+        let forest_2 = Card::new_with_zone("Forest", Zone::Hand);
+
+        let mut strategy: Box<dyn Strategy> = Box::new(Scripted::new(
+            vec![
+                ScriptStep::PlayLand("Forest".to_owned()),
+                ScriptStep::Pass,
+                ScriptStep::PlayLand("Forest".to_owned()),
+                ScriptStep::Pass,
+            ],
+            Box::new(FloatsMana(false)),
+        ));
+        let mut game = Game {
+            game_objects: vec![forest_1, forest_2],
+            life_total: 20,
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        for _ in 0..2 {
+            game.begin_turn();
+            game.untap();
+            game.take_game_actions(&mut strategy);
+            // Tapping the lands for mana afterwards doesn't take away from what was available
+            game.float_mana();
+            game.cleanup(&mut strategy);
+        }
+
+        assert_eq!(Some(&1), game.mana_available_by_turn[&1].get(&Mana::Green));
+        assert_eq!(Some(&2), game.mana_available_by_turn[&2].get(&Mana::Green));
+        assert_eq!(None, game.mana_available_by_turn[&2].get(&Mana::Blue));
+    }
+
+    #[test]
+    fn it_leaves_tapped_sources_out_of_the_available_mana() {
+        let forest = Card::new_with_zone("Forest", Zone::Battlefield);
+        let island = Card::new_with_zone("Island", Zone::Battlefield);
+        forest.borrow_mut().is_tapped = true;
+
+        let game = Game {
+            game_objects: vec![forest, island],
+            ..Default::default()
+        };
+
+        let available = game.available_mana();
+
+        assert_eq!(Some(&1), available.get(&Mana::Blue));
+        assert_eq!(None, available.get(&Mana::Green));
+    }
+
+    #[test]
+    fn it_reports_library_size_thinned_by_fetchlands() {
+        let decklist = "1 Wooded Foothills\n59 Forest".parse::<Decklist>().unwrap();
+        let mut strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+        let mut game = Game::new(&decklist).unwrap();
+        game.begin_turn();
+
+        assert_eq!(60, game.library_start_size);
+        assert_eq!(0, game.library_thinning());
+
+        let wooded_foothills = game.deck.maindeck.iter().find(|card| is_named(card, "Wooded Foothills")).cloned().unwrap();
+        game.deck.remove(&wooded_foothills);
+        game.move_to_zone(&wooded_foothills, Zone::Graveyard);
+
+        let effect = wooded_foothills.borrow().on_resolve.clone().unwrap();
+        effect.resolve(&mut game, &wooded_foothills, &PatternCombo {});
+        game.cleanup(&mut strategy);
+
+        assert_eq!(58, game.deck.len());
+        assert_eq!(2, game.library_thinning());
+        assert_eq!(BTreeMap::from([(1, 58)]), game.library_size_by_turn);
+    }
+
+    #[test]
+    fn it_sacrifices_gemstone_mine_after_its_last_charge() {
+        let gemstone_mine = Card::new_with_zone("Gemstone Mine", Zone::Battlefield);
+        let mut game = Game {
+            game_objects: vec![gemstone_mine.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        for expected_charges in [2, 1] {
+            assert!(game.tap_for_mana(&gemstone_mine, Mana::Green));
+            assert_eq!(Some(expected_charges), gemstone_mine.borrow().charges);
+            assert_eq!(Zone::Battlefield, gemstone_mine.borrow().zone);
+            gemstone_mine.borrow_mut().is_tapped = false;
+        }
+
+        assert!(game.tap_for_mana(&gemstone_mine, Mana::Green));
+        assert_eq!(Zone::Graveyard, gemstone_mine.borrow().zone);
+        assert_eq!(Some(&3), game.floating_mana.get(&Mana::Green));
+    }
+
+    #[test]
+    fn it_sacrifices_one_shot_lotus_petal_after_one_use() {
+        let lotus_petal = Card::new_with_zone("Lotus Petal", Zone::Battlefield);
+        let mut game = Game {
+            game_objects: vec![lotus_petal.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        assert!(game.tap_for_mana(&lotus_petal, Mana::Blue));
+        assert_eq!(Zone::Graveyard, lotus_petal.borrow().zone);
+        assert!(!game.tap_for_mana(&lotus_petal, Mana::Blue));
+        assert_eq!(Some(&1), game.floating_mana.get(&Mana::Blue));
+    }
+
+    #[test]
+    fn it_stops_resolving_effects_after_brainstorm_draws_from_an_empty_library() {
+        let mut strategy: Box<dyn Strategy> = Box::new(Scripted::new(
+            vec![ScriptStep::Cast(String::from("Brainstorm"))],
+            from_enum(&DeckStrategy::Aluren),
+        ));
+
+        let library = vec![
+            Card::new_with_zone("Forest", Zone::Library),
+            Card::new_with_zone("Island", Zone::Library),
+        ];
+        let brainstorm = Card::new_with_zone("Brainstorm", Zone::Hand);
+        let brain_freeze = Card::new_with_zone("Brain Freeze", Zone::Graveyard);
+
+        let mut game = Game {
+            game_objects: [vec![brainstorm.clone(), brain_freeze.clone()], library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            floating_mana: HashMap::from([(Mana::Blue, 1)]),
+            turn: 2,
+            life_total: 20,
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        // Brainstorm draws three cards out of the two in the library
+        assert!(matches!(
+            game.take_game_actions(&mut strategy),
+            GameStatus::Finished(Outcome::Lose)
+        ));
+        assert_eq!(Zone::Graveyard, brainstorm.borrow().zone);
+        assert!(game.drew_from_empty_library);
+
+        // Nothing resolves once the game is lost, not even a Brain Freeze with storm
+        assert_eq!(1, game.storm);
+        game.handle_on_resolve_effects(&brain_freeze, &PatternCombo {});
+        assert_eq!(60, game.opponent_library);
+    }
+
+    #[test]
+    fn it_loses_when_an_effect_draws_from_an_empty_library() {
+        let mut strategy: Box<dyn Strategy> = Box::new(Scripted::new(
+            vec![ScriptStep::Cast(String::from("Meditate"))],
+            from_enum(&DeckStrategy::Aluren),
+        ));
+
+        let library = vec![
+            Card::new_with_zone("Forest", Zone::Library),
+            Card::new_with_zone("Island", Zone::Library),
+        ];
+        let meditate = Card::new_with_zone("Meditate", Zone::Hand);
+
+        let mut game = Game {
+            game_objects: [vec![meditate.clone()], library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            floating_mana: HashMap::from([(Mana::Blue, 3)]),
+            turn: 2,
+            life_total: 20,
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            game.take_game_actions(&mut strategy),
+            GameStatus::Finished(Outcome::Lose)
+        ));
+        assert_eq!(Zone::Graveyard, meditate.borrow().zone);
+        assert!(game.deck.is_empty());
+        assert!(matches!(game.classify_loss(), LossReason::DeckedOut));
+    }
+
+    #[test]
+    fn it_records_floating_mana_wasted_at_end_of_turn() {
+        let mut strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+        let mut game = Game {
+            life_total: 20,
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        game.begin_turn();
+        game.floating_mana.insert(Mana::Green, 2);
+        game.cleanup(&mut strategy);
+
+        game.begin_turn();
+        game.cleanup(&mut strategy);
+
+        assert_eq!(BTreeMap::from([(1, 2), (2, 0)]), game.wasted_mana_by_turn);
+        assert!(game.floating_mana.values().all(|amount| *amount == 0));
+    }
+
+    #[test]
+    fn it_sacrifices_karmic_guide_on_next_upkeep_for_echo() {
+        let karmic_guide = Card::new_with_zone("Karmic Guide", Zone::Battlefield);
+        karmic_guide.borrow_mut().is_summoning_sick = true;
+
+        let mut game = Game {
+            game_objects: vec![karmic_guide.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        game.untap();
+
+        assert_eq!(Zone::Graveyard, karmic_guide.borrow().zone);
+    }
+
+    #[test]
+    fn it_returns_two_small_creatures_when_reveillark_is_sacrificed() {
+        let reveillark = Card::new_with_zone("Reveillark", Zone::Battlefield);
+        let veteran_explorer = Card::new_with_zone("Veteran Explorer", Zone::Graveyard);
+        let academy_rector = Card::new_with_zone("Academy Rector", Zone::Graveyard);
+        let phantom_nishoba = Card::new_with_zone("Phantom Nishoba", Zone::Graveyard);
+
+        let mut game = Game {
+            game_objects: vec![
+                reveillark.clone(),
+                veteran_explorer.clone(),
+                academy_rector.clone(),
+                phantom_nishoba.clone(),
+            ],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        game.sacrifice(&reveillark, &PatternCombo {});
+
+        assert_eq!(Zone::Graveyard, reveillark.borrow().zone);
+        assert_eq!(Zone::Battlefield, veteran_explorer.borrow().zone);
+        assert_eq!(Zone::Battlefield, academy_rector.borrow().zone);
+        assert_eq!(Zone::Graveyard, phantom_nishoba.borrow().zone);
+    }
+
+    #[test]
+    fn it_tracks_the_creatures_that_died_this_turn() {
+        let veteran_explorer = Card::new_with_zone("Veteran Explorer", Zone::Battlefield);
+        let academy_rector = Card::new_with_zone("Academy Rector", Zone::Battlefield);
+        let pattern_of_rebirth = Card::new_with_zone("Pattern of Rebirth", Zone::Battlefield);
+
+        let mut game = Game {
+            game_objects: vec![veteran_explorer.clone(), academy_rector.clone(), pattern_of_rebirth.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        game.sacrifice(&veteran_explorer, &PatternCombo {});
+        game.sacrifice(&academy_rector, &PatternCombo {});
+        game.sacrifice(&pattern_of_rebirth, &PatternCombo {});
+
+        assert_eq!(2, game.creatures_died_this_turn.len());
+        assert!(same_card(&veteran_explorer, &game.creatures_died_this_turn[0]));
+        assert!(same_card(&academy_rector, &game.creatures_died_this_turn[1]));
+
+        game.begin_turn();
+
+        assert!(game.creatures_died_this_turn.is_empty());
+    }
+
+    #[test]
+    fn it_removes_a_sacrificed_token_from_the_game() {
+        let mut game = Game {
+            life_total: 20,
+            ..Default::default()
+        };
+
+        let bear = game.create_token("Bear", 2, 2, HashSet::from([CardType::Creature]));
+
+        assert!(is_battlefield(&&bear) && is_card_type(&&bear, &CardType::Creature));
+        assert_eq!(1, game.count_in_zone("Bear", Zone::Battlefield));
+
+        game.sacrifice(&bear, &PatternCombo {});
+
+        assert_eq!(Zone::Outside, bear.borrow().zone);
+        assert_eq!(0, game.count_in_zone("Bear", Zone::Graveyard));
+        assert!(same_card(&bear, &game.creatures_died_this_turn[0]));
+    }
+
+    #[test]
+    fn it_reports_final_life_and_storm() {
+        let mut strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+        let decklist = strategy.default_decklist();
+        let config = GameConfig {
+            seed: Some(1),
+            life_total: 13,
+            ..Default::default()
+        };
+
+        let mut game = Game::with_config(&decklist, &config).unwrap();
+        let result = game.run(&mut strategy);
+
+        // Pattern Combo doesn't deal or take any damage on its own
+        assert_eq!(13, result.final_life);
+        assert_eq!(0, result.damage_dealt);
+        assert!(result.max_storm >= 1);
+        assert_eq!(game.max_storm, result.max_storm);
+    }
+
+    #[test]
+    fn it_picks_the_kill_turns_by_their_weights() {
+        let decklist = "60 Forest".parse::<Decklist>().unwrap();
+        let disruption = DisruptionModel {
+            turn: 1,
+            disruption: Disruption::Discard,
+            kill_turns: vec![(3, 20), (4, 50), (5, 30)],
+        };
+
+        let games = 5000;
+        let mut kill_turns: BTreeMap<usize, usize> = BTreeMap::new();
+        for seed in 0..games {
+            let config = GameConfig {
+                seed: Some(seed),
+                disruption: Some(disruption.clone()),
+                ..Default::default()
+            };
+            let game = Game::with_config(&decklist, &config).unwrap();
+            *kill_turns.entry(game.kill_turn.unwrap()).or_default() += 1;
+        }
+
+        for (turn, weight) in disruption.kill_turns.iter() {
+            let realized = kill_turns[turn] as f64 / games as f64;
+            let expected = *weight as f64 / 100.0;
+            assert!((realized - expected).abs() < 0.03, "turn {turn}: {realized} vs {expected}");
+        }
+    }
+
+    /// Has its combo ready from the first turn, but only goes for the win on the third
+    struct SlowCombo {
+        win_turn: usize,
+    }
+
+    impl Strategy for SlowCombo {
+        fn name(&self) -> String {
+            "Slow combo".to_owned()
+        }
+
+        fn description(&self) -> &'static str {
+            "Wins on the given turn"
+        }
+
+        fn default_decklist(&self) -> Decklist {
+            "60 Forest".parse().unwrap()
+        }
+
+        fn game_status(&self, game: &Game) -> GameStatus {
+            if game.turn >= self.win_turn {
+                return GameStatus::Finished(Outcome::Win);
+            }
+
+            GameStatus::Continue
+        }
+
+        fn combo_progress(&self, _game: &Game) -> f32 {
+            1.0
+        }
+
+        fn is_keepable_hand(&self, _game: &Game, _mulligan_count: usize) -> bool {
+            true
+        }
+
+        fn take_game_action(&mut self, _game: &mut Game) -> bool {
+            false
+        }
+
+        fn select_best(&self, _game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
+            cards.into_values().flatten().next()
+        }
+    }
+
+    #[test]
+    fn it_records_the_delay_between_the_first_winnable_turn_and_the_win() {
+        let mut strategy: Box<dyn Strategy> = Box::new(SlowCombo { win_turn: 3 });
+        let mut game = Game::with_seed(&strategy.default_decklist(), 0).unwrap();
+
+        let result = game.run(&mut strategy);
+
+        assert_eq!(Outcome::Win, result.result);
+        assert_eq!(3, result.turn);
+        assert_eq!(Some(1), result.first_winnable_turn);
+        assert_eq!(Some(2), result.win_delay());
+    }
+
+    #[test]
+    fn it_counts_the_wins_after_the_success_turn_cap_as_losses() {
+        for (win_turn, outcome, loss_reason) in [(5, Outcome::Win, None), (6, Outcome::Lose, Some(LossReason::TooSlow))] {
+            let mut strategy: Box<dyn Strategy> = Box::new(SlowCombo { win_turn });
+            let config = GameConfig {
+                seed: Some(0),
+                success_turn_cap: Some(5),
+                ..Default::default()
+            };
+            let mut game = Game::with_config(&strategy.default_decklist(), &config).unwrap();
+
+            let result = game.run(&mut strategy);
+
+            assert_eq!(win_turn, result.turn);
+            assert_eq!(outcome, result.result);
+            assert_eq!(loss_reason, result.loss_reason);
+            assert_eq!(Some(1), result.first_winnable_turn);
+        }
+    }
+
+    #[test]
+    fn it_leaves_the_loss_reason_empty_on_a_draw() {
+        let mut strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+        let mut game = Game {
+            turn: 50,
+            life_total: 20,
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        let result = game.finish(&mut strategy, Outcome::Draw);
+
+        assert_eq!(Outcome::Draw, result.result);
+        assert_eq!(None, result.loss_reason);
+    }
+
+    struct FloatsMana(bool);
+
+    impl Strategy for FloatsMana {
+        fn name(&self) -> String {
+            "Floats mana".to_owned()
+        }
+
+        fn description(&self) -> &'static str {
+            "Floats the mana of the lands when asked to"
+        }
+
+        fn default_decklist(&self) -> Decklist {
+            "60 Forest".parse().unwrap()
+        }
+
+        fn is_keepable_hand(&self, _game: &Game, _mulligan_count: usize) -> bool {
+            true
+        }
+
+        fn take_game_action(&mut self, _game: &mut Game) -> bool {
+            false
+        }
+
+        fn should_float_mana(&self, _game: &Game) -> bool {
+            self.0
+        }
+
+        fn select_best(&self, _game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
+            cards.into_values().flatten().next()
+        }
+    }
+
+    #[test]
+    fn it_floats_mana_when_the_strategy_asks_for_it() {
+        for should_float_mana in [true, false] {
+            let forest_1 = Card::new_with_zone("Forest", Zone::Battlefield);
+            let forest_2 = Card::new_with_zone("Forest", Zone::Battlefield);
+
+            let mut strategy: Box<dyn Strategy> = Box::new(FloatsMana(should_float_mana));
+            let mut game = Game {
+                game_objects: vec![forest_1.clone(), forest_2.clone()],
+                life_total: 20,
+                opponent_library: 60,
+                ..Default::default()
+            };
+
+            game.take_game_actions(&mut strategy);
+
+            let floating = if should_float_mana { Some(&2) } else { None };
+            assert_eq!(floating, game.floating_mana.get(&Mana::Green));
+            assert_eq!(should_float_mana, is_tapped(&&forest_1));
+            assert_eq!(should_float_mana, is_tapped(&&forest_2));
+        }
+    }
+
+    #[test]
+    fn it_floats_two_green_from_hickory_woodlot_without_going_over() {
+        let savannah = Card::new_with_zone("Savannah", Zone::Battlefield);
+        let hickory_woodlot = Card::new_with_zone("Hickory Woodlot", Zone::Battlefield);
+        hickory_woodlot.borrow_mut().is_tapped = false;
+
+        let mut game = Game {
+            game_objects: vec![savannah.clone(), hickory_woodlot.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        game.float_mana();
+
+        assert_eq!(Some(&2), game.floating_mana.get(&Mana::Green));
+        assert_eq!(Some(&1), game.floating_mana.get(&Mana::White));
+        assert!(is_tapped(&&savannah) && is_tapped(&&hickory_woodlot));
+    }
+
+    #[test]
+    fn it_loses_to_the_clock_of_the_opponent() {
+        let decklist = "60 Forest".parse::<Decklist>().unwrap();
+
+        for (is_first_player, last_turn) in [(true, 3), (false, 2)] {
+            let config = GameConfig {
+                seed: Some(0),
+                is_first_player,
+                disruption: Some(DisruptionModel {
+                    turn: 10,
+                    disruption: Disruption::Counter,
+                    kill_turns: vec![(3, 1)],
+                }),
+                ..Default::default()
+            };
+
+            let mut game = Game::with_config(&decklist, &config).unwrap();
+            let result = game.run(&mut from_enum(&DeckStrategy::Aluren));
+
+            assert_eq!(Outcome::Lose, result.result);
+            assert_eq!(last_turn + 1, result.turn);
+        }
+    }
+
+    #[test]
+    fn it_flips_a_coin_for_being_on_the_play() {
+        let decklist = "60 Forest".parse::<Decklist>().unwrap();
+
+        let on_the_play = (0..1000)
+            .filter(|seed| {
+                let config = GameConfig {
+                    seed: Some(*seed),
+                    is_first_player: false,
+                    random_play_draw: true,
+                    ..Default::default()
+                };
+
+                Game::with_config(&decklist, &config).unwrap().is_first_player
+            })
+            .count();
+
+        assert!((450..=550).contains(&on_the_play), "{on_the_play} games on the play");
+    }
+
+    #[test]
+    fn it_draws_the_same_cards_with_the_same_shuffle_seed() {
+        let strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+        let decklist = strategy.default_decklist();
+
+        let drawn_cards = |seed: u64| {
+            let config = GameConfig {
+                seed: Some(seed),
+                shuffle_seed: Some(7),
+                ..Default::default()
+            };
+
+            let mut game = Game::with_config(&decklist, &config).unwrap();
+            game.draw_n(10);
+
+            game.cards_in_zone(Zone::Hand)
+                .map(|card| card.borrow().name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(drawn_cards(1), drawn_cards(2));
+    }
+
+    #[test]
+    fn it_discards_one_random_card_on_turn_one_with_disruption() {
+        let hand = ["Forest", "Llanowar Elves", "Pattern of Rebirth", "Cabal Therapy"]
+            .iter()
+            .map(|name| Card::new_with_zone(name, Zone::Hand))
+            .collect::<Vec<_>>();
+
+        let mut game = Game {
+            game_objects: hand,
+            disruption: Some(DisruptionModel {
+                turn: 1,
+                disruption: Disruption::Discard,
+                kill_turns: Vec::new(),
+            }),
+            ..Default::default()
+        };
+
+        game.begin_turn();
+        game.apply_disruption();
+
+        assert_eq!(3, game.game_objects.iter().filter(is_hand).count());
+        assert_eq!(1, game.game_objects.iter().filter(is_graveyard).count());
+
+        // Disruption happens only once
+        game.apply_disruption();
+        assert_eq!(3, game.game_objects.iter().filter(is_hand).count());
+    }
+
+    #[test]
+    fn it_counters_the_next_spell_with_disruption() {
+        let strategy = PatternCombo {};
+        let elves = Card::new_with_zone("Llanowar Elves", Zone::Hand);
+
+        let mut game = Game {
+            game_objects: vec![elves.clone()],
+            disruption: Some(DisruptionModel {
+                turn: 1,
+                disruption: Disruption::Counter,
+                kill_turns: Vec::new(),
+            }),
+            ..Default::default()
+        };
+
+        game.begin_turn();
+        game.apply_disruption();
+        let payment = PaymentAndFloating {
+            payment: vec![],
+            floating: HashMap::new(),
+        };
+        game.cast_spell(&strategy, &elves, &payment, None);
+
+        assert_eq!(Zone::Graveyard, elves.borrow().zone);
+        assert_eq!(1, game.storm);
+        assert!(!game.counter_next_spell);
+    }
+
+    #[test]
+    fn it_delays_countering_with_defense_grid_on_battlefield() {
+        let defense_grid = Card::new_with_zone("Defense Grid", Zone::Battlefield);
+
+        let mut game = Game {
+            game_objects: vec![defense_grid],
+            disruption: Some(DisruptionModel {
+                turn: 1,
+                disruption: Disruption::Counter,
+                kill_turns: Vec::new(),
+            }),
+            ..Default::default()
+        };
+
+        for _ in 1..=3 {
+            game.begin_turn();
+            game.apply_disruption();
+            assert!(!game.counter_next_spell);
+        }
+
+        game.begin_turn();
+        game.apply_disruption();
+        assert_eq!(4, game.turn);
+        assert!(game.counter_next_spell);
+    }
+
+    #[test]
+    fn it_does_not_counter_spells_with_xantid_swarm_on_battlefield() {
+        let strategy = PatternCombo {};
+        let pattern = Card::new_with_zone("Pattern of Rebirth", Zone::Hand);
+        let xantid_swarm = Card::new_with_zone("Xantid Swarm", Zone::Battlefield);
+
+        let mut game = Game {
+            game_objects: vec![pattern.clone(), xantid_swarm.clone()],
+            disruption: Some(DisruptionModel {
+                turn: 1,
+                disruption: Disruption::Counter,
+                kill_turns: Vec::new(),
+            }),
+            ..Default::default()
+        };
+
+        game.begin_turn();
+        game.apply_disruption();
+        let payment = PaymentAndFloating {
+            payment: vec![],
+            floating: HashMap::new(),
+        };
+        game.cast_spell(&strategy, &pattern, &payment, Some(xantid_swarm.clone()));
+
+        assert_eq!(Zone::Battlefield, pattern.borrow().zone);
+        assert!(pattern.borrow().attached_to.is_some());
+        assert!(game.counter_next_spell);
+    }
+
+    #[test]
+    fn it_counts_cards_in_zone() {
+        let strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+        let decklist = strategy.default_decklist();
+        let game = Game::with_seed(&decklist, 0).unwrap();
+
+        let forests = decklist
+            .maindeck
+            .iter()
+            .find(|(name, _)| name == "Forest")
+            .map(|(_, quantity)| *quantity)
+            .unwrap();
+
+        assert_eq!(forests, game.count_in_zone("Forest", Zone::Library));
+        assert_eq!(forests, game.deck.count("Forest"));
+        assert_eq!(0, game.count_in_zone("Forest", Zone::Hand));
+    }
+
+    #[test]
+    fn it_triggers_wirewood_savage_when_a_beast_enters() {
+        let strategy = PatternCombo {};
+        let wirewood_savage = Card::new_with_zone("Wirewood Savage", Zone::Battlefield);
+        let ravenous_baloth = Card::new_with_zone("Ravenous Baloth", Zone::Hand);
+        let library = vec![
+            Card::new_with_zone("Forest", Zone::Library),
+            Card::new_with_zone("Forest", Zone::Library),
+        ];
+
+        let mut game = Game {
+            game_objects: [vec![wirewood_savage, ravenous_baloth.clone()], library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            turn: 2,
+            ..Default::default()
+        };
+
+        let payment = PaymentAndFloating {
+            payment: vec![],
+            floating: HashMap::new(),
+        };
+        game.cast_spell(&strategy, &ravenous_baloth, &payment, None);
+
+        assert_eq!(Zone::Battlefield, ravenous_baloth.borrow().zone);
+        assert_eq!(1, game.count_in_zone("Forest", Zone::Hand));
+        assert_eq!(1, game.deck.len());
+    }
+
+    #[test]
+    fn it_stops_optional_draws_before_decking_out() {
+        let strategy = PatternCombo {};
+
+        for (min_cards_left_in_library, expected_draws) in [(1, 2), (2, 1), (0, 3)] {
+            let wirewood_savage = Card::new_with_zone("Wirewood Savage", Zone::Battlefield);
+            let ravenous_baloths: Vec<_> = (0..4)
+                .map(|_| Card::new_with_zone("Ravenous Baloth", Zone::Hand))
+                .collect();
+            let library: Vec<_> = (0..3)
+                .map(|_| Card::new_with_zone("Forest", Zone::Library))
+                .collect();
+
+            let mut game = Game {
+                game_objects: [vec![wirewood_savage], ravenous_baloths.clone(), library.clone()].concat(),
+                deck: Deck {
+                    maindeck: VecDeque::from(library),
+                    sideboard: vec![],
+                },
+                turn: 2,
+                min_cards_left_in_library,
+                ..Default::default()
+            };
+
+            let payment = PaymentAndFloating {
+                payment: vec![],
+                floating: HashMap::new(),
+            };
+            for ravenous_baloth in ravenous_baloths.iter() {
+                game.cast_spell(&strategy, ravenous_baloth, &payment, None);
+            }
+
+            assert_eq!(expected_draws, game.count_in_zone("Forest", Zone::Hand));
+            assert_eq!(3 - expected_draws, game.deck.len());
+            assert!(!game.drew_from_empty_library);
+        }
+    }
+
+    #[test]
+    fn it_produces_green_for_each_forest_with_rofellos() {
+        let rofellos = Card::new_with_zone("Rofellos, Llanowar Emissary", Zone::Battlefield);
+        let forests = (0..3)
+            .map(|_| {
+                let forest = Card::new_with_zone("Forest", Zone::Battlefield);
+                forest.borrow_mut().is_tapped = true;
+                forest
+            })
+            .collect::<Vec<_>>();
+        let wirewood_savage = Card::new_with_zone("Wirewood Savage", Zone::Hand);
+
+        let game = Game {
+            game_objects: [vec![rofellos.clone(), wirewood_savage.clone()], forests].concat(),
+            ..Default::default()
+        };
+
+        let castable = game.find_castable();
+
+        assert_eq!(Some(&3), rofellos.borrow().produced_mana.get(&Mana::Green));
+        assert!(castable.iter().any(|(card, payment)| {
+            Rc::ptr_eq(card, &wirewood_savage) && Rc::ptr_eq(&payment.payment[0], &rofellos)
+        }));
+    }
+
+    #[test]
+    fn it_discards_to_max_hand_size_on_cleanup() {
+        for (max_hand_size, expected_hand) in [(Some(7), 7), (Some(8), 8), (None, 8)] {
+            let mut strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+            let hand = (0..8)
+                .map(|_| Card::new_with_zone("Forest", Zone::Hand))
+                .collect::<Vec<_>>();
+
+            let mut game = Game {
+                game_objects: hand,
+                opponent_library: 60,
+                max_hand_size,
+                ..Default::default()
+            };
+
+            game.cleanup(&mut strategy);
+
+            assert_eq!(expected_hand, game.count_in_zone("Forest", Zone::Hand));
+            assert_eq!(8 - expected_hand, game.count_in_zone("Forest", Zone::Graveyard));
+        }
+    }
+
+    #[test]
+    fn it_takes_extra_turns_before_the_opponent() {
+        let mut strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+        let source = Card::new_with_zone("Meditate", Zone::Graveyard);
+
+        let mut game = Game {
+            game_objects: vec![source.clone()],
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        game.begin_turn();
+        Effect::ExtraTurn(1).resolve(&mut game, &source, &PatternCombo {});
+        game.cleanup(&mut strategy);
+        assert_eq!(60, game.opponent_library);
+
+        game.begin_turn();
+        game.cleanup(&mut strategy);
+        assert_eq!(2, game.turn);
+        assert_eq!(59, game.opponent_library);
+    }
+
+    #[test]
+    fn it_lets_the_opponent_draw_on_skipped_turns() {
+        let mut game = Game {
+            opponent_library: 60,
+            turns_to_skip: 1,
+            turn: 1,
+            ..Default::default()
+        };
+
+        game.begin_turn();
+
+        assert_eq!(3, game.turn);
+        assert_eq!(59, game.opponent_library);
+    }
+
+    #[test]
+    fn it_wins_when_the_opponent_draws_from_an_empty_library_on_a_skipped_turn() {
+        let mut game = Game {
+            opponent_library: 0,
+            turns_to_skip: 2,
+            turn: 1,
+            ..Default::default()
+        };
+
+        assert!(matches!(game.begin_turn(), GameStatus::Finished(Outcome::Win)));
+        assert_eq!(2, game.turn);
+        assert_eq!(-1, game.opponent_library);
+    }
+
+    #[test]
+    fn it_pays_twice_in_a_turn_with_wall_of_roots() {
+        let strategy = PatternCombo {};
+        let wall_of_roots = Card::new_with_zone("Wall of Roots", Zone::Battlefield);
+        let elves = [
+            Card::new_with_zone("Llanowar Elves", Zone::Hand),
+            Card::new_with_zone("Fyndhorn Elves", Zone::Hand),
+        ];
+
+        let mut game = Game {
+            game_objects: [vec![wall_of_roots.clone()], elves.to_vec()].concat(),
+            ..Default::default()
+        };
+
+        for elf in elves.iter() {
+            let castable = game.find_castable();
+            let (_, payment) = castable
+                .iter()
+                .find(|(card, _)| Rc::ptr_eq(card, elf))
+                .expect("should be able to pay with Wall of Roots");
+
+            game.cast_spell(&strategy, elf, payment, None);
+            assert_eq!(Zone::Battlefield, elf.borrow().zone);
+        }
+
+        assert_eq!(Some(3), wall_of_roots.borrow().charges);
+        assert!(!wall_of_roots.borrow().is_tapped);
+    }
+
+    #[test]
+    fn it_limits_mulligans_with_mulligan_policy() {
+        for deck_strategy in STRATEGIES.iter() {
+            let mut strategy = from_enum(deck_strategy);
+            let decklist = strategy.default_decklist();
+
+            for (mulligan_policy, max_mulligans) in [(MulliganPolicy::KeepAll, 0), (MulliganPolicy::Conservative, 1)] {
+                let config = GameConfig {
+                    seed: Some(0),
+                    mulligan_policy,
+                    ..Default::default()
+                };
+
+                let results = crate::simulate_many(&mut strategy, &decklist, &config, 20, |_| {}).unwrap();
+
+                assert!(results.iter().all(|result| result.mulligan_count <= max_mulligans));
+            }
+        }
+    }
+
+    #[test]
+    fn it_iterates_cards_in_zone() {
+        let strategy = from_enum(&DeckStrategy::Aluren);
+        let decklist = strategy.default_decklist();
+        let mut game = Game::with_seed(&decklist, 0).unwrap();
+
+        game.find_starting_hand(&strategy);
+
+        assert_eq!(game.game_objects.iter().filter(is_hand).count(), game.hand().count());
+        assert!(game.hand().all(|card| card.borrow().zone == Zone::Hand));
+        assert_eq!(0, game.battlefield().count());
+        assert_eq!(0, game.graveyard().count());
+        assert_eq!(game.deck.len(), game.cards_in_zone(Zone::Library).count());
+    }
+
+    #[test]
+    fn it_wins_by_attacking_twice_with_progenitus() {
+        let mut strategy = from_enum(&DeckStrategy::Aluren);
+        let progenitus = Card::new_with_zone("Progenitus", Zone::Battlefield);
+
+        let mut game = Game {
+            game_objects: vec![progenitus.clone()],
+            life_total: 20,
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        // Summoning sick on the turn it entered
+        progenitus.borrow_mut().is_summoning_sick = true;
+        assert!(matches!(game.combat(&mut strategy), GameStatus::Continue));
+        assert_eq!(0, game.damage_dealt);
+
+        game.untap();
+        assert!(matches!(game.combat(&mut strategy), GameStatus::Continue));
+        assert_eq!(10, game.damage_dealt);
+        assert!(progenitus.borrow().is_tapped);
+
+        game.untap();
+        assert!(matches!(game.combat(&mut strategy), GameStatus::Finished(Outcome::Win)));
+        assert_eq!(20, game.damage_dealt);
+    }
+
+    #[test]
+    fn it_tracks_net_cards_drawn() {
+        let hand = ["Island", "Frantic Search", "Words of Wisdom"]
+            .iter()
+            .map(|name| Card::new_with_zone(name, Zone::Hand))
+            .collect::<Vec<_>>();
+        let battlefield = (0..4)
+            .map(|_| Card::new_with_zone("Island", Zone::Battlefield))
+            .collect::<Vec<_>>();
+        let library = (0..10)
+            .map(|_| Card::new_with_zone("Swamp", Zone::Library))
+            .collect::<Vec<_>>();
+
+        let mut game = Game {
+            game_objects: [hand, battlefield, library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            turn: 2,
+            life_total: 20,
+            opponent_library: 60,
+            available_land_drops: 1,
+            ..Default::default()
+        };
+
+        let mut strategy: Box<dyn Strategy> = Box::new(Scripted::new(
+            vec![
+                ScriptStep::PlayLand("Island".to_owned()),
+                ScriptStep::Cast("Words of Wisdom".to_owned()),
+                ScriptStep::Cast("Frantic Search".to_owned()),
+                ScriptStep::Pass,
+            ],
+            from_enum(&DeckStrategy::Aluren),
+        ));
+
+        game.take_game_actions(&mut strategy);
+
+        assert_eq!(BTreeMap::from([(2, 4)]), game.cards_drawn_by_turn);
+        assert_eq!(BTreeMap::from([(2, 2)]), game.cards_discarded_by_turn);
+        assert_eq!(2, game.net_cards_drawn());
+    }
+
+    #[test]
+    fn it_classifies_losses_from_the_end_state() {
+        let library = vec![Card::new_with_zone("Swamp", Zone::Library)];
+        let hand = ["Cabal Therapy", "Intuition", "Aluren", "Impulse", "Living Wish", "Unearth", "Cavern Harpy"]
+            .iter()
+            .map(|name| Card::new_with_zone(name, Zone::Hand))
+            .collect::<Vec<_>>();
+
+        let mut game = Game {
+            game_objects: [hand, library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            turn: 5,
+            ..Default::default()
+        };
+
+        assert_eq!(LossReason::ManaScrew, game.classify_loss());
+
+        for _ in 0..4 {
+            game.game_objects.push(Card::new_with_zone("Island", Zone::Battlefield));
+        }
+        assert_eq!(LossReason::NoCombo, game.classify_loss());
+
+        for _ in 0..8 {
+            game.game_objects.push(Card::new_with_zone("Island", Zone::Hand));
+        }
+        assert_eq!(LossReason::ManaFlood, game.classify_loss());
+
+        game.deck.draw();
+        assert_eq!(LossReason::DeckedOut, game.classify_loss());
+    }
+
+    #[test]
+    fn it_taps_mana_sources_for_mana() {
+        let ancient_tomb = Card::new_with_zone("Ancient Tomb", Zone::Battlefield);
+        let llanowar_elves = Card::new_with_zone("Llanowar Elves", Zone::Battlefield);
+        llanowar_elves.borrow_mut().is_summoning_sick = true;
+
+        let mut game = Game {
+            game_objects: vec![ancient_tomb.clone(), llanowar_elves.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        assert!(!game.tap_for_mana(&ancient_tomb, Mana::Green));
+        assert!(game.tap_for_mana(&ancient_tomb, Mana::Colorless));
+        assert_eq!(Some(&2), game.floating_mana.get(&Mana::Colorless));
+        assert_eq!(18, game.life_total);
+        assert!(ancient_tomb.borrow().is_tapped);
+
+        // Already tapped
+        assert!(!game.tap_for_mana(&ancient_tomb, Mana::Colorless));
+        assert_eq!(18, game.life_total);
+
+        assert!(!game.tap_for_mana(&llanowar_elves, Mana::Green));
+        llanowar_elves.borrow_mut().is_summoning_sick = false;
+        assert!(game.tap_for_mana(&llanowar_elves, Mana::Green));
+        assert_eq!(Some(&1), game.floating_mana.get(&Mana::Green));
+    }
+
+    #[test]
+    fn it_cant_tap_dryad_arbor_for_mana_the_turn_it_enters() {
+        let dryad_arbor = Card::new_with_zone("Dryad Arbor", Zone::Hand);
+        let llanowar_elves = Card::new_with_zone("Llanowar Elves", Zone::Hand);
+        // As if it had been on the battlefield before
+        dryad_arbor.borrow_mut().is_summoning_sick = false;
+
+        let mut game = Game {
+            game_objects: vec![dryad_arbor.clone(), llanowar_elves.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        game.begin_turn();
+        game.play_land(dryad_arbor.clone());
+
+        assert_eq!(Zone::Battlefield, dryad_arbor.borrow().zone);
+        assert!(game.find_castable().is_empty());
+        assert!(!game.tap_for_mana(&dryad_arbor, Mana::Green));
+
+        game.begin_turn();
+        game.untap();
+
+        assert_eq!(1, game.find_castable().len());
+        assert!(game.tap_for_mana(&dryad_arbor, Mana::Green));
+    }
+
+    #[test]
+    fn it_takes_damage_from_ancient_tomb_when_casting() {
+        let ancient_tomb = Card::new_with_zone("Ancient Tomb", Zone::Battlefield);
+        let defense_grid = Card::new_with_zone("Defense Grid", Zone::Hand);
+
+        let mut game = Game {
+            game_objects: vec![ancient_tomb.clone(), defense_grid.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        let castable = game.find_castable();
+        let (card, payment) = castable.first().unwrap();
+        game.cast_spell(&PatternCombo {}, card, payment, None);
+
+        assert_eq!(Zone::Battlefield, defense_grid.borrow().zone);
+        assert!(ancient_tomb.borrow().is_tapped);
+        assert_eq!(18, game.life_total);
+    }
+
+    #[test]
+    fn it_spends_all_remaining_mana_on_x() {
+        let draw_x = Rc::new(RefCell::new(Card {
+            name: "Draw X".to_owned(),
+            card_types: HashSet::from([CardType::Sorcery]),
+            zone: Zone::Hand,
+            x_cost: true,
+            on_resolve: Some(Effect::DrawX),
+            ..Default::default()
+        }));
+        let islands = (0..4)
+            .map(|_| Card::new_with_zone("Island", Zone::Battlefield))
+            .collect::<Vec<_>>();
+        let lotus_petal = Card::new_with_zone("Lotus Petal", Zone::Battlefield);
+        let library = (0..6)
+            .map(|_| Card::new_with_zone("Forest", Zone::Library))
+            .collect::<Vec<_>>();
+
+        let mut game = Game {
+            game_objects: [vec![draw_x.clone(), lotus_petal.clone()], islands.clone(), library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            life_total: 20,
+            ..Default::default()
+        };
+
+        let castable = game.find_castable();
+        let (card, payment) = castable.iter().find(|(card, _)| Rc::ptr_eq(card, &draw_x)).unwrap();
+        game.cast_spell(&PatternCombo {}, card, payment, None);
+
+        assert_eq!(4, draw_x.borrow().x);
+        assert_eq!(4, game.hand().count());
+        assert_eq!(2, game.deck.len());
+        assert!(islands.iter().all(|island| island.borrow().is_tapped));
+        assert_eq!(Zone::Battlefield, lotus_petal.borrow().zone);
+        assert!(game.floating_mana.values().all(|amount| *amount == 0));
+    }
+
+    #[test]
+    fn it_builds_storm_with_free_cantrips() {
+        let probes = (0..2)
+            .map(|_| Card::new_with_zone("Gitaxian Probe", Zone::Hand))
+            .collect::<Vec<_>>();
+        let library = (0..4)
+            .map(|_| Card::new_with_zone("Forest", Zone::Library))
+            .collect::<Vec<_>>();
+
+        let mut game = Game {
+            game_objects: [probes.clone(), library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            life_total: 20,
+            ..Default::default()
+        };
+
+        for probe in probes.iter() {
+            let castable = game.find_castable();
+            let (card, payment) = castable.iter().find(|(card, _)| same_card(card, probe)).unwrap();
+            game.cast_spell(&PatternCombo {}, card, payment, None);
+        }
+
+        assert_eq!(2, game.storm);
+        assert_eq!(2, game.hand().count());
+        assert_eq!(2, game.deck.len());
+        assert!(probes.iter().all(|probe| probe.borrow().zone == Zone::Graveyard));
+    }
 }
@@ -4,4 +4,104 @@ pub mod deck;
 pub mod game;
 pub mod utils;
 pub mod strategy;
-pub mod effect;
\ No newline at end of file
+pub mod effect;
+pub mod observer;
+pub mod replay;
+pub mod summary;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+
+use deck::{Decklist, ParseDeckError};
+use game::{Game, GameConfig, GameResult};
+use strategy::Strategy;
+
+/// Simulates a single game with the strategy and decklist.
+pub fn simulate_one(
+    strategy: &mut Box<dyn Strategy>,
+    decklist: &Decklist,
+    config: &GameConfig,
+) -> Result<GameResult, ParseDeckError> {
+    let mut game = Game::with_config(decklist, config)?;
+    Ok(game.run(strategy))
+}
+
+/// Simulates `games` games with the strategy and decklist.
+/// When the config has a seed, each game is seeded with the following seeds in order.
+/// The `on_progress` callback is called with the number of games simulated so far after each game.
+///
+/// ```
+/// use goldfisher::game::GameConfig;
+/// use goldfisher::strategy::{pattern_combo, Strategy};
+///
+/// let mut strategy: Box<dyn Strategy> = Box::new(pattern_combo::PatternCombo {});
+/// let decklist = strategy.default_decklist();
+///
+/// let results = goldfisher::simulate_many(&mut strategy, &decklist, &GameConfig::default(), 5, |games| {
+///     println!("Simulated {games}/5 games");
+/// })
+/// .unwrap();
+///
+/// assert_eq!(5, results.len());
+/// ```
+pub fn simulate_many(
+    strategy: &mut Box<dyn Strategy>,
+    decklist: &Decklist,
+    config: &GameConfig,
+    games: usize,
+    mut on_progress: impl FnMut(usize),
+) -> Result<Vec<GameResult>, ParseDeckError> {
+    (0..games)
+        .map(|index| {
+            let config = GameConfig {
+                seed: config.seed.map(|seed| seed.wrapping_add(index as u64)),
+                ..config.clone()
+            };
+
+            let result = simulate_one(strategy, decklist, &config)?;
+            on_progress(index + 1);
+
+            Ok(result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{from_enum, DeckStrategy, STRATEGIES};
+
+    #[test]
+    fn it_reports_the_progress_after_each_game() {
+        let mut strategy = from_enum(&DeckStrategy::PatternCombo);
+        let decklist = strategy.default_decklist();
+
+        let mut progress = vec![];
+        let results = simulate_many(&mut strategy, &decklist, &GameConfig::default(), 5, |games| {
+            progress.push(games)
+        })
+        .unwrap();
+
+        assert_eq!(5, results.len());
+        assert_eq!(vec![1, 2, 3, 4, 5], progress);
+    }
+
+    #[test]
+    fn it_reproduces_the_games_with_the_same_seed() {
+        for deck_strategy in STRATEGIES {
+            for seed in 0..10 {
+                let simulate = || {
+                    let mut strategy = from_enum(deck_strategy);
+                    let decklist = strategy.default_decklist();
+                    let config = GameConfig {
+                        seed: Some(seed),
+                        ..strategy.default_config()
+                    };
+
+                    simulate_one(&mut strategy, &decklist, &config).unwrap()
+                };
+
+                assert_eq!(simulate(), simulate(), "{deck_strategy:?} with seed {seed}");
+            }
+        }
+    }
+}
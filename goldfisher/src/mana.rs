@@ -2,17 +2,21 @@ use std::{collections::HashMap, hash::Hash};
 use std::rc::Rc;
 use std::vec;
 
-use crate::card::{CardRef, CardType};
+use crate::card::{same_card, CardRef, CardType, LandType};
 use crate::utils::*;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mana {
     White,
     Blue,
     Black,
     Red,
     Green,
+    /// Mana produced by colorless sources, and costs that can only be paid with such mana
     Colorless,
+    /// Generic costs that can be paid with any type of mana
+    Generic,
 }
 
 #[derive(Clone, Debug)]
@@ -21,16 +25,109 @@ pub struct PaymentAndFloating {
     pub floating: HashMap<Mana, u32>
 }
 
-const COLORS: [Mana; 5] = [Mana::White, Mana::Blue, Mana::Black, Mana::Red, Mana::Green];
+const COLORS_AND_COLORLESS: [Mana; 6] = [
+    Mana::White,
+    Mana::Blue,
+    Mana::Black,
+    Mana::Red,
+    Mana::Green,
+    Mana::Colorless,
+];
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub enum CostReduction {
-    All(Mana, i32),
-    Color(Mana, (Mana, i32)),
+    All(Mana, u32),
+    Color(Mana, (Mana, u32)),
     Aluren
 }
 
+/// Mana production that depends on the state of the game
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub enum DynamicMana {
+    /// Produces one of the mana for each land of the type on the battlefield
+    PerLandType(Mana, LandType),
+}
+
+/// Parses a mana cost written with mana symbols, like "2GG" or "{2}{G}{G}".
+/// Hybrid, Phyrexian and X symbols aren't supported.
+pub fn parse_cost(cost: &str) -> Result<HashMap<Mana, u32>, String> {
+    let mut parsed = HashMap::new();
+    let mut generic: Option<u32> = None;
+
+    for symbol in cost.chars().filter(|symbol| !matches!(symbol, '{' | '}')) {
+        if let Some(digit) = symbol.to_digit(10) {
+            generic = Some(generic.unwrap_or(0) * 10 + digit);
+            continue;
+        }
+
+        let mana = match symbol {
+            'W' => Mana::White,
+            'U' => Mana::Blue,
+            'B' => Mana::Black,
+            'R' => Mana::Red,
+            'G' => Mana::Green,
+            'C' => Mana::Colorless,
+            '/' => return Err(format!("hybrid and Phyrexian mana aren't supported: {cost}")),
+            symbol => return Err(format!("unknown mana symbol \"{symbol}\" in cost: {cost}")),
+        };
+
+        *parsed.entry(mana).or_insert(0) += 1;
+    }
+
+    if let Some(generic) = generic.filter(|generic| *generic > 0) {
+        parsed.insert(Mana::Generic, generic);
+    }
+
+    Ok(parsed)
+}
+
+/// Finds the mana sources to pay the cost of the card with, using the floating mana first.
+/// Spells with X in their cost spend all of the remaining mana on X.
 pub fn find_payment_for(
+    card: CardRef,
+    mana_sources: &[CardRef],
+    floating: HashMap<Mana, u32>,
+    cost_reductions: &[CostReduction],
+) -> Option<PaymentAndFloating> {
+    let payment = find_payment_for_cost(card.clone(), mana_sources, floating, cost_reductions)?;
+
+    if card.borrow().x_cost {
+        return Some(spend_remaining_on_x(&card, mana_sources, payment));
+    }
+
+    Some(payment)
+}
+
+/// Adds the rest of the mana sources to the payment, leaving their mana floating to be spent on X.
+/// The one-shot sources are saved for the other spells instead.
+fn spend_remaining_on_x(
+    card: &CardRef,
+    mana_sources: &[CardRef],
+    PaymentAndFloating { mut payment, mut floating }: PaymentAndFloating,
+) -> PaymentAndFloating {
+    for source in mana_sources {
+        if same_card(source, card)
+            || source.borrow().is_one_shot
+            || payment.iter().any(|used| same_card(used, source))
+        {
+            continue;
+        }
+
+        let mut produced_mana = source.borrow().produced_mana.clone().into_iter().collect::<Vec<_>>();
+        produced_mana.sort();
+
+        if let Some((color, amount)) = produced_mana.into_iter().max_by(|(_, a), (_, b)| a.cmp(b)) {
+            *floating.entry(color).or_insert(0) += amount;
+            payment.push(Rc::clone(source));
+        }
+    }
+
+    PaymentAndFloating { payment, floating }
+}
+
+fn find_payment_for_cost(
     card: CardRef,
     mana_sources: &[CardRef],
     mut floating: HashMap<Mana, u32>,
@@ -48,8 +145,12 @@ pub fn find_payment_for(
     for reduction in cost_reductions {
         match reduction {
             CostReduction::Aluren => {
-                if is_card_type(&&card, &CardType::Creature) && cost_after_reductions.values().sum::<i32>() <= 3 {
-                    // "Any player may cast creature spells with mana value 3 or less without paying their mana costs"
+                // "Any player may cast creature spells with mana value 3 or less without paying their mana costs"
+                // Only spells cast from hand are free, Aluren does nothing for abilities or other zones.
+                if is_hand(&&card)
+                    && is_card_type(&&card, &CardType::Creature)
+                    && card.borrow().mana_value() <= 3
+                {
                     return Some(PaymentAndFloating{
                         payment: vec![],
                         floating
@@ -58,28 +159,30 @@ pub fn find_payment_for(
             },
             CostReduction::Color(color, (mana, reduction)) => {
                 if is_color(&&card, *color) {
-                    *cost_after_reductions.entry(*mana).or_insert(0) -= reduction;
+                    let cost = cost_after_reductions.entry(*mana).or_insert(0);
+                    *cost = cost.saturating_sub(*reduction);
                 }
             },
             CostReduction::All(mana, reduction) => {
-                *cost_after_reductions.entry(*mana).or_insert(0) -= reduction;
+                let cost = cost_after_reductions.entry(*mana).or_insert(0);
+                *cost = cost.saturating_sub(*reduction);
             }
         }
     }
 
     let mut sources_to_pay_colors_with = HashMap::new();
 
-    // Gather the color requirements first
-    for color in &COLORS {
+    // Gather the color requirements first, colorless costs can only be paid with colorless mana
+    for color in &COLORS_AND_COLORLESS {
         if let Some(cost) = cost_after_reductions.get(color) {
-            if *cost <= 0 {
+            if *cost == 0 {
                 continue;
             }
 
             let available_sources: Vec<_> = mana_sources
                 .iter()
                 // Prevent doing something like paying for Elvish Spirit Guide with itself
-                .filter(|source| !Rc::ptr_eq(*source, &card))
+                .filter(|source| !same_card(source, &card))
                 .flat_map(|source| {
                     source
                         .borrow()
@@ -91,7 +194,7 @@ pub fn find_payment_for(
 
             let total_available: u32 = available_sources.iter().map(|source| source.1).sum();
             let total_floating = floating.get(color).unwrap_or(&0);
-            if *cost as u32 > total_available + total_floating {
+            if *cost > total_available + total_floating {
                 // Not enough mana to pay for this color
                 return None;
             }
@@ -102,12 +205,13 @@ pub fn find_payment_for(
 
     let mut used_sources = Vec::new();
 
-    for (color, cost) in cost_after_reductions.iter() {
-        if *color == Mana::Colorless || *cost <= 0 {
+    // Pay the colors in a fixed order so that the payment is deterministic
+    for color in &COLORS_AND_COLORLESS {
+        let cost = *cost_after_reductions.get(color).unwrap_or(&0);
+        if cost == 0 {
             continue;
         }
 
-        let cost = *cost as u32;
         let mut paid = 0;
 
         // Try to spend any floating mana we might have
@@ -124,7 +228,7 @@ pub fn find_payment_for(
 
         let sources = sources_to_pay_colors_with.get(color).unwrap();
         for (source, amount) in sources.iter() {
-            if used_sources.iter().any(|used| Rc::ptr_eq(used, source)) {
+            if used_sources.iter().any(|used| same_card(used, source)) {
                 continue;
             }
 
@@ -142,22 +246,26 @@ pub fn find_payment_for(
         }
     }
 
-    if let Some(cost) = cost_after_reductions.get(&Mana::Colorless) {
-        if *cost <= 0 {
+    if let Some(cost) = cost_after_reductions.get(&Mana::Generic) {
+        if *cost == 0 {
             return Some(PaymentAndFloating{
                 payment: used_sources,
                 floating
             });
         }
 
-        let cost = *cost as u32;
+        let cost = *cost;
 
         // Use the floating mana first
         let mut paid = 0;
 
         // Try to spend any floating mana we might have
-        for floating_mana in floating.values_mut() {
-            // Try to spend any floating mana we might have
+        for color in &COLORS_AND_COLORLESS {
+            let floating_mana = match floating.get_mut(color) {
+                Some(floating_mana) => floating_mana,
+                None => continue,
+            };
+
             if *floating_mana < cost {
                 // Partial payment with floating mana
                 paid += *floating_mana;
@@ -175,24 +283,34 @@ pub fn find_payment_for(
             let mut remaining_sources = mana_sources
                 .iter()
                 .filter(|source| {
-                    !used_sources.iter().any(|used| Rc::ptr_eq(used, source))
+                    !used_sources.iter().any(|used| same_card(used, source))
                 })
                 .collect::<Vec<_>>();
 
+            // Prefer the painless sources, then the ones not producing more than is left to pay,
+            // then the ones producing the most mana, and save the sources producing the most colors for last
+            let remaining_cost = cost - paid;
             remaining_sources.sort_by(|a, b| {
-                b.borrow()
-                    .produced_mana
-                    .values()
-                    .max()
-                    .unwrap_or(&0)
-                    .partial_cmp(a.borrow().produced_mana.values().max().unwrap_or(&0))
-                    .unwrap()
+                let max_produced = |source: &CardRef| {
+                    source.borrow().produced_mana.values().max().copied().unwrap_or(0)
+                };
+                let overshoot = |source: &CardRef| max_produced(source).saturating_sub(remaining_cost);
+
+                a.borrow()
+                    .pain
+                    .cmp(&b.borrow().pain)
+                    .then_with(|| overshoot(a).cmp(&overshoot(b)))
+                    .then_with(|| max_produced(b).cmp(&max_produced(a)))
+                    .then_with(|| sort_by_best_mana_to_use(a, b))
             });
 
             for source in remaining_sources {
                 let borrowed = source.borrow();
-                let max_produced_mana_from_source = borrowed.produced_mana
-                    .iter()
+                let mut produced_mana = borrowed.produced_mana.iter().collect::<Vec<_>>();
+                produced_mana.sort();
+
+                let max_produced_mana_from_source = produced_mana
+                    .into_iter()
                     .max_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
 
                 if let Some((color, amount)) = max_produced_mana_from_source {
@@ -223,7 +341,9 @@ pub fn find_payment_for(
 #[rustfmt::skip]
 mod tests {
     use super::*;
-    use crate::card::{Card};
+    use crate::card::{Card, Zone};
+    use std::cell::RefCell;
+    use std::collections::HashSet;
 
     #[test]
     fn it_finds_payment_no_mana_sources() {
@@ -444,19 +564,19 @@ mod tests {
     }
 
     #[test]
-    fn it_finds_payment_2cmc_colorless_prefers_sol_lands() {
+    fn it_finds_payment_2cmc_colorless_prefers_single_multi_mana_land() {
         let altar_of_dementia = Card::new_as_ref("Altar of Dementia");
 
         let forest = Card::new_as_ref("Forest");
         let mountain = Card::new_as_ref("Mountain");
-        let ancient_tomb = Card::new_as_ref("Ancient Tomb");
+        let hickory_woodlot = Card::new_as_ref("Hickory Woodlot");
 
         let payment = find_payment_for(
             altar_of_dementia,
             &vec![
                 forest.clone(),
                 mountain.clone(),
-                ancient_tomb.clone()
+                hickory_woodlot.clone()
             ],
             HashMap::new(),
             &Vec::new(),
@@ -465,10 +585,67 @@ mod tests {
         assert_eq!(true, payment.is_some());
         let PaymentAndFloating{ payment, floating } = payment.unwrap();
         assert_eq!(1, payment.len());
-        assert_eq!(true, payment.iter().any(|source| Rc::ptr_eq(&ancient_tomb, source)));
+        assert_eq!(true, payment.iter().any(|source| Rc::ptr_eq(&hickory_woodlot, source)));
         assert_eq!(true, is_empty_mana_pool(floating));
     }
 
+    // Ancient Tomb deals damage, so the painless sources are tapped first even for costs it would
+    // pay alone, like {2} with a Forest and a Mountain. It only pays what the painless sources can't.
+    #[test]
+    fn it_finds_payment_1cmc_prefers_forest_over_ancient_tomb() {
+        let chromatic_sphere = Rc::new(RefCell::new(Card {
+            name: "Chromatic Sphere".to_owned(),
+            card_types: HashSet::from([CardType::Artifact]),
+            cost: HashMap::from([(Mana::Generic, 1)]),
+            ..Default::default()
+        }));
+
+        let ancient_tomb = Card::new_as_ref("Ancient Tomb");
+        let forest = Card::new_as_ref("Forest");
+
+        let payment = find_payment_for(
+            chromatic_sphere,
+            &[ancient_tomb, forest.clone()],
+            HashMap::new(),
+            &Vec::new(),
+        );
+
+        assert!(payment.is_some());
+        let PaymentAndFloating{ payment, floating } = payment.unwrap();
+        assert_eq!(1, payment.len());
+        assert!(Rc::ptr_eq(&forest, &payment[0]));
+        assert!(is_empty_mana_pool(floating));
+    }
+
+    #[test]
+    fn it_finds_payment_generic_cost_prefers_basics_over_five_color_lands() {
+        let altar_of_dementia = Card::new_as_ref("Altar of Dementia");
+
+        let city_of_brass = Card::new_as_ref("City of Brass");
+        let gemstone_mine = Card::new_as_ref("Gemstone Mine");
+        let forest_1 = Card::new_as_ref("Forest");
+        let forest_2 = Card::new_as_ref("Forest");
+
+        let payment = find_payment_for(
+            altar_of_dementia,
+            &[
+                city_of_brass.clone(),
+                gemstone_mine.clone(),
+                forest_1.clone(),
+                forest_2.clone(),
+            ],
+            HashMap::new(),
+            &Vec::new(),
+        );
+
+        assert!(payment.is_some());
+        let PaymentAndFloating{ payment, floating } = payment.unwrap();
+        assert_eq!(2, payment.len());
+        assert!(payment.iter().any(|source| Rc::ptr_eq(&forest_1, source)));
+        assert!(payment.iter().any(|source| Rc::ptr_eq(&forest_2, source)));
+        assert!(is_empty_mana_pool(floating));
+    }
+
     #[test]
     fn it_finds_payment_3cmc_saves_colors() {
         let vindicate = Card::new_as_ref("Vindicate");
@@ -560,4 +737,109 @@ mod tests {
         assert_eq!(1, *floating.get(&Mana::Red).unwrap());
         assert_eq!(0, *floating.get(&Mana::Green).unwrap());
     }
+
+    fn colorless_cost_card() -> CardRef {
+        Rc::new(RefCell::new(Card {
+            name: "Matter Reshaper".to_owned(),
+            card_types: HashSet::from([CardType::Creature]),
+            cost: HashMap::from([(Mana::Colorless, 1), (Mana::Generic, 2)]),
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn it_finds_payment_colorless_cost_not_payable_with_forest() {
+        let forest_1 = Card::new_as_ref("Forest");
+        let forest_2 = Card::new_as_ref("Forest");
+        let forest_3 = Card::new_as_ref("Forest");
+
+        let payment = find_payment_for(
+            colorless_cost_card(),
+            &[forest_1, forest_2, forest_3],
+            HashMap::from([(Mana::Green, 1)]),
+            &Vec::new(),
+        );
+
+        assert!(payment.is_none());
+    }
+
+    #[test]
+    fn it_finds_payment_colorless_cost_with_colorless_source() {
+        let ancient_tomb = Card::new_as_ref("Ancient Tomb");
+        let forest = Card::new_as_ref("Forest");
+
+        let payment = find_payment_for(
+            colorless_cost_card(),
+            &[forest.clone(), ancient_tomb.clone()],
+            HashMap::new(),
+            &Vec::new(),
+        );
+
+        assert!(payment.is_some());
+        let PaymentAndFloating{ payment, floating } = payment.unwrap();
+        assert_eq!(2, payment.len());
+        assert!(payment.iter().any(|source| Rc::ptr_eq(&ancient_tomb, source)));
+        assert!(payment.iter().any(|source| Rc::ptr_eq(&forest, source)));
+        assert!(is_empty_mana_pool(floating));
+    }
+
+    #[test]
+    fn it_finds_payment_aluren_3cmc_creature_from_hand_is_free() {
+        let cloud_of_faeries = Card::new_with_zone("Cloud of Faeries", Zone::Hand);
+
+        let payment = find_payment_for(
+            cloud_of_faeries,
+            &[],
+            HashMap::from([(Mana::Blue, 1)]),
+            &[CostReduction::Aluren],
+        );
+
+        assert!(payment.is_some());
+        let PaymentAndFloating{ payment, floating } = payment.unwrap();
+        assert!(payment.is_empty());
+        assert_eq!(1, *floating.get(&Mana::Blue).unwrap());
+    }
+
+    #[test]
+    fn it_finds_payment_aluren_4cmc_creature_is_not_free() {
+        let body_snatcher = Card::new_with_zone("Body Snatcher", Zone::Hand);
+
+        let payment = find_payment_for(
+            body_snatcher,
+            &[],
+            HashMap::new(),
+            &[CostReduction::Aluren],
+        );
+
+        assert!(payment.is_none());
+    }
+
+    #[test]
+    fn it_finds_payment_aluren_creature_outside_hand_is_not_free() {
+        let cloud_of_faeries = Card::new_with_zone("Cloud of Faeries", Zone::Graveyard);
+
+        let payment = find_payment_for(
+            cloud_of_faeries,
+            &[],
+            HashMap::new(),
+            &[CostReduction::Aluren],
+        );
+
+        assert!(payment.is_none());
+    }
+
+    #[test]
+    fn it_parses_costs_written_with_mana_symbols() {
+        assert_eq!(
+            Ok(HashMap::from([(Mana::Generic, 1), (Mana::White, 1), (Mana::Blue, 1)])),
+            parse_cost("1WU")
+        );
+        assert_eq!(Ok(HashMap::from([(Mana::Generic, 2), (Mana::Green, 2)])), parse_cost("{2}{G}{G}"));
+        assert_eq!(Ok(HashMap::from([(Mana::Generic, 12)])), parse_cost("12"));
+        assert_eq!(Ok(HashMap::new()), parse_cost("0"));
+
+        assert!(parse_cost("{G/W}").is_err());
+        assert!(parse_cost("{G/P}").is_err());
+        assert!(parse_cost("XG").is_err());
+    }
 }
@@ -0,0 +1,26 @@
+use crate::card::{CardRef, Zone};
+use crate::game::GameResult;
+
+/// Receives the events a `Game` emits while it is being played.
+///
+/// All of the callbacks have empty default implementations, so observers
+/// only need to implement the events they are interested in.
+pub trait GameObserver {
+    /// Called for every action or game state line the game logs.
+    fn on_action(&self, _turn: usize, _message: &str) {}
+
+    /// Called whenever a game object moves between zones.
+    fn on_zone_change(&self, _card: &CardRef, _from: &Zone, _to: &Zone) {}
+
+    /// Called once the game has finished.
+    fn on_game_end(&self, _result: &GameResult) {}
+}
+
+/// Observer that forwards the game actions to the `log` crate as debug output.
+pub struct LoggingObserver {}
+
+impl GameObserver for LoggingObserver {
+    fn on_action(&self, _turn: usize, message: &str) {
+        log::debug!("{message}");
+    }
+}
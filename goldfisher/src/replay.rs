@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// A single strategic decision made during a game.
+///
+/// Cards are referred to by their index in the game objects, followed by the sideboard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Decision {
+    /// Whether the opening hand was kept or mulliganed
+    Keep(bool),
+    /// The card chosen with `select_best`, if any
+    Select(Option<usize>),
+    /// The cards chosen as a group, such as Intuition piles or discarded cards
+    Cards(Vec<usize>),
+}
+
+/// Everything needed to re-simulate a game deterministically: the seed used for
+/// shuffling and the decisions the strategy made, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub decisions: Vec<Decision>,
+}
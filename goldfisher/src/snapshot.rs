@@ -0,0 +1,325 @@
+//! Snapshots of the game state that can be serialized, for example to attach a
+//! mid-game state to a bug report. Enabled with the `snapshot` feature.
+//!
+//! The game objects are shared between the zones as `CardRef`s, so the snapshot stores
+//! the cards once and refers to them by their index in the game objects instead.
+
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::rc::Rc;
+use std::sync::Mutex;
+
+use crate::card::{same_card, Card, CardRef};
+use crate::deck::Deck;
+use crate::game::{DisruptionModel, Game, LogEvent, MulliganPolicy};
+use crate::mana::Mana;
+use crate::replay::Decision;
+
+#[derive(Serialize, Deserialize)]
+struct CardSnapshot {
+    card: Card,
+    /// Index of the game object this card is attached to
+    attached_to: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GameSnapshot {
+    turn: usize,
+    available_land_drops: usize,
+    life_total: i32,
+    damage_dealt: i32,
+    opponent_library: i32,
+    floating_mana: HashMap<Mana, u32>,
+    is_first_player: bool,
+    mulligan_count: usize,
+    turns_to_skip: usize,
+    extra_turns: usize,
+    storm: usize,
+    max_storm: usize,
+    /// Indices of the game objects that died this turn
+    creatures_died_this_turn: Vec<usize>,
+    output: Vec<LogEvent>,
+    seed: u64,
+    rng: ChaCha12Rng,
+    shuffle_seed: u64,
+    shuffle_rng: ChaCha12Rng,
+    decisions: Vec<Decision>,
+    replaying: Option<VecDeque<Decision>>,
+    mana_available_by_turn: BTreeMap<usize, HashMap<Mana, u32>>,
+    combo_progress_by_turn: BTreeMap<usize, f32>,
+    disruption: Option<DisruptionModel>,
+    kill_turn: Option<usize>,
+    counter_next_spell: bool,
+    drew_from_empty_library: bool,
+    max_hand_size: Option<usize>,
+    mulligan_policy: MulliganPolicy,
+    min_cards_left_in_library: usize,
+    cards_drawn_by_turn: BTreeMap<usize, usize>,
+    cards_discarded_by_turn: BTreeMap<usize, usize>,
+    wasted_mana_by_turn: BTreeMap<usize, u32>,
+    library_start_size: usize,
+    library_size_by_turn: BTreeMap<usize, usize>,
+    success_turn_cap: Option<usize>,
+    game_objects: Vec<CardSnapshot>,
+    /// Indices of the game objects in the library, from the bottom to the top
+    library: Vec<usize>,
+    sideboard: Vec<Card>,
+}
+
+fn index_of(game_objects: &[CardRef], card: &CardRef) -> Option<usize> {
+    game_objects
+        .iter()
+        .position(|game_object| same_card(game_object, card))
+}
+
+impl From<&Game> for GameSnapshot {
+    fn from(game: &Game) -> Self {
+        let game_objects = game
+            .game_objects
+            .iter()
+            .map(|card| {
+                let card = card.borrow();
+                CardSnapshot {
+                    attached_to: card
+                        .attached_to
+                        .as_ref()
+                        .and_then(|target| index_of(&game.game_objects, target)),
+                    card: card.clone(),
+                }
+            })
+            .collect();
+
+        Self {
+            turn: game.turn,
+            available_land_drops: game.available_land_drops,
+            life_total: game.life_total,
+            damage_dealt: game.damage_dealt,
+            opponent_library: game.opponent_library,
+            floating_mana: game.floating_mana.clone(),
+            is_first_player: game.is_first_player,
+            mulligan_count: game.mulligan_count,
+            turns_to_skip: game.turns_to_skip,
+            extra_turns: game.extra_turns,
+            storm: game.storm,
+            max_storm: game.max_storm,
+            creatures_died_this_turn: game
+                .creatures_died_this_turn
+                .iter()
+                .flat_map(|card| index_of(&game.game_objects, card))
+                .collect(),
+            output: game.output.lock().unwrap().clone(),
+            seed: game.seed,
+            rng: game.rng.clone(),
+            shuffle_seed: game.shuffle_seed,
+            shuffle_rng: game.shuffle_rng.clone(),
+            decisions: game.decisions.lock().unwrap().clone(),
+            replaying: game
+                .replaying
+                .as_ref()
+                .map(|decisions| decisions.lock().unwrap().clone()),
+            mana_available_by_turn: game.mana_available_by_turn.clone(),
+            combo_progress_by_turn: game.combo_progress_by_turn.clone(),
+            disruption: game.disruption.clone(),
+            kill_turn: game.kill_turn,
+            counter_next_spell: game.counter_next_spell,
+            drew_from_empty_library: game.drew_from_empty_library,
+            max_hand_size: game.max_hand_size,
+            mulligan_policy: game.mulligan_policy,
+            min_cards_left_in_library: game.min_cards_left_in_library,
+            cards_drawn_by_turn: game.cards_drawn_by_turn.clone(),
+            cards_discarded_by_turn: game.cards_discarded_by_turn.clone(),
+            wasted_mana_by_turn: game.wasted_mana_by_turn.clone(),
+            library_start_size: game.library_start_size,
+            library_size_by_turn: game.library_size_by_turn.clone(),
+            success_turn_cap: game.success_turn_cap,
+            game_objects,
+            library: game
+                .deck
+                .iter()
+                .flat_map(|card| index_of(&game.game_objects, card))
+                .collect(),
+            sideboard: game
+                .deck
+                .sideboard
+                .iter()
+                .map(|card| card.borrow().clone())
+                .collect(),
+        }
+    }
+}
+
+impl From<GameSnapshot> for Game {
+    fn from(snapshot: GameSnapshot) -> Self {
+        let game_objects: Vec<CardRef> = snapshot
+            .game_objects
+            .iter()
+            .map(|object| Rc::new(RefCell::new(object.card.clone())))
+            .collect();
+
+        for (card, object) in game_objects.iter().zip(snapshot.game_objects.iter()) {
+            card.borrow_mut().attached_to = object
+                .attached_to
+                .and_then(|index| game_objects.get(index))
+                .cloned();
+        }
+
+        let deck = Deck {
+            maindeck: snapshot
+                .library
+                .iter()
+                .flat_map(|index| game_objects.get(*index))
+                .cloned()
+                .collect::<VecDeque<_>>(),
+            sideboard: snapshot
+                .sideboard
+                .into_iter()
+                .map(|card| Rc::new(RefCell::new(card)))
+                .collect(),
+        };
+
+        let creatures_died_this_turn = snapshot
+            .creatures_died_this_turn
+            .iter()
+            .flat_map(|index| game_objects.get(*index))
+            .cloned()
+            .collect();
+
+        Self {
+            turn: snapshot.turn,
+            game_objects,
+            available_land_drops: snapshot.available_land_drops,
+            deck,
+            life_total: snapshot.life_total,
+            damage_dealt: snapshot.damage_dealt,
+            opponent_library: snapshot.opponent_library,
+            floating_mana: snapshot.floating_mana,
+            is_first_player: snapshot.is_first_player,
+            mulligan_count: snapshot.mulligan_count,
+            turns_to_skip: snapshot.turns_to_skip,
+            extra_turns: snapshot.extra_turns,
+            storm: snapshot.storm,
+            max_storm: snapshot.max_storm,
+            creatures_died_this_turn,
+            output: Rc::new(Mutex::new(snapshot.output)),
+            // The observer is attached by the caller, it can't be serialized
+            observer: None,
+            seed: snapshot.seed,
+            rng: snapshot.rng,
+            shuffle_seed: snapshot.shuffle_seed,
+            shuffle_rng: snapshot.shuffle_rng,
+            decisions: Rc::new(Mutex::new(snapshot.decisions)),
+            replaying: snapshot
+                .replaying
+                .map(|decisions| Rc::new(Mutex::new(decisions))),
+            mana_available_by_turn: snapshot.mana_available_by_turn,
+            combo_progress_by_turn: snapshot.combo_progress_by_turn,
+            disruption: snapshot.disruption,
+            kill_turn: snapshot.kill_turn,
+            counter_next_spell: snapshot.counter_next_spell,
+            drew_from_empty_library: snapshot.drew_from_empty_library,
+            max_hand_size: snapshot.max_hand_size,
+            mulligan_policy: snapshot.mulligan_policy,
+            min_cards_left_in_library: snapshot.min_cards_left_in_library,
+            cards_drawn_by_turn: snapshot.cards_drawn_by_turn,
+            cards_discarded_by_turn: snapshot.cards_discarded_by_turn,
+            wasted_mana_by_turn: snapshot.wasted_mana_by_turn,
+            library_start_size: snapshot.library_start_size,
+            library_size_by_turn: snapshot.library_size_by_turn,
+            success_turn_cap: snapshot.success_turn_cap,
+        }
+    }
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameSnapshot::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        GameSnapshot::deserialize(deserializer).map(Game::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Zone;
+    use crate::strategy::{from_enum, pattern_combo::PatternCombo, Strategy, STRATEGIES};
+
+    #[test]
+    fn it_round_trips_game_state_through_json() {
+        let strategy = PatternCombo {};
+        let mut game = Game::with_seed(&strategy.default_decklist(), 0).unwrap();
+        game.draw_n(7);
+
+        let rector = Card::new_with_zone("Academy Rector", Zone::Battlefield);
+        let pattern = Card::new_with_zone("Pattern of Rebirth", Zone::Battlefield);
+        pattern.borrow_mut().attached_to = Some(rector.clone());
+
+        game.game_objects.extend([rector, pattern]);
+        game.turn = 3;
+        game.life_total = 17;
+        game.floating_mana.insert(Mana::Green, 2);
+        game.drew_from_empty_library = true;
+        game.cards_drawn_by_turn.insert(2, 1);
+        game.cards_discarded_by_turn.insert(3, 2);
+        game.wasted_mana_by_turn.insert(2, 1);
+        game.library_size_by_turn.insert(2, 44);
+
+        let json = serde_json::to_string(&game).unwrap();
+        let restored: Game = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(3, restored.turn);
+        assert_eq!(17, restored.life_total);
+        assert_eq!(Some(&2), restored.floating_mana.get(&Mana::Green));
+        assert!(restored.drew_from_empty_library);
+        assert_eq!(game.cards_drawn_by_turn, restored.cards_drawn_by_turn);
+        assert_eq!(game.cards_discarded_by_turn, restored.cards_discarded_by_turn);
+        assert_eq!(game.wasted_mana_by_turn, restored.wasted_mana_by_turn);
+        assert_eq!(game.library_size_by_turn, restored.library_size_by_turn);
+        assert_eq!(game.game_objects.len(), restored.game_objects.len());
+        assert_eq!(game.deck.len(), restored.deck.len());
+        assert_eq!(game.deck.sideboard.len(), restored.deck.sideboard.len());
+
+        for (original, card) in game.game_objects.iter().zip(restored.game_objects.iter()) {
+            assert_eq!(original.borrow().name, card.borrow().name);
+            assert_eq!(original.borrow().zone, card.borrow().zone);
+        }
+
+        // The library and the attachments still refer to the same game objects
+        for (original, card) in game.deck.iter().zip(restored.deck.iter()) {
+            assert_eq!(index_of(&game.game_objects, original), index_of(&restored.game_objects, card));
+        }
+
+        let restored_pattern = restored.game_objects.last().unwrap();
+        let restored_rector = &restored.game_objects[restored.game_objects.len() - 2];
+        let attached_to = restored_pattern.borrow().attached_to.clone();
+        assert!(attached_to.is_some_and(|target| Rc::ptr_eq(&target, restored_rector)));
+    }
+
+    #[test]
+    fn it_finishes_a_restored_mid_game_snapshot_with_the_same_result() {
+        for deck_strategy in STRATEGIES.iter() {
+            for seed in 0..10 {
+                let mut strategy = from_enum(deck_strategy);
+                let mut game = Game::with_seed(&strategy.default_decklist(), seed).unwrap();
+
+                for _ in 0..2 {
+                    game.step_turn(&mut strategy);
+                }
+
+                let json = serde_json::to_string(&game).unwrap();
+                let mut restored: Game = serde_json::from_str(&json).unwrap();
+
+                let result = game.run(&mut strategy);
+                let restored_result = restored.run(&mut from_enum(deck_strategy));
+
+                assert_eq!(result, restored_result);
+            }
+        }
+    }
+}
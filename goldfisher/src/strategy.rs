@@ -1,12 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
-use std::rc::Rc;
 use std::str::FromStr;
 
-use crate::card::{CardRef, CardType};
-use crate::deck::Decklist;
-use crate::game::{Game, Outcome, GameStatus};
+use crate::card::{same_card, CardRef, CardType};
+use crate::deck::{Decklist, Format};
+use crate::game::{Game, GameConfig, Outcome, GameStatus};
 use crate::mana::{PaymentAndFloating};
 use crate::utils::*;
 
@@ -52,6 +51,16 @@ impl fmt::Display for DeckStrategy {
     }
 }
 
+impl DeckStrategy {
+    /// The format the default decklist of the strategy is built for
+    pub fn format(&self) -> Format {
+        match self {
+            DeckStrategy::PatternCombo | DeckStrategy::Aluren | DeckStrategy::FranticStorm => Format::Premodern,
+            DeckStrategy::TurboSmog => Format::Legacy,
+        }
+    }
+}
+
 pub const STRATEGIES: &[DeckStrategy] = &[
     DeckStrategy::PatternCombo,
     DeckStrategy::Aluren,
@@ -59,6 +68,53 @@ pub const STRATEGIES: &[DeckStrategy] = &[
     DeckStrategy::TurboSmog,
 ];
 
+/// Which castable cards a cast priority applies to
+#[derive(Clone)]
+pub enum CastFilter {
+    Named(String),
+    Matching(fn(&CardRef) -> bool),
+}
+
+impl CastFilter {
+    pub fn matches(&self, card: &CardRef) -> bool {
+        match self {
+            CastFilter::Named(name) => card.borrow().name == *name,
+            CastFilter::Matching(predicate) => predicate(card),
+        }
+    }
+}
+
+/// A single entry of the declarative cast order of a strategy
+#[derive(Clone)]
+pub struct CastPriority {
+    pub filter: CastFilter,
+    /// Only cast when the condition holds, always when `None`
+    pub condition: Option<fn(&Game) -> bool>,
+}
+
+impl CastPriority {
+    pub fn named(card_name: &str) -> Self {
+        Self {
+            filter: CastFilter::Named(card_name.to_owned()),
+            condition: None,
+        }
+    }
+
+    pub fn matching(predicate: fn(&CardRef) -> bool) -> Self {
+        Self {
+            filter: CastFilter::Matching(predicate),
+            condition: None,
+        }
+    }
+
+    pub fn when(self, condition: fn(&Game) -> bool) -> Self {
+        Self {
+            condition: Some(condition),
+            ..self
+        }
+    }
+}
+
 pub fn from_enum(strategy: &DeckStrategy) -> Box<dyn Strategy> {
     match strategy {
         DeckStrategy::PatternCombo => Box::new(pattern_combo::PatternCombo::new()),
@@ -70,7 +126,16 @@ pub fn from_enum(strategy: &DeckStrategy) -> Box<dyn Strategy> {
 
 pub trait Strategy {
     fn name(&self) -> String;
+    /// Summary of the deck the strategy plays and how it wins
+    fn description(&self) -> &'static str;
     fn default_decklist(&self) -> Decklist;
+
+    /// The game settings this strategy is measured with unless overridden, such as the
+    /// starting life total or the size of the opponent's library for the mill decks
+    fn default_config(&self) -> GameConfig {
+        GameConfig::default()
+    }
+
     fn cleanup(&mut self) {}
 
     fn game_status(&self, game: &Game) -> GameStatus {
@@ -96,6 +161,59 @@ pub trait Strategy {
     fn is_keepable_hand(&self, game: &Game, mulligan_count: usize) -> bool;
     fn take_game_action(&mut self, game: &mut Game) -> bool;
 
+    /// How far the combo has been assembled, from 0.0 for nothing to 1.0 for ready to win
+    fn combo_progress(&self, _game: &Game) -> f32 {
+        0.0
+    }
+
+    /// Whether to float the mana of all the untapped lands before the next game action,
+    /// like when going off with untappers. Checked by the game before each action.
+    fn should_float_mana(&self, _game: &Game) -> bool {
+        false
+    }
+
+    /// Chooses the creatures to attack with. By default only the unblockable creatures attack,
+    /// leaving the rest home for the combo.
+    fn attackers(&self, game: &Game) -> Vec<CardRef> {
+        game.battlefield()
+            .filter(|card| {
+                let card = card.borrow();
+                card.card_types.contains(&CardType::Creature)
+                    && card.is_unblockable
+                    && card.power > 0
+                    && !card.is_tapped
+                    && (!card.is_summoning_sick || card.is_haste)
+            })
+            .collect()
+    }
+
+    /// The order to cast spells in for the current game state, consumed by `cast_by_priority`.
+    /// Strategies that decide everything in `take_game_action` can leave this empty.
+    fn priorities(&self, _game: &Game) -> Vec<CastPriority> {
+        Vec::new()
+    }
+
+    /// Casts the first castable spell of the priorities, in the order they were declared
+    fn cast_by_priority(&self, game: &mut Game, priorities: &[CastPriority]) -> bool
+    where
+        Self: Sized,
+    {
+        let castable = game.find_castable();
+
+        for priority in priorities {
+            if priority.condition.is_some_and(|condition| !condition(game)) {
+                continue;
+            }
+
+            if let Some((card_ref, payment)) = castable.iter().find(|(card, _)| priority.filter.matches(card)) {
+                game.cast_spell(self, card_ref, payment, None);
+                return true;
+            }
+        }
+
+        false
+    }
+
     fn cast_named(
         &self,
         game: &mut Game,
@@ -159,22 +277,25 @@ pub trait Strategy {
         }
         false
     }
-    fn select_best(&self, game: &Game, cards: HashMap<String, Vec<CardRef>>) -> Option<CardRef>;
+    fn select_best(&self, game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef>;
 
+    /// Picks the three cards for Intuition. By default this grabs the copies of the most wanted card,
+    /// filling the rest of the pile with anything from the library.
     fn select_intuition(&self, game: &Game) -> Vec<CardRef> {
         let searchable = apply_search_filter(game, &None);
-        let mut selected = Vec::with_capacity(3);
 
-        while let Some(found) = self.select_best(game, group_by_name(searchable.clone())) {
-            selected.push(found);
+        match self.select_best(game, group_by_name(searchable)) {
+            Some(found) => {
+                let name = found.borrow().name.clone();
+                find_n_with_priority(game, 3, &[name.as_str()])
+            }
+            // Empty library
+            None => Vec::new(),
         }
-
-        selected
     }
 
     fn discard_to_hand_size(&self, game: &Game, hand_size: usize) -> Vec<CardRef> {
-        let mut cards_to_discard: Vec<_> =
-            game.game_objects.iter().filter(is_hand).cloned().collect();
+        let mut cards_to_discard: Vec<_> = game.hand().collect();
 
         if cards_to_discard.len() <= hand_size {
             return Vec::new();
@@ -182,12 +303,242 @@ pub trait Strategy {
 
         for _ in 0..hand_size {
             if let Some(best) = self.select_best(game, group_by_name(cards_to_discard.clone())) {
-                cards_to_discard.retain(|card| !Rc::ptr_eq(card, &best));
+                cards_to_discard.retain(|card| !same_card(card, &best));
+            }
+        }
+
+        cards_to_discard
+    }
+
+    /// Chooses the given amount of the least useful cards in hand to discard to an effect,
+    /// as opposed to `discard_to_hand_size` that orders the hand for the cleanup step.
+    fn choose_discard(&self, game: &Game, count: usize) -> Vec<CardRef> {
+        let mut cards_to_discard: Vec<_> = game.hand().collect();
+
+        while cards_to_discard.len() > count {
+            match self.select_best(game, group_by_name(cards_to_discard.clone())) {
+                Some(best) => cards_to_discard.retain(|card| !same_card(card, &best)),
+                None => break,
             }
         }
 
+        cards_to_discard.truncate(count);
         cards_to_discard
     }
+
+    /// Chooses the cards looked at with scry or surveil to keep on top of the library, the top card first.
+    /// The rest go to the bottom of the library or to the graveyard. By default only the best card is kept.
+    fn choose_scry(&self, game: &Game, cards: Vec<CardRef>) -> Vec<CardRef> {
+        self.select_best(game, group_by_name(cards)).into_iter().collect()
+    }
+}
+
+/// Wraps a strategy to always keep the opening seven, delegating everything else to it.
+/// Useful for comparing the raw consistency of a deck against its mulligan decisions.
+pub struct NoMulligan<S: Strategy + ?Sized>(pub Box<S>);
+
+impl<S: Strategy + ?Sized> Strategy for NoMulligan<S> {
+    fn name(&self) -> String {
+        format!("{} (no mulligans)", self.0.name())
+    }
+
+    fn description(&self) -> &'static str {
+        self.0.description()
+    }
+
+    fn default_decklist(&self) -> Decklist {
+        self.0.default_decklist()
+    }
+
+    fn default_config(&self) -> GameConfig {
+        self.0.default_config()
+    }
+
+    fn cleanup(&mut self) {
+        self.0.cleanup()
+    }
+
+    fn game_status(&self, game: &Game) -> GameStatus {
+        self.0.game_status(game)
+    }
+
+    fn is_keepable_hand(&self, _game: &Game, _mulligan_count: usize) -> bool {
+        true
+    }
+
+    fn take_game_action(&mut self, game: &mut Game) -> bool {
+        self.0.take_game_action(game)
+    }
+
+    fn combo_progress(&self, game: &Game) -> f32 {
+        self.0.combo_progress(game)
+    }
+
+    fn should_float_mana(&self, game: &Game) -> bool {
+        self.0.should_float_mana(game)
+    }
+
+    fn attackers(&self, game: &Game) -> Vec<CardRef> {
+        self.0.attackers(game)
+    }
+
+    fn priorities(&self, game: &Game) -> Vec<CastPriority> {
+        self.0.priorities(game)
+    }
+
+    fn play_land(&self, game: &mut Game) -> bool {
+        self.0.play_land(game)
+    }
+
+    fn select_best(&self, game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
+        self.0.select_best(game, cards)
+    }
+
+    fn select_intuition(&self, game: &Game) -> Vec<CardRef> {
+        self.0.select_intuition(game)
+    }
+
+    fn discard_to_hand_size(&self, game: &Game, hand_size: usize) -> Vec<CardRef> {
+        self.0.discard_to_hand_size(game, hand_size)
+    }
+
+    fn choose_discard(&self, game: &Game, count: usize) -> Vec<CardRef> {
+        self.0.choose_discard(game, count)
+    }
+
+    fn choose_scry(&self, game: &Game, cards: Vec<CardRef>) -> Vec<CardRef> {
+        self.0.choose_scry(game, cards)
+    }
+}
+
+/// A single instruction for the `Scripted` strategy
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptStep {
+    /// Plays the named land from hand, if possible
+    PlayLand(String),
+    /// Casts the named card, if possible
+    Cast(String),
+    /// Passes the turn
+    Pass,
+}
+
+/// Strategy that follows a script of game actions instead of making its own decisions,
+/// for testing specific lines deterministically. Steps that can't be taken are skipped.
+/// The opening hand is always kept and all the other decisions are left to the wrapped strategy.
+pub struct Scripted<S: Strategy + ?Sized> {
+    pub steps: VecDeque<ScriptStep>,
+    pub strategy: Box<S>,
+}
+
+impl<S: Strategy + ?Sized> Scripted<S> {
+    pub fn new(steps: Vec<ScriptStep>, strategy: Box<S>) -> Self {
+        Self {
+            steps: VecDeque::from(steps),
+            strategy,
+        }
+    }
+}
+
+impl<S: Strategy + ?Sized> Strategy for Scripted<S> {
+    fn name(&self) -> String {
+        format!("{} (scripted)", self.strategy.name())
+    }
+
+    fn description(&self) -> &'static str {
+        self.strategy.description()
+    }
+
+    fn default_decklist(&self) -> Decklist {
+        self.strategy.default_decklist()
+    }
+
+    fn default_config(&self) -> GameConfig {
+        self.strategy.default_config()
+    }
+
+    fn cleanup(&mut self) {
+        self.strategy.cleanup()
+    }
+
+    fn game_status(&self, game: &Game) -> GameStatus {
+        self.strategy.game_status(game)
+    }
+
+    fn is_keepable_hand(&self, _game: &Game, _mulligan_count: usize) -> bool {
+        true
+    }
+
+    fn take_game_action(&mut self, game: &mut Game) -> bool {
+        while let Some(step) = self.steps.pop_front() {
+            match step {
+                ScriptStep::PlayLand(name) => {
+                    let land = game
+                        .game_objects
+                        .iter()
+                        .find(|card| {
+                            is_hand(card) && is_named(card, &name) && is_card_type(card, &CardType::Land)
+                        })
+                        .cloned();
+
+                    if let Some(land) = land {
+                        if game.available_land_drops > 0 {
+                            game.play_land(land);
+                            return true;
+                        }
+                    }
+                }
+                ScriptStep::Cast(name) => {
+                    let castable = game.find_castable();
+                    if self.cast_named(game, castable, &name) {
+                        return true;
+                    }
+                }
+                ScriptStep::Pass => return false,
+            }
+        }
+
+        false
+    }
+
+    fn combo_progress(&self, game: &Game) -> f32 {
+        self.strategy.combo_progress(game)
+    }
+
+    fn should_float_mana(&self, game: &Game) -> bool {
+        self.strategy.should_float_mana(game)
+    }
+
+    fn attackers(&self, game: &Game) -> Vec<CardRef> {
+        self.strategy.attackers(game)
+    }
+
+    fn priorities(&self, game: &Game) -> Vec<CastPriority> {
+        self.strategy.priorities(game)
+    }
+
+    fn play_land(&self, game: &mut Game) -> bool {
+        self.strategy.play_land(game)
+    }
+
+    fn select_best(&self, game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
+        self.strategy.select_best(game, cards)
+    }
+
+    fn select_intuition(&self, game: &Game) -> Vec<CardRef> {
+        self.strategy.select_intuition(game)
+    }
+
+    fn discard_to_hand_size(&self, game: &Game, hand_size: usize) -> Vec<CardRef> {
+        self.strategy.discard_to_hand_size(game, hand_size)
+    }
+
+    fn choose_discard(&self, game: &Game, count: usize) -> Vec<CardRef> {
+        self.strategy.choose_discard(game, count)
+    }
+
+    fn choose_scry(&self, game: &Game, cards: Vec<CardRef>) -> Vec<CardRef> {
+        self.strategy.choose_scry(game, cards)
+    }
 }
 
 #[cfg(test)]
@@ -197,6 +548,7 @@ mod tests {
 
     use crate::card::{Card, Zone};
     use crate::deck::{Deck, Decklist};
+    use crate::game::MulliganPolicy;
     use crate::strategy::pattern_combo::{PatternCombo};
     
     use rand::seq::SliceRandom;
@@ -253,4 +605,164 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn it_always_keeps_the_first_hand_without_mulligans() {
+        for deck_strategy in STRATEGIES {
+            for seed in 0..10 {
+                let mut strategy: Box<dyn Strategy> = Box::new(NoMulligan(from_enum(deck_strategy)));
+                let decklist = strategy.default_decklist();
+
+                let mut game = Game::with_seed(&decklist, seed).unwrap();
+                let result = game.run(&mut strategy);
+
+                assert_eq!(0, result.mulligan_count);
+            }
+        }
+    }
+
+    #[test]
+    fn it_describes_every_strategy() {
+        for deck_strategy in STRATEGIES {
+            let strategy = from_enum(deck_strategy);
+            assert!(!strategy.description().is_empty(), "{deck_strategy} has no description");
+        }
+    }
+
+    #[test]
+    fn it_follows_the_script() {
+        let forest = Card::new_with_zone("Forest", Zone::Hand);
+        let llanowar_elves = Card::new_with_zone("Llanowar Elves", Zone::Hand);
+
+        let mut game = Game {
+            game_objects: vec![forest.clone(), llanowar_elves.clone()],
+            life_total: 20,
+            opponent_library: 60,
+            available_land_drops: 1,
+            turn: 1,
+            ..Default::default()
+        };
+
+        let mut strategy: Box<dyn Strategy> = Box::new(Scripted::new(
+            vec![
+                ScriptStep::Cast("Pattern of Rebirth".to_owned()),
+                ScriptStep::PlayLand("Forest".to_owned()),
+                ScriptStep::Cast("Llanowar Elves".to_owned()),
+                ScriptStep::Pass,
+            ],
+            Box::new(aluren::Aluren::new()),
+        ));
+
+        game.take_game_actions(&mut strategy);
+
+        assert_eq!(Zone::Battlefield, forest.borrow().zone);
+        assert_eq!(Zone::Battlefield, llanowar_elves.borrow().zone);
+        assert!(forest.borrow().is_tapped);
+    }
+
+    #[test]
+    fn it_casts_in_declared_priority_order() {
+        let hand = ["Defense Grid", "Llanowar Elves", "Birds of Paradise", "Xantid Swarm"]
+            .iter()
+            .map(|name| Card::new_with_zone(name, Zone::Hand))
+            .collect::<Vec<_>>();
+        let forests = (0..5)
+            .map(|_| Card::new_with_zone("Forest", Zone::Battlefield))
+            .collect::<Vec<_>>();
+
+        let mut game = Game {
+            game_objects: [hand, forests].concat(),
+            life_total: 20,
+            ..Default::default()
+        };
+
+        let priorities = vec![
+            CastPriority::named("Xantid Swarm").when(|game| game.turn > 1),
+            CastPriority::named("Birds of Paradise"),
+            CastPriority::matching(|card| is_card_type(&card, &CardType::Artifact)),
+            CastPriority::named("Llanowar Elves"),
+        ];
+
+        let strategy = PatternCombo {};
+        let mut cast_order = Vec::new();
+        while strategy.cast_by_priority(&mut game, &priorities) {
+            let last_cast = game
+                .battlefield()
+                .filter(|card| !is_card_type(&card, &CardType::Land))
+                .map(|card| card.borrow().name.clone())
+                .find(|name| !cast_order.contains(name))
+                .unwrap();
+            cast_order.push(last_cast);
+        }
+
+        assert_eq!(vec!["Birds of Paradise", "Defense Grid", "Llanowar Elves"], cast_order);
+    }
+
+    struct Aggro;
+
+    impl Strategy for Aggro {
+        fn name(&self) -> String {
+            "Aggro".to_owned()
+        }
+
+        fn description(&self) -> &'static str {
+            "Races the opponent on the draw"
+        }
+
+        fn default_decklist(&self) -> Decklist {
+            Decklist { maindeck: vec![], sideboard: vec![] }
+        }
+
+        fn default_config(&self) -> GameConfig {
+            // Racing an opponent on the draw, with a hand good enough to keep up
+            GameConfig {
+                is_first_player: false,
+                mulligan_policy: MulliganPolicy::Aggressive,
+                ..Default::default()
+            }
+        }
+
+        fn is_keepable_hand(&self, _game: &Game, _mulligan_count: usize) -> bool {
+            true
+        }
+
+        fn take_game_action(&mut self, _game: &mut Game) -> bool {
+            false
+        }
+
+        fn select_best(&self, _game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
+            cards.into_values().flatten().next()
+        }
+    }
+
+    #[test]
+    fn it_bakes_the_default_config_into_the_strategy() {
+        let pattern_combo = PatternCombo {};
+        let aggro = NoMulligan(Box::new(Aggro));
+
+        assert_eq!(GameConfig::default(), pattern_combo.default_config());
+        assert_ne!(pattern_combo.default_config(), aggro.default_config());
+        assert!(!aggro.default_config().is_first_player);
+        assert_eq!(MulliganPolicy::Aggressive, aggro.default_config().mulligan_policy);
+    }
+
+    #[test]
+    fn it_selects_three_cards_for_intuition_by_default() {
+        let strategy = PatternCombo {};
+        let game = Game::with_seed(&strategy.default_decklist(), 0).unwrap();
+
+        let wanted = strategy
+            .select_best(&game, group_by_name(apply_search_filter(&game, &None)))
+            .unwrap();
+        let found = strategy.select_intuition(&game);
+
+        assert_eq!(3, found.len());
+        assert_eq!(wanted.borrow().name, found[0].borrow().name);
+        for (index, card) in found.iter().enumerate() {
+            assert!(found[index + 1..].iter().all(|other| !same_card(card, other)));
+        }
+
+        // Choosing the pile leaves the cards in the library for Intuition to move
+        assert!(found.iter().all(|card| card.borrow().zone == Zone::Library));
+    }
 }
@@ -1,15 +1,15 @@
 use log::{warn};
-use std::collections::HashMap;
-use std::rc::Rc;
+use std::collections::BTreeMap;
 
 use crate::card::{CardRef, CardType, Zone};
 use crate::deck::Decklist;
-use crate::game::Game;
-use crate::strategy::Strategy;
+use crate::game::{Game, Phase};
+use crate::strategy::{CastPriority, Strategy};
 use crate::utils::*;
 
 const DEFAULT_DECKLIST: &str = include_str!("../../resources/aluren.txt");
 pub const NAME: &str = "Premodern - Aluren";
+pub const DESCRIPTION: &str = "Casts the creatures for free with Aluren, looping Cavern Harpy with Maggot Carrier, Soul Warden and Wirewood Savage to drain the opponent.";
 
 struct ComboStatus {
     mana_sources: usize,
@@ -25,6 +25,19 @@ struct ComboStatus {
 
 pub struct Aluren {}
 
+/// Whether Cavern Harpy, Soul Warden and a draw engine are all in hand or on the battlefield
+fn has_combo_pieces(game: &Game) -> bool {
+    let have = |names: &[&str]| {
+        game.game_objects.iter().any(|card| {
+            let card = card.borrow();
+            (card.zone == Zone::Battlefield || card.zone == Zone::Hand)
+                && names.contains(&card.name.as_str())
+        })
+    };
+
+    have(&["Cavern Harpy"]) && have(&["Soul Warden"]) && have(&["Wirewood Savage", "Raven Familiar"])
+}
+
 impl Aluren {
     pub fn new() -> Self {
         Self {}
@@ -89,6 +102,124 @@ impl Aluren {
             maggot_carriers,
         }
     }
+
+    /// Orders the cards in hand from the most to the least useful
+    fn ordered_hand(&self, game: &Game) -> Vec<CardRef> {
+        let mut ordered_hand = Vec::new();
+
+        let mut lands = Vec::with_capacity(7);
+        let mut alurens = Vec::with_capacity(7);
+        let mut cavern_harpies = Vec::with_capacity(7);
+        let mut draw_engines = Vec::with_capacity(7);
+        let mut tutors = Vec::with_capacity(7);
+        let mut wincons = Vec::with_capacity(7);
+        let mut mana_dorks = Vec::with_capacity(7);
+
+        let is_aluren_on_battlefield = game
+            .game_objects
+            .iter()
+            .any(|card| is_battlefield(&card) && card.borrow().name == "Aluren");
+
+        let mut other_cards = Vec::with_capacity(7);
+
+        let hand = game.game_objects.iter().filter(is_hand);
+
+        for card in hand {
+            let borrowed = card.borrow();
+            match borrowed.name.as_str() {
+                "Aluren" => alurens.push(card.clone()),
+                "Cavern Harpy" => cavern_harpies.push(card.clone()),
+                "Wirewood Savage" | "Raven Familiar" => draw_engines.push(card.clone()),
+                "Living Wish" | "Intuition" | "Worldly Tutor" => tutors.push(card.clone()),
+                "Maggot Carrier" | "Soul Warden" => wincons.push(card.clone()),
+                name => {
+                    if is_aluren_on_battlefield && name == "Unearth" {
+                        wincons.push(card.clone())
+                    } else if is_card_type(&card, &CardType::Land) {
+                        lands.push(card.clone())
+                    } else if is_card_type(&card, &CardType::Creature)
+                        && !borrowed.produced_mana.is_empty()
+                    {
+                        mana_dorks.push(card.clone())
+                    } else {
+                        other_cards.push(card.clone())
+                    }
+                }
+            };
+        }
+
+        lands.sort_by(sort_by_best_mana_to_play);
+
+        // First keep a balanced mix of lands and combo pieces
+        // Prefer lands that produce the most colors of mana (sorted to the end of the iter)
+        let mut lands_iter = lands.iter().rev();
+        if !is_aluren_on_battlefield {
+            for _ in 0..2 {
+                if let Some(card) = lands_iter.next() {
+                    ordered_hand.push(card);
+                }
+            }
+        }
+
+        let mut alurens_iter = alurens.iter();
+        if !is_aluren_on_battlefield {
+            for _ in 0..1 {
+                if let Some(card) = alurens_iter.next() {
+                    ordered_hand.push(card);
+                }
+            }
+        }
+
+        // Try to keep the wincons in hand
+        for card in wincons.iter() {
+            ordered_hand.push(card);
+        }
+
+        let mut cavern_harpies_iter = cavern_harpies.iter();
+        for _ in 0..1 {
+            if let Some(card) = cavern_harpies_iter.next() {
+                ordered_hand.push(card);
+            }
+        }
+
+        let mut draw_engines_iter = draw_engines.iter();
+        if !is_aluren_on_battlefield {
+            for _ in 0..1 {
+                if let Some(card) = draw_engines_iter.next() {
+                    ordered_hand.push(card);
+                }
+            }
+        }
+
+        // Take all tutors
+        for card in tutors.iter() {
+            ordered_hand.push(card);
+        }
+
+        // Take all mana dorks over extra lands for quick kills
+        for card in mana_dorks.iter() {
+            ordered_hand.push(card);
+        }
+
+        // Then take the rest of the cards, still in priority order
+        for card in lands_iter {
+            ordered_hand.push(card);
+        }
+        for card in draw_engines_iter {
+            ordered_hand.push(card);
+        }
+        for card in other_cards.iter() {
+            ordered_hand.push(card);
+        }
+        for card in cavern_harpies_iter {
+            ordered_hand.push(card);
+        }
+        for card in alurens_iter {
+            ordered_hand.push(card);
+        }
+
+        ordered_hand.into_iter().cloned().collect()
+    }
 }
 
 impl Strategy for Aluren {
@@ -96,6 +227,10 @@ impl Strategy for Aluren {
         NAME.to_owned()
     }
 
+    fn description(&self) -> &'static str {
+        DESCRIPTION
+    }
+
     fn default_decklist(&self) -> Decklist {
         DEFAULT_DECKLIST.parse::<Decklist>().unwrap()
     }
@@ -142,7 +277,7 @@ impl Strategy for Aluren {
         false
     }
 
-    fn select_best(&self, game: &Game, cards: HashMap<String, Vec<CardRef>>) -> Option<CardRef> {
+    fn select_best(&self, game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
         let status = self.combo_status(game, vec![Zone::Hand, Zone::Battlefield]);
         let battlefield = self.combo_status(game, vec![Zone::Battlefield]);
 
@@ -310,7 +445,7 @@ impl Strategy for Aluren {
                 vec![
                     "City of Brass",
                     "Gemstone Mine",
-                    "Llawnowar Wastes",
+                    "Llanowar Wastes",
                     "Forest",
                 ]
             } else if is_card_type(&&found, &CardType::Creature) {
@@ -350,124 +485,68 @@ impl Strategy for Aluren {
     }
 
     fn discard_to_hand_size(&self, game: &Game, hand_size: usize) -> Vec<CardRef> {
-        let mut ordered_hand = Vec::new();
-
-        let mut lands = Vec::with_capacity(7);
-        let mut alurens = Vec::with_capacity(7);
-        let mut cavern_harpies = Vec::with_capacity(7);
-        let mut draw_engines = Vec::with_capacity(7);
-        let mut tutors = Vec::with_capacity(7);
-        let mut wincons = Vec::with_capacity(7);
-        let mut mana_dorks = Vec::with_capacity(7);
-
-        let is_aluren_on_battlefield = game
-            .game_objects
-            .iter()
-            .any(|card| is_battlefield(&card) && card.borrow().name == "Aluren");
-
-        let mut other_cards = Vec::with_capacity(7);
-
-        let hand = game.game_objects.iter().filter(is_hand);
-
-        for card in hand {
-            let borrowed = card.borrow();
-            match borrowed.name.as_str() {
-                "Aluren" => alurens.push(card.clone()),
-                "Cavern Harpy" => cavern_harpies.push(card.clone()),
-                "Wirewood Savage" | "Raven Familiar" => draw_engines.push(card.clone()),
-                "Living Wish" | "Intuition" | "Worldly Tutor" => tutors.push(card.clone()),
-                "Maggot Carrier" | "Soul Warden" => wincons.push(card.clone()),
-                name => {
-                    if is_aluren_on_battlefield && name == "Unearth" {
-                        wincons.push(card.clone())
-                    } else if is_card_type(&card, &CardType::Land) {
-                        lands.push(card.clone())
-                    } else if is_card_type(&card, &CardType::Creature)
-                        && !borrowed.produced_mana.is_empty()
-                    {
-                        mana_dorks.push(card.clone())
-                    } else {
-                        other_cards.push(card.clone())
-                    }
-                }
-            };
-        }
-
-        lands.sort_by(sort_by_best_mana_to_play);
+        self.ordered_hand(game).into_iter().skip(hand_size).collect()
+    }
 
-        // First keep a balanced mix of lands and combo pieces
-        // Prefer lands that produce the most colors of mana (sorted to the end of the iter)
-        let mut lands_iter = lands.iter().rev();
-        if !is_aluren_on_battlefield {
-            for _ in 0..2 {
-                if let Some(card) = lands_iter.next() {
-                    ordered_hand.push(card);
-                }
-            }
-        }
+    fn combo_progress(&self, game: &Game) -> f32 {
+        let battlefield = self.combo_status(game, vec![Zone::Battlefield]);
+        let available = self.combo_status(game, vec![Zone::Hand, Zone::Battlefield]);
 
-        let mut alurens_iter = alurens.iter();
-        if !is_aluren_on_battlefield {
-            for _ in 0..1 {
-                if let Some(card) = alurens_iter.next() {
-                    ordered_hand.push(card);
-                }
-            }
-        }
+        // Aluren has to be on the battlefield, the rest of the pieces can be cast for free with it
+        let pieces = [
+            battlefield.alurens > 0,
+            available.cavern_harpies > 0,
+            available.maggot_carriers > 0,
+            available.soul_wardens > 0,
+        ];
 
-        // Try to keep the wincons in hand
-        for card in wincons.iter() {
-            ordered_hand.push(card);
-        }
+        pieces.iter().filter(|piece| **piece).count() as f32 / pieces.len() as f32
+    }
 
-        let mut cavern_harpies_iter = cavern_harpies.iter();
-        for _ in 0..1 {
-            if let Some(card) = cavern_harpies_iter.next() {
-                ordered_hand.push(card);
-            }
-        }
+    fn choose_discard(&self, game: &Game, count: usize) -> Vec<CardRef> {
+        let ordered_hand = self.ordered_hand(game);
+        let keep = ordered_hand.len().saturating_sub(count);
 
-        let mut draw_engines_iter = draw_engines.iter();
-        if !is_aluren_on_battlefield {
-            for _ in 0..1 {
-                if let Some(card) = draw_engines_iter.next() {
-                    ordered_hand.push(card);
-                }
-            }
-        }
+        ordered_hand.into_iter().skip(keep).collect()
+    }
 
-        // Take all tutors
-        for card in tutors.iter() {
-            ordered_hand.push(card);
+    fn priorities(&self, game: &Game) -> Vec<CastPriority> {
+        let alurens_on_battlefield = game.count_in_zone("Aluren", Zone::Battlefield);
+        let alurens_in_hand = game.count_in_zone("Aluren", Zone::Hand);
+
+        if alurens_on_battlefield > 0 {
+            return vec![
+                CastPriority::named("Soul Warden"),
+                CastPriority::named("Maggot Carrier"),
+                CastPriority::named("Wirewood Savage"),
+                CastPriority::named("Living Wish"),
+                CastPriority::named("Intuition").when(|game| !has_combo_pieces(game)),
+                // If there's still deck left to cast Raven Familiars and still pass the turn
+                CastPriority::named("Raven Familiar").when(|game| game.can_safely_draw(1)),
+            ];
         }
 
-        // Take all mana dorks over extra lands for quick kills
-        for card in mana_dorks.iter() {
-            ordered_hand.push(card);
+        let mut priorities = Vec::with_capacity(10);
+        if alurens_in_hand == 0 {
+            priorities.push(CastPriority::named("Aluren"));
         }
 
-        // Then take the rest of the cards, still in priority order
-        for card in lands_iter {
-            ordered_hand.push(card);
-        }
-        for card in draw_engines_iter {
-            ordered_hand.push(card);
-        }
-        for card in other_cards.iter() {
-            ordered_hand.push(card);
-        }
-        for card in cavern_harpies_iter {
-            ordered_hand.push(card);
-        }
-        for card in alurens_iter {
-            ordered_hand.push(card);
-        }
+        priorities.extend(
+            [
+                "Intuition",
+                "Living Wish",
+                "Impulse",
+                "Soul Warden",
+                "Maggot Carrier",
+                "Cloud of Faeries",
+                "Raven Familiar",
+                "Wirewood Savage",
+                "Cavern Harpy",
+            ]
+            .map(CastPriority::named),
+        );
 
-        ordered_hand
-            .iter()
-            .skip(hand_size)
-            .map(|card| Rc::clone(card))
-            .collect()
+        priorities
     }
 
     fn take_game_action(&mut self, game: &mut Game) -> bool {
@@ -482,23 +561,8 @@ impl Strategy for Aluren {
             let castable = game.find_castable();
 
             if hand.alurens == 0 {
-                let priority_order = [
-                    "Aluren",
-                    "Intuition",
-                    "Living Wish",
-                    "Impulse",
-                    "Soul Warden",
-                    "Maggot Carrier",
-                    "Cloud of Faeries",
-                    "Raven Familiar",
-                    "Wirewood Savage",
-                    "Cavern Harpy",
-                ];
-
-                for card_name in priority_order {
-                    if self.cast_named(game, castable.clone(), card_name) {
-                        return true;
-                    }
+                if self.cast_by_priority(game, &self.priorities(game)) {
+                    return true;
                 }
 
                 if self.cast_mana_producers(game) {
@@ -513,85 +577,36 @@ impl Strategy for Aluren {
                     return true;
                 }
 
-                let priority_order = [
-                    "Intuition",
-                    "Living Wish",
-                    "Impulse",
-                    "Soul Warden",
-                    "Maggot Carrier",
-                    "Cloud of Faeries",
-                    "Raven Familiar",
-                    "Wirewood Savage",
-                    "Cavern Harpy",
-                ];
-
-                for card_name in priority_order {
-                    if self.cast_named(game, castable.clone(), card_name) {
-                        return true;
-                    }
+                if self.cast_by_priority(game, &self.priorities(game)) {
+                    return true;
                 }
             }
         } else {
             let cavern_harpy_on_battlefield = game
                 .game_objects
                 .iter()
-                .find(|card| is_battlefield(card) && card.borrow().name == "Cavern Harpy");
+                .find(|card| is_battlefield(card) && card.borrow().name == "Cavern Harpy")
+                .cloned();
 
             if let Some(card) = cavern_harpy_on_battlefield {
                 // Return any Cavern Harpies sitting on the battlefield back to hand
-                game.log(format!(
-                    "[Turn {turn:002}][Action]: Returning \"Cavern Harpy\" back to hand.",
-                    turn = game.turn
-                ));
-                card.borrow_mut().zone = Zone::Hand;
+                game.log(Phase::Action, "Returning \"Cavern Harpy\" back to hand.".to_owned());
+                game.move_to_zone(&card, Zone::Hand);
                 game.take_damage(1);
                 return true;
             }
 
-            let mut castable = game.find_castable();
-
             // Cast any mana dorks for free
             if self.cast_mana_producers(game) {
                 return true;
             }
 
-            let have_cavern_harpy = game.game_objects.iter().any(|card| {
-                let card = card.borrow();
-                (card.zone == Zone::Battlefield || card.zone == Zone::Hand)
-                    && card.name == "Cavern Harpy"
-            });
-            let have_soul_warden = game.game_objects.iter().any(|card| {
-                let card = card.borrow();
-                (card.zone == Zone::Battlefield || card.zone == Zone::Hand)
-                    && card.name == "Soul Warden"
-            });
-            let have_draw_engine = game.game_objects.iter().any(|card| {
-                let card = card.borrow();
-                (card.zone == Zone::Battlefield || card.zone == Zone::Hand)
-                    && (card.name == "Wirewood Savage" || card.name == "Raven Familiar")
-            });
-
-            let mut priority_order = vec![
-                "Soul Warden",
-                "Maggot Carrier",
-                "Wirewood Savage",
-                "Living Wish",
-            ];
-
-            if !(have_cavern_harpy && have_soul_warden && have_draw_engine) {
-                priority_order.push("Intuition");
-            }
-            for card_name in priority_order {
-                if self.cast_named(game, castable.clone(), card_name) {
-                    return true;
-                }
-            }
-
-            // If there's still deck left to cast Raven Familiars and still pass the turn
-            if game.deck.len() > 1 && self.cast_named(game, castable.clone(), "Raven Familiar") {
+            if self.cast_by_priority(game, &self.priorities(game)) {
                 return true;
             }
 
+            let mut castable = game.find_castable();
+
             let land_count = game
                 .game_objects
                 .iter()
@@ -627,7 +642,7 @@ impl Strategy for Aluren {
                 }
             }
 
-            if game.deck.len() <= 1 && hand.maggot_carriers == 0 && battlefield.maggot_carriers == 0
+            if !game.can_safely_draw(1) && hand.maggot_carriers == 0 && battlefield.maggot_carriers == 0
             {
                 // Have to pass the turn, probably due to lack of mana :(
                 return false;
@@ -652,6 +667,11 @@ impl Strategy for Aluren {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::card::Card;
+    use crate::deck::Deck;
+    use crate::effect::Effect;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
 
     fn setup_game(cards_and_zones: Vec<(&str, Zone)>, strategy: &impl Strategy) -> Game {
         let game = Game::new(&strategy.default_decklist()).unwrap();
@@ -780,4 +800,102 @@ mod tests {
             ],
         )
     }
+
+    #[test]
+    fn it_keeps_floating_mana_when_looping_cloud_of_faeries() {
+        let aluren = Card::new_with_zone("Aluren", Zone::Battlefield);
+        let island_1 = Card::new_with_zone("Island", Zone::Battlefield);
+        let island_2 = Card::new_with_zone("Island", Zone::Battlefield);
+        let cloud_of_faeries = Card::new_with_zone("Cloud of Faeries", Zone::Hand);
+
+        let mut game = Game {
+            game_objects: vec![aluren, island_1, island_2, cloud_of_faeries.clone()],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        game.float_mana();
+        assert_eq!(2, game.floating_mana.values().sum::<u32>());
+
+        let castable = game.find_castable();
+        let (card, payment) = castable
+            .iter()
+            .find(|(card, _)| Rc::ptr_eq(card, &cloud_of_faeries))
+            .unwrap();
+        assert!(payment.payment.is_empty());
+
+        game.cast_spell(&Aluren {}, card, payment, None);
+
+        // The free Cloud of Faeries keeps the floating mana and untaps both lands
+        assert_eq!(2, game.floating_mana.values().sum::<u32>());
+        assert_eq!(
+            0,
+            game.game_objects
+                .iter()
+                .filter(|card| is_card_type(card, &CardType::Land) && is_tapped(card))
+                .count()
+        );
+        assert_eq!(Zone::Battlefield, cloud_of_faeries.borrow().zone);
+    }
+
+    #[test]
+    fn it_discards_least_useful_cards_to_frantic_search() {
+        let strategy = Aluren {};
+        let frantic_search = Card::new_with_zone("Frantic Search", Zone::Graveyard);
+        let hand = vec![
+            Card::new_with_zone("Aluren", Zone::Hand),
+            Card::new_with_zone("Cavern Harpy", Zone::Hand),
+            Card::new_with_zone("Forest", Zone::Hand),
+            Card::new_with_zone("Forest", Zone::Hand),
+        ];
+        let library = vec![
+            Card::new_with_zone("Aluren", Zone::Library),
+            Card::new_with_zone("Cavern Harpy", Zone::Library),
+        ];
+
+        let mut game = Game {
+            game_objects: [vec![frantic_search.clone()], hand, library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            turn: 2,
+            life_total: 20,
+            ..Default::default()
+        };
+
+        Effect::FranticSearch.resolve(&mut game, &frantic_search, &strategy);
+
+        // The redundant copies of the combo pieces are the least useful, the lands are kept
+        assert_eq!(1, game.count_in_zone("Aluren", Zone::Hand));
+        assert_eq!(1, game.count_in_zone("Cavern Harpy", Zone::Hand));
+        assert_eq!(2, game.count_in_zone("Forest", Zone::Hand));
+        assert_eq!(1, game.count_in_zone("Aluren", Zone::Graveyard));
+        assert_eq!(1, game.count_in_zone("Cavern Harpy", Zone::Graveyard));
+    }
+
+    #[test]
+    fn it_reports_combo_progress() {
+        let strategy = Aluren {};
+        let progress = |cards_and_zones: Vec<(&str, Zone)>| {
+            let game = Game {
+                game_objects: cards_and_zones
+                    .into_iter()
+                    .map(|(name, zone)| Card::new_with_zone(name, zone))
+                    .collect(),
+                ..Default::default()
+            };
+
+            strategy.combo_progress(&game)
+        };
+
+        assert_eq!(0.0, progress(vec![("Aluren", Zone::Hand)]));
+        assert_eq!(0.25, progress(vec![("Aluren", Zone::Battlefield)]));
+        assert_eq!(1.0, progress(vec![
+            ("Aluren", Zone::Battlefield),
+            ("Cavern Harpy", Zone::Hand),
+            ("Maggot Carrier", Zone::Hand),
+            ("Soul Warden", Zone::Battlefield),
+        ]));
+    }
 }
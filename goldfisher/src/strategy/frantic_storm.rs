@@ -1,12 +1,13 @@
-use std::collections::{HashMap};
+use std::collections::BTreeMap;
 
 use crate::card::{CardRef, CardType, Zone};
 use crate::deck::Decklist;
-use crate::game::Game;
+use crate::game::{Game, Phase};
 use crate::strategy::Strategy;
 use crate::utils::*;
 
 pub const NAME: &str = "Premodern - Frantic Storm";
+pub const DESCRIPTION: &str = "Untaps lands with free spells like Frantic Search to build up the storm count, milling the opponent out with Brain Freeze.";
 const DEFAULT_DECKLIST: &str = include_str!("../../resources/frantic-storm.txt");
 
 struct ComboStatus {
@@ -48,6 +49,7 @@ impl FranticStorm {
             .clone()
             .filter(|card| {
                 is_named(card, "Frantic Search")
+                    || is_named(card, "Gitaxian Probe")
                     || is_named(card, "Impulse")
                     || is_named(card, "Meditate")
                     || is_named(card, "Sleight of Hand")
@@ -81,6 +83,10 @@ impl Strategy for FranticStorm {
         NAME.to_owned()
     }
 
+    fn description(&self) -> &'static str {
+        DESCRIPTION
+    }
+
     fn default_decklist(&self) -> Decklist {
         DEFAULT_DECKLIST.parse::<Decklist>().unwrap()
     }
@@ -89,6 +95,11 @@ impl Strategy for FranticStorm {
         self.is_storming = false;
     }
 
+    fn should_float_mana(&self, _game: &Game) -> bool {
+        // We might as well float all mana when storming to make casting untappers easy
+        self.is_storming
+    }
+
     fn is_keepable_hand(&self, game: &Game, mulligan_count: usize) -> bool {
         if mulligan_count >= 3 {
             // Just keep any hand with 4 cards
@@ -115,7 +126,7 @@ impl Strategy for FranticStorm {
         return true;
     }
 
-    fn select_best(&self, game: &Game, cards: HashMap<String, Vec<CardRef>>) -> Option<CardRef> {
+    fn select_best(&self, game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
         let status = self.combo_status(game, vec![Zone::Hand, Zone::Battlefield]);
 
         if status.lands < 2 {
@@ -198,21 +209,19 @@ impl Strategy for FranticStorm {
                 && hand.cantrips >= 1
             {
                 self.is_storming = true;
-                game.log(format!(
-                    "[Turn {turn:002}][Strategy]: Trying to storm off!",
-                    turn = game.turn
-                ));
+                game.log(Phase::Strategy, "Trying to storm off!".to_owned());
+
+                // Let the game float the mana before casting the first spell
+                return true;
             }
         }
 
         if self.is_storming {
-            // We might as well float all mana now to make casting untappers easy
-            game.float_mana();
-
             // NOTE: `castable` needs to be always refreshed after floating mana, not optimal
             let mut castable = game.find_castable();
 
-            for card_name in ["Lotus Petal", "Cloud of Faeries", "Turnabout"] {
+            // Chain the free cantrips first to build up the storm count
+            for card_name in ["Gitaxian Probe", "Lotus Petal", "Cloud of Faeries", "Turnabout"] {
                 if self.cast_named(game, castable.clone(), card_name) {
                     return true;
                 }
@@ -285,3 +294,48 @@ impl Strategy for FranticStorm {
         return false;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+    use crate::deck::Deck;
+    use crate::strategy::Scripted;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn it_chains_free_cantrips_first_when_storming() {
+        let gitaxian_probe = Card::new_with_zone("Gitaxian Probe", Zone::Hand);
+        let lotus_petal = Card::new_with_zone("Lotus Petal", Zone::Hand);
+        let island = Card::new_with_zone("Island", Zone::Battlefield);
+        let library = vec![Card::new_with_zone("Island", Zone::Library)];
+
+        let mut game = Game {
+            game_objects: [vec![gitaxian_probe.clone(), lotus_petal.clone(), island], library.clone()].concat(),
+            deck: Deck {
+                maindeck: VecDeque::from(library),
+                sideboard: vec![],
+            },
+            life_total: 20,
+            opponent_library: 60,
+            ..Default::default()
+        };
+
+        let mut strategy = FranticStorm { is_storming: true };
+
+        assert!(strategy.take_game_action(&mut game));
+        assert_eq!(Zone::Graveyard, gitaxian_probe.borrow().zone);
+        assert_eq!(Zone::Hand, lotus_petal.borrow().zone);
+        assert_eq!(1, game.storm);
+    }
+
+    #[test]
+    fn it_stops_storming_on_cleanup_when_scripted() {
+        let game = Game::default();
+        let mut strategy = Scripted::new(vec![], Box::new(FranticStorm { is_storming: true }));
+
+        assert!(strategy.should_float_mana(&game));
+        strategy.cleanup();
+        assert!(!strategy.should_float_mana(&game));
+    }
+}
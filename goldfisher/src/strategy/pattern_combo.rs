@@ -1,26 +1,107 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
-use crate::card::{CardRef, CardType, Zone, ZONES};
+use crate::card::{CardRef, CardType, Zone};
 use crate::deck::Decklist;
-use crate::game::{Game, Outcome, GameStatus};
+use crate::game::{Game, Outcome, GameStatus, Phase};
 use crate::mana::Mana;
 use crate::strategy::Strategy;
 use crate::utils::*;
 
 const DEFAULT_DECKLIST: &str = include_str!("../../resources/pattern-combo-greedy.txt");
-const COMBO_PIECES: &[&str] = &[
-    "Body Snatcher",
-    "Iridescent Drake",
-    "Pattern of Rebirth",
-    "Karmic Guide",
-    "Volrath's Shapeshifter",
-    "Academy Rector",
-    "Goblin Bombardment",
-    "Akroma, Angel of Wrath",
-    "Caller of the Claw",
-];
+/// Requires at least `count` copies of any of the `cards` across the given `zones`
+pub struct ComboRequirement {
+    pub cards: &'static [&'static str],
+    pub zones: &'static [Zone],
+    pub count: usize,
+}
+
+impl ComboRequirement {
+    pub fn is_met(&self, game: &Game) -> bool {
+        let count = game
+            .game_objects
+            .iter()
+            .filter(|card| {
+                self.zones.iter().any(|zone| is_zone(card, zone))
+                    && self.cards.iter().any(|name| is_named(card, name))
+            })
+            .count();
+
+        count >= self.count
+    }
+}
+
+/// A way to kill, available when all requirements of any of the alternatives are met
+pub struct KillPattern {
+    pub alternatives: &'static [&'static [ComboRequirement]],
+}
+
+impl KillPattern {
+    pub fn is_available(&self, game: &Game) -> bool {
+        self.alternatives
+            .iter()
+            .any(|requirements| requirements.iter().all(|requirement| requirement.is_met(game)))
+    }
+}
+
+const LIBRARY: &[Zone] = &[Zone::Library];
+
+/// Goblin Bombardment with a reanimation target or two in library
+pub static SIMPLE_KILL: KillPattern = KillPattern {
+    alternatives: &[
+        &[
+            ComboRequirement { cards: &["Goblin Bombardment"], zones: &[Zone::Battlefield, Zone::Hand], count: 1 },
+            ComboRequirement { cards: &["Iridescent Drake"], zones: LIBRARY, count: 1 },
+            ComboRequirement { cards: &["Volrath's Shapeshifter", "Karmic Guide", "Body Snatcher"], zones: LIBRARY, count: 2 },
+        ],
+        &[
+            ComboRequirement { cards: &["Iridescent Drake", "Body Snatcher"], zones: LIBRARY, count: 1 },
+            ComboRequirement { cards: &["Volrath's Shapeshifter", "Karmic Guide"], zones: LIBRARY, count: 2 },
+        ],
+    ],
+};
+
+/// Academy Rector into Pattern of Rebirth, chaining into Goblin Bombardment
+pub static MAIN_KILL: KillPattern = KillPattern {
+    alternatives: &[&[
+        ComboRequirement { cards: &["Volrath's Shapeshifter", "Karmic Guide", "Body Snatcher"], zones: LIBRARY, count: 3 },
+        ComboRequirement { cards: &["Iridescent Drake", "Body Snatcher"], zones: LIBRARY, count: 1 },
+        ComboRequirement { cards: &["Academy Rector"], zones: LIBRARY, count: 1 },
+        ComboRequirement { cards: &["Pattern of Rebirth"], zones: LIBRARY, count: 1 },
+        ComboRequirement { cards: &["Goblin Bombardment"], zones: LIBRARY, count: 1 },
+    ]],
+};
+
+/// Academy Rector into Pattern of Rebirth, chaining into Akroma and Caller of the Claw
+pub static BACKUP_KILL: KillPattern = KillPattern {
+    alternatives: &[&[
+        ComboRequirement { cards: &["Volrath's Shapeshifter"], zones: LIBRARY, count: 2 },
+        ComboRequirement { cards: &["Karmic Guide", "Body Snatcher"], zones: LIBRARY, count: 2 },
+        ComboRequirement { cards: &["Academy Rector"], zones: LIBRARY, count: 1 },
+        ComboRequirement { cards: &["Pattern of Rebirth"], zones: LIBRARY, count: 1 },
+        ComboRequirement { cards: &["Akroma, Angel of Wrath"], zones: LIBRARY, count: 1 },
+        ComboRequirement { cards: &["Caller of the Claw"], zones: LIBRARY, count: 1 },
+    ]],
+};
+
+pub static KILL_PATTERNS: &[&KillPattern] = &[&SIMPLE_KILL, &MAIN_KILL, &BACKUP_KILL];
+
+/// Goblin Bombardment with Karmic Guide reanimating another Karmic Guide or Volrath's Shapeshifter
+/// can be looped for infinite damage, even without Pattern of Rebirth or Academy Rector
+pub static BOMBARDMENT_LOOP: KillPattern = KillPattern {
+    alternatives: &[&[
+        ComboRequirement { cards: &["Goblin Bombardment"], zones: &[Zone::Battlefield], count: 1 },
+        ComboRequirement { cards: &["Karmic Guide"], zones: &[Zone::Battlefield], count: 1 },
+        ComboRequirement {
+            cards: &["Karmic Guide", "Volrath's Shapeshifter"],
+            zones: &[Zone::Battlefield, Zone::Graveyard],
+            count: 2,
+        },
+    ]],
+};
+
 pub const NAME: &str = "Premodern - Pattern Combo";
+pub const DESCRIPTION: &str = "Puts Pattern of Rebirth on a creature with Academy Rector and sacrifices it to chain creatures like Karmic Guide into a Goblin Bombardment kill.";
 
 struct ComboStatus {
     mana_sources: usize,
@@ -133,7 +214,7 @@ impl PatternCombo {
     }
 
     fn sac_veteran_explorer(&self, game: &mut Game, veteran_explorer: CardRef) {
-        veteran_explorer.borrow_mut().zone = Zone::Graveyard;
+        game.sacrifice(&veteran_explorer, self);
 
         for _ in 0..2 {
             let basics = game
@@ -142,12 +223,11 @@ impl PatternCombo {
                 .filter(|card| is_library(card) && is_basic(card))
                 .cloned()
                 .collect();
-            if let Some(land) = self.select_best(game, group_by_name(basics)) {
-                land.borrow_mut().zone = Zone::Battlefield;
-                game.log(format!(
-                    "[Turn {turn:002}][Action]: Searched for \"{card_name}\" with \"Veteran Explorer\" dies trigger.",
-                    card_name = land.borrow().name,
-                    turn = game.turn
+            if let Some(land) = game.select_best(self, group_by_name(basics)) {
+                game.move_to_zone(&land, Zone::Battlefield);
+                game.log(Phase::Action, format!(
+                    "Searched for \"{card_name}\" with \"Veteran Explorer\" dies trigger.",
+                    card_name = land.borrow().name
                 ));
             }
         }
@@ -170,10 +250,9 @@ impl PatternCombo {
             .find(|card| is_battlefield(&card) && is_sac_outlet(&card))
             .cloned()
         {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Sacrificing \"Veteran Explorer\" with \"{card_name}\".",
-                card_name = sac_outlet.borrow().name,
-                turn = game.turn
+            game.log(Phase::Action, format!(
+                "Sacrificing \"Veteran Explorer\" with \"{card_name}\".",
+                card_name = sac_outlet.borrow().name
             ));
             self.sac_veteran_explorer(game, veteran_explorer);
             return true;
@@ -185,13 +264,12 @@ impl PatternCombo {
             .find(|card| is_graveyard(card) && is_named(card, "Cabal Therapy"))
             .cloned()
         {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Sacrificing \"Veteran Explorer\" with \"{card_name}\".",
-                card_name = cabal_therapy.borrow().name,
-                turn = game.turn
+            game.log(Phase::Action, format!(
+                "Sacrificing \"Veteran Explorer\" with \"{card_name}\".",
+                card_name = cabal_therapy.borrow().name
             ));
             self.sac_veteran_explorer(game, veteran_explorer);
-            cabal_therapy.borrow_mut().zone = Zone::Exile;
+            game.move_to_zone(&cabal_therapy, Zone::Exile);
             return true;
         }
 
@@ -203,10 +281,9 @@ impl PatternCombo {
             })
             .cloned()
         {
-            game.log(format!(
-                "[Turn {turn:002}][Action]: Sacrificing \"Veteran Explorer\" with \"{card_name}\".",
-                card_name = phyrexian_tower.borrow().name,
-                turn = game.turn
+            game.log(Phase::Action, format!(
+                "Sacrificing \"Veteran Explorer\" with \"{card_name}\".",
+                card_name = phyrexian_tower.borrow().name
             ));
             self.sac_veteran_explorer(game, veteran_explorer);
             phyrexian_tower.borrow_mut().is_tapped = true;
@@ -217,6 +294,45 @@ impl PatternCombo {
         false
     }
 
+    fn sac_reveillark(&self, game: &mut Game) -> bool {
+        let reveillark = match game
+            .game_objects
+            .iter()
+            .find(|card| is_battlefield(card) && is_named(card, "Reveillark"))
+            .cloned()
+        {
+            Some(card) => card,
+            None => return false,
+        };
+
+        // Only worth it when there is something for the dies trigger to return
+        let is_target_in_graveyard = game.game_objects.iter().any(|card| {
+            is_graveyard(&card)
+                && is_card_type(&card, &CardType::Creature)
+                && card.borrow().power <= 2
+        });
+
+        if !is_target_in_graveyard {
+            return false;
+        }
+
+        if let Some(sac_outlet) = game
+            .game_objects
+            .iter()
+            .find(|card| is_battlefield(&card) && is_sac_outlet(&card))
+            .cloned()
+        {
+            game.log(Phase::Action, format!(
+                "Sacrificing \"Reveillark\" with \"{card_name}\".",
+                card_name = sac_outlet.borrow().name
+            ));
+            game.sacrifice(&reveillark, self);
+            return true;
+        }
+
+        false
+    }
+
     fn cast_sac_outlet(&self, game: &mut Game) -> bool {
         let castable = game.find_castable();
 
@@ -340,6 +456,7 @@ impl PatternCombo {
 
 impl Strategy for PatternCombo {
     fn name(&self) -> String { NAME.to_owned() }
+    fn description(&self) -> &'static str { DESCRIPTION }
 
     fn default_decklist(&self) -> Decklist {
         DEFAULT_DECKLIST.parse::<Decklist>().unwrap()
@@ -347,81 +464,26 @@ impl Strategy for PatternCombo {
 
     fn game_status(&self, game: &Game) -> super::GameStatus {
         if game.life_total <= 0 {
-            game.log(format!(
-                "[Turn {turn:002}][Game]: Out of life points, lost the game!",
-                turn = game.turn
-            ));
+            game.log(Phase::Game, "Out of life points, lost the game!".to_owned());
             return GameStatus::Finished(Outcome::Lose);
         }
 
         let status = self.combo_status(game, false, true);
 
-        let mut by_zone: HashMap<(&str, Zone), usize> = HashMap::new();
-
-        for zone in ZONES {
-            for name in COMBO_PIECES {
-                let count = game
-                    .game_objects
-                    .iter()
-                    .filter(|card| is_zone(&card, zone) && is_named(&card, name))
-                    .count();
-
-                *by_zone.entry((name, zone.clone())).or_insert(0) = count
-            }
+        if BOMBARDMENT_LOOP.is_available(game) {
+            return GameStatus::Finished(Outcome::Win);
         }
 
         // Make sure required combo pieces are still in library
         // NOTE: This is not be 100% accurate, and is probably missing some lines that
         // involve just playing out the cards from hand.
-        let simple_kill_available = (*by_zone.get(&("Goblin Bombardment", Zone::Battlefield)).unwrap() > 0
-            || *by_zone.get(&("Goblin Bombardment", Zone::Hand)).unwrap() > 0)
-            && ((*by_zone.get(&("Iridescent Drake", Zone::Library)).unwrap() >= 1)
-                && (*by_zone.get(&("Volrath's Shapeshifter", Zone::Library)).unwrap()
-                    + *by_zone.get(&("Karmic Guide", Zone::Library)).unwrap()
-                    + *by_zone.get(&("Body Snatcher", Zone::Library)).unwrap()
-                    >= 2))
-            || ((*by_zone.get(&("Iridescent Drake", Zone::Library)).unwrap()
-                + *by_zone.get(&("Body Snatcher", Zone::Library)).unwrap()
-                >= 1)
-                && (*by_zone.get(&("Volrath's Shapeshifter", Zone::Library)).unwrap()
-                    + *by_zone.get(&("Karmic Guide", Zone::Library)).unwrap()
-                    >= 2));
-
-        let main_kill_available = (*by_zone.get(&("Volrath's Shapeshifter", Zone::Library)).unwrap()
-            + *by_zone.get(&("Karmic Guide", Zone::Library)).unwrap()
-            + *by_zone.get(&("Body Snatcher", Zone::Library)).unwrap()
-            >= 3)
-            && (*by_zone.get(&("Iridescent Drake", Zone::Library)).unwrap() >= 1
-                || *by_zone.get(&("Body Snatcher", Zone::Library)).unwrap() >= 1)
-            && *by_zone.get(&("Academy Rector", Zone::Library)).unwrap() >= 1
-            && *by_zone.get(&("Pattern of Rebirth", Zone::Library)).unwrap() >= 1
-            && *by_zone.get(&("Goblin Bombardment", Zone::Library)).unwrap() >= 1;
-
-        let backup_kill_available = *by_zone.get(&("Volrath's Shapeshifter", Zone::Library)).unwrap() >= 2
-            && (*by_zone.get(&("Karmic Guide", Zone::Library)).unwrap()
-                + *by_zone.get(&("Body Snatcher", Zone::Library)).unwrap()
-                >= 2)
-            && *by_zone.get(&("Academy Rector", Zone::Library)).unwrap() >= 1
-            && *by_zone.get(&("Pattern of Rebirth", Zone::Library)).unwrap() >= 1
-            && *by_zone.get(&("Akroma, Angel of Wrath", Zone::Library)).unwrap() >= 1
-            && *by_zone.get(&("Caller of the Claw", Zone::Library)).unwrap() >= 1;
-
-        // TODO: This doesn't seem to be accurate
-        if !simple_kill_available
-            && !main_kill_available
-            && !backup_kill_available
-        {
-            game.log(format!(
-                "[Turn {turn:002}][Game]: Can't combo anymore, lost the game!",
-                turn = game.turn
-            ));
+        if !KILL_PATTERNS.iter().any(|kill| kill.is_available(game)) {
+            game.log(Phase::Game, "Can't combo anymore, lost the game!".to_owned());
             return GameStatus::Finished(Outcome::Lose);
         }
 
         // Winning combinations:
 
-        // TODO: Treat Bombardment + Karmic Guide loop as a wincon (without Pattern/Rector)
-
         // TODO: Add a flag to toggle treating a non-summoning sick Carrion Feeded / Nantuko Husk attacker as wincon
 
         // 1) At least one sac outlet + Pattern of Rebirth on another
@@ -521,7 +583,7 @@ impl Strategy for PatternCombo {
     fn select_best(
         &self,
         game: &Game,
-        cards: HashMap<String, Vec<CardRef>>,
+        cards: BTreeMap<String, Vec<CardRef>>,
     ) -> Option<CardRef> {
         let status = self.combo_status(game, true, true);
 
@@ -712,9 +774,148 @@ impl Strategy for PatternCombo {
             || self.cast_pattern_of_rebirth(game)
             || self.cast_academy_rector(game)
             || self.cast_sac_outlet(game)
+            || self.sac_reveillark(game)
             || self.ramp_with_veteran_explorer(game)
             || self.cast_mana_dork(game)
             || self.cast_other_creature(game)
             || self.cast_others(game)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+
+    #[test]
+    fn it_wins_with_goblin_bombardment_and_karmic_guide_loop() {
+        let game = Game {
+            game_objects: vec![
+                Card::new_with_zone("Goblin Bombardment", Zone::Battlefield),
+                Card::new_with_zone("Karmic Guide", Zone::Battlefield),
+                Card::new_with_zone("Karmic Guide", Zone::Graveyard),
+            ],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            PatternCombo {}.game_status(&game),
+            GameStatus::Finished(Outcome::Win)
+        ));
+    }
+
+    #[test]
+    fn it_does_not_win_with_single_karmic_guide() {
+        let game = Game {
+            game_objects: vec![
+                Card::new_with_zone("Goblin Bombardment", Zone::Battlefield),
+                Card::new_with_zone("Karmic Guide", Zone::Battlefield),
+            ],
+            life_total: 20,
+            ..Default::default()
+        };
+
+        assert!(!matches!(
+            PatternCombo {}.game_status(&game),
+            GameStatus::Finished(Outcome::Win)
+        ));
+    }
+
+    fn game_with(cards_and_zones: &[(&str, Zone)]) -> Game {
+        Game {
+            game_objects: cards_and_zones
+                .iter()
+                .map(|(name, zone)| Card::new_with_zone(name, zone.clone()))
+                .collect(),
+            life_total: 20,
+            ..Default::default()
+        }
+    }
+
+    fn assert_only_available(expected: &KillPattern, cards_and_zones: &[(&str, Zone)]) {
+        let game = game_with(cards_and_zones);
+
+        for kill in KILL_PATTERNS.iter().copied() {
+            assert_eq!(std::ptr::eq(kill, expected), kill.is_available(&game));
+        }
+    }
+
+    #[test]
+    fn it_finds_simple_kill_available() {
+        assert_only_available(&SIMPLE_KILL, &[
+            ("Goblin Bombardment", Zone::Hand),
+            ("Iridescent Drake", Zone::Library),
+            ("Karmic Guide", Zone::Library),
+            ("Body Snatcher", Zone::Library),
+        ]);
+
+        assert_only_available(&SIMPLE_KILL, &[
+            ("Body Snatcher", Zone::Library),
+            ("Karmic Guide", Zone::Library),
+            ("Volrath's Shapeshifter", Zone::Library),
+        ]);
+    }
+
+    #[test]
+    fn it_finds_main_kill_available() {
+        assert_only_available(&MAIN_KILL, &[
+            ("Volrath's Shapeshifter", Zone::Library),
+            ("Body Snatcher", Zone::Library),
+            ("Body Snatcher", Zone::Library),
+            ("Academy Rector", Zone::Library),
+            ("Pattern of Rebirth", Zone::Library),
+            ("Goblin Bombardment", Zone::Library),
+        ]);
+    }
+
+    #[test]
+    fn it_finds_backup_kill_available() {
+        assert_only_available(&BACKUP_KILL, &[
+            ("Volrath's Shapeshifter", Zone::Library),
+            ("Volrath's Shapeshifter", Zone::Library),
+            ("Body Snatcher", Zone::Graveyard),
+            ("Karmic Guide", Zone::Library),
+            ("Karmic Guide", Zone::Library),
+            ("Academy Rector", Zone::Library),
+            ("Pattern of Rebirth", Zone::Library),
+            ("Akroma, Angel of Wrath", Zone::Library),
+            ("Caller of the Claw", Zone::Library),
+        ]);
+    }
+
+    #[test]
+    fn it_finds_no_kill_available_without_library() {
+        let game = game_with(&[
+            ("Goblin Bombardment", Zone::Battlefield),
+            ("Iridescent Drake", Zone::Graveyard),
+            ("Karmic Guide", Zone::Graveyard),
+            ("Body Snatcher", Zone::Graveyard),
+        ]);
+
+        assert!(!KILL_PATTERNS.iter().any(|kill| kill.is_available(&game)));
+    }
+
+    #[test]
+    fn it_sacrifices_reveillark_to_return_academy_rector() {
+        let mut game = game_with(&[
+            ("Carrion Feeder", Zone::Battlefield),
+            ("Reveillark", Zone::Battlefield),
+            ("Academy Rector", Zone::Graveyard),
+            ("Veteran Explorer", Zone::Graveyard),
+            ("Iridescent Drake", Zone::Library),
+            ("Karmic Guide", Zone::Library),
+            ("Volrath's Shapeshifter", Zone::Library),
+        ]);
+
+        let mut strategy: Box<dyn Strategy> = Box::new(PatternCombo {});
+
+        assert!(matches!(
+            game.take_game_actions(&mut strategy),
+            GameStatus::Finished(Outcome::Win)
+        ));
+        assert_eq!(1, game.count_in_zone("Reveillark", Zone::Graveyard));
+        assert_eq!(1, game.count_in_zone("Academy Rector", Zone::Battlefield));
+        assert_eq!(1, game.count_in_zone("Veteran Explorer", Zone::Battlefield));
+    }
+}
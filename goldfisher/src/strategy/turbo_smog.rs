@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::card::{CardRef, CardType, Zone};
 use crate::deck::Decklist;
@@ -7,6 +7,7 @@ use crate::strategy::Strategy;
 use crate::utils::*;
 
 pub const NAME: &str = "Legacy - Turbo Smog";
+pub const DESCRIPTION: &str = "Copies Chain of Smog over and over with Witherbloom Apprentice or Sedgemoor Witch on the battlefield to drain the opponent.";
 const DEFAULT_DECKLIST: &str = include_str!("../../resources/turbo-smog.txt");
 
 struct ComboStatus {
@@ -31,11 +32,7 @@ impl TurboSmog {
     }
 
     fn combo_status(&self, game: &Game, zones: Vec<Zone>) -> ComboStatus {
-        let chain_of_smogs = game
-            .game_objects
-            .iter()
-            .filter(|card| is_hand(card) && is_named(card, "Chain of Smog"))
-            .count();
+        let chain_of_smogs = game.count_in_zone("Chain of Smog", Zone::Hand);
 
         let cantrips = game
             .game_objects
@@ -98,6 +95,10 @@ impl Strategy for TurboSmog {
         NAME.to_owned()
     }
 
+    fn description(&self) -> &'static str {
+        DESCRIPTION
+    }
+
     fn default_decklist(&self) -> Decklist {
         DEFAULT_DECKLIST.parse::<Decklist>().unwrap()
     }
@@ -144,7 +145,7 @@ impl Strategy for TurboSmog {
         return true;
     }
 
-    fn select_best(&self, game: &Game, cards: HashMap<String, Vec<CardRef>>) -> Option<CardRef> {
+    fn select_best(&self, game: &Game, cards: BTreeMap<String, Vec<CardRef>>) -> Option<CardRef> {
         let status = self.combo_status(game, vec![Zone::Hand, Zone::Battlefield]);
 
         if status.lands < 2 {
@@ -0,0 +1,523 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::game::{GameResult, LossReason, Outcome};
+
+/// Aggregated statistics over a batch of simulated games.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    pub name: String,
+    pub simulated_games: usize,
+    pub wins_by_turn: BTreeMap<usize, usize>,
+    pub losses_by_turn: BTreeMap<usize, usize>,
+    pub draws_by_turn: BTreeMap<usize, usize>,
+    pub total_mulligans: usize,
+    /// Number of games by the amount of mulligans taken before keeping
+    pub mulligan_distribution: BTreeMap<usize, usize>,
+    /// Cards drawn after the opening hand minus the cards discarded, summed over the games
+    pub total_net_cards_drawn: i32,
+    /// Number of lost games by the likely reason for losing them
+    pub loss_reasons: BTreeMap<LossReason, usize>,
+    /// Number of games by the highest storm count reached during the game
+    pub storm_histogram: BTreeMap<usize, usize>,
+    /// Life total at the end of the won games, summed over the won games
+    pub total_final_life_on_wins: i32,
+    /// Damage dealt to the opponent, summed over the games
+    pub total_damage_dealt: i32,
+    /// Cards left in the library at the end of each turn, summed over the games that got that far
+    pub total_library_size_by_turn: BTreeMap<usize, usize>,
+    /// Number of games that got to the end of each turn
+    pub games_by_turn: BTreeMap<usize, usize>,
+}
+
+/// Window of turns to focus the summary on
+#[derive(Debug, Clone, Default)]
+pub struct TurnWindow {
+    pub min_turn: Option<usize>,
+    pub max_turn: Option<usize>,
+    /// Leave the games won outside of the window out of the summary, instead of counting them as losses
+    pub exclude_outside: bool,
+}
+
+impl TurnWindow {
+    pub fn contains(&self, turn: usize) -> bool {
+        self.min_turn.is_none_or(|min_turn| turn >= min_turn)
+            && self.max_turn.is_none_or(|max_turn| turn <= max_turn)
+    }
+}
+
+impl Summary {
+    /// Creates a summary of the game results under the given name
+    pub fn new(name: &str, results: &[GameResult]) -> Self {
+        Self::with_turn_window(name, results, &TurnWindow::default())
+    }
+
+    /// Creates a summary of the game results under the given name, only counting the wins
+    /// within the turn window as wins
+    pub fn with_turn_window(name: &str, results: &[GameResult], window: &TurnWindow) -> Self {
+        let mut summary = Self {
+            name: name.to_owned(),
+            ..Default::default()
+        };
+
+        for result in results {
+            if result.result == Outcome::Win && !window.contains(result.turn) {
+                if !window.exclude_outside {
+                    summary.add(&GameResult {
+                        result: Outcome::Lose,
+                        ..result.clone()
+                    });
+                }
+            } else {
+                summary.add(result);
+            }
+        }
+
+        summary
+    }
+
+    /// Adds a single game result to the summary
+    pub fn add(&mut self, GameResult { result, turn, mulligan_count, final_life, damage_dealt, max_storm, net_cards_drawn, loss_reason, library_size_by_turn, .. }: &GameResult) {
+        match result {
+            Outcome::Win => {
+                *self.wins_by_turn.entry(*turn).or_insert(0) += 1;
+                self.total_final_life_on_wins += final_life;
+            }
+            Outcome::Lose => *self.losses_by_turn.entry(*turn).or_insert(0) += 1,
+            Outcome::Draw => *self.draws_by_turn.entry(*turn).or_insert(0) += 1,
+        }
+
+        self.simulated_games += 1;
+        self.total_mulligans += mulligan_count;
+        self.total_net_cards_drawn += net_cards_drawn;
+        self.total_damage_dealt += damage_dealt;
+        *self.mulligan_distribution.entry(*mulligan_count).or_insert(0) += 1;
+        *self.storm_histogram.entry(*max_storm).or_insert(0) += 1;
+
+        if let (Outcome::Lose, Some(loss_reason)) = (result, loss_reason) {
+            *self.loss_reasons.entry(*loss_reason).or_insert(0) += 1;
+        }
+
+        for (turn, library_size) in library_size_by_turn.iter() {
+            *self.total_library_size_by_turn.entry(*turn).or_insert(0) += library_size;
+            *self.games_by_turn.entry(*turn).or_insert(0) += 1;
+        }
+    }
+
+    /// Adds the statistics of another summary to this one, keeping the name of this summary
+    pub fn merge(&mut self, other: &Summary) {
+        fn merge_counts<K: Ord + Copy>(into: &mut BTreeMap<K, usize>, from: &BTreeMap<K, usize>) {
+            for (key, count) in from.iter() {
+                *into.entry(*key).or_insert(0) += count;
+            }
+        }
+
+        self.simulated_games += other.simulated_games;
+        self.total_mulligans += other.total_mulligans;
+        self.total_net_cards_drawn += other.total_net_cards_drawn;
+        self.total_final_life_on_wins += other.total_final_life_on_wins;
+        self.total_damage_dealt += other.total_damage_dealt;
+        merge_counts(&mut self.wins_by_turn, &other.wins_by_turn);
+        merge_counts(&mut self.losses_by_turn, &other.losses_by_turn);
+        merge_counts(&mut self.draws_by_turn, &other.draws_by_turn);
+        merge_counts(&mut self.mulligan_distribution, &other.mulligan_distribution);
+        merge_counts(&mut self.loss_reasons, &other.loss_reasons);
+        merge_counts(&mut self.storm_histogram, &other.storm_histogram);
+        merge_counts(&mut self.total_library_size_by_turn, &other.total_library_size_by_turn);
+        merge_counts(&mut self.games_by_turn, &other.games_by_turn);
+    }
+
+    pub fn total_wins(&self) -> usize {
+        self.wins_by_turn.values().sum()
+    }
+
+    pub fn total_losses(&self) -> usize {
+        self.losses_by_turn.values().sum()
+    }
+
+    pub fn total_draws(&self) -> usize {
+        self.draws_by_turn.values().sum()
+    }
+
+    /// Average turn of the won games
+    pub fn average_turn(&self) -> f32 {
+        let turns = self
+            .wins_by_turn
+            .iter()
+            .map(|(turn, wins)| turn * wins)
+            .sum::<usize>();
+
+        turns as f32 / self.total_wins() as f32
+    }
+
+    pub fn average_mulligans(&self) -> f32 {
+        self.total_mulligans as f32 / self.simulated_games as f32
+    }
+
+    /// Earliest turn any of the games was won on
+    pub fn fastest_win(&self) -> Option<usize> {
+        self.wins_by_turn.keys().next().copied()
+    }
+
+    /// Turn by which the given percentage of the won games were won, using the nearest rank
+    pub fn win_turn_percentile(&self, percentile: f32) -> Option<usize> {
+        let rank = ((percentile / 100.0 * self.total_wins() as f32).ceil() as usize).max(1);
+
+        let mut cumulative = 0;
+        for (turn, wins) in self.wins_by_turn.iter() {
+            cumulative += wins;
+            if cumulative >= rank {
+                return Some(*turn);
+            }
+        }
+
+        None
+    }
+
+    /// Average cards drawn per game after the opening hand, net of discards
+    pub fn average_net_cards_drawn(&self) -> f32 {
+        self.total_net_cards_drawn as f32 / self.simulated_games as f32
+    }
+
+    /// Average life total at the end of the won games
+    pub fn average_final_life_on_wins(&self) -> f32 {
+        self.total_final_life_on_wins as f32 / self.total_wins() as f32
+    }
+
+    /// Average damage dealt to the opponent per game
+    pub fn average_damage_dealt(&self) -> f32 {
+        self.total_damage_dealt as f32 / self.simulated_games as f32
+    }
+
+    pub fn average_max_storm(&self) -> f32 {
+        let storm = self
+            .storm_histogram
+            .iter()
+            .map(|(storm, games)| storm * games)
+            .sum::<usize>();
+
+        storm as f32 / self.simulated_games as f32
+    }
+
+    /// Average cards left in the library at the end of the turn, over the games that got that far
+    pub fn average_library_size(&self, turn: usize) -> Option<f32> {
+        let games = *self.games_by_turn.get(&turn)?;
+        let library_size = self.total_library_size_by_turn.get(&turn).copied().unwrap_or(0);
+
+        Some(library_size as f32 / games as f32)
+    }
+
+    /// Percentage of games that never won
+    pub fn brick_rate(&self) -> f32 {
+        let losses = self.simulated_games - self.total_wins();
+        100.0 * losses as f32 / self.simulated_games as f32
+    }
+
+    /// Cumulative percentage of games won by the end of the given turn
+    pub fn cumulative_win_rate(&self, turn: usize) -> f32 {
+        let wins = self
+            .wins_by_turn
+            .range(..=turn)
+            .map(|(_, wins)| wins)
+            .sum::<usize>();
+
+        100.0 * wins as f32 / self.simulated_games as f32
+    }
+}
+
+/// Renders the summaries side by side as a comparison table, one line per row.
+pub fn render_comparison(summaries: &[Summary]) -> Vec<String> {
+    let label_width = 20;
+    let widths = summaries
+        .iter()
+        .map(|summary| summary.name.len().max(8))
+        .collect::<Vec<_>>();
+
+    let row = |label: &str, values: Vec<String>| {
+        let columns = values
+            .iter()
+            .zip(widths.iter())
+            .map(|(value, width)| format!("{value:>width$}"))
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        format!("{label:<label_width$} | {columns}")
+    };
+
+    let mut lines = vec![
+        row("", summaries.iter().map(|summary| summary.name.clone()).collect()),
+        row("Games", summaries.iter().map(|summary| summary.simulated_games.to_string()).collect()),
+        row("Average turn", summaries.iter().map(|summary| format!("{:.2}", summary.average_turn())).collect()),
+        row("Average mulligans", summaries.iter().map(|summary| format!("{:.2}", summary.average_mulligans())).collect()),
+        row("Fastest win", summaries.iter().map(|summary| format_turn(summary.fastest_win())).collect()),
+        row("Win turn p10", summaries.iter().map(|summary| format_turn(summary.win_turn_percentile(10.0))).collect()),
+        row("Win turn p50", summaries.iter().map(|summary| format_turn(summary.win_turn_percentile(50.0))).collect()),
+        row("Win turn p90", summaries.iter().map(|summary| format_turn(summary.win_turn_percentile(90.0))).collect()),
+        row("Brick rate", summaries.iter().map(|summary| format!("{:.1}%", summary.brick_rate())).collect()),
+        row("Average max storm", summaries.iter().map(|summary| format!("{:.2}", summary.average_max_storm())).collect()),
+        row("Average net draws", summaries.iter().map(|summary| format!("{:.2}", summary.average_net_cards_drawn())).collect()),
+    ];
+
+    let last_turn = summaries
+        .iter()
+        .flat_map(|summary| summary.wins_by_turn.keys().last())
+        .max();
+
+    if let Some(last_turn) = last_turn {
+        for turn in 1..=*last_turn {
+            lines.push(row(
+                &format!("Won by turn {turn:002}"),
+                summaries.iter().map(|summary| format!("{:.1}%", summary.cumulative_win_rate(turn))).collect(),
+            ));
+        }
+    }
+
+    lines
+}
+
+fn format_turn(turn: Option<usize>) -> String {
+    turn.map_or_else(|| "-".to_owned(), |turn| turn.to_string())
+}
+
+/// Renders the win percentage of each turn as a horizontal bar chart, one line per turn with wins.
+/// The bars are scaled so that the turn with the most wins fills the given width.
+pub fn render_chart(summary: &Summary, width: usize) -> Vec<String> {
+    let most_wins = summary.wins_by_turn.values().max().copied().unwrap_or(0);
+
+    summary
+        .wins_by_turn
+        .iter()
+        .map(|(turn, wins)| {
+            let win_percentage = 100.0 * *wins as f32 / summary.simulated_games as f32;
+            let bar = "#".repeat((width as f32 * *wins as f32 / most_wins as f32).round() as usize);
+
+            format!("Turn {turn:002} | {bar:<width$} | {win_percentage:.1}%")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{game_result, GameConfig};
+    use crate::simulate_many;
+    use crate::strategy::{from_enum, DeckStrategy};
+
+    fn simulate(deck_strategy: &DeckStrategy, games: usize) -> Summary {
+        let mut strategy = from_enum(deck_strategy);
+        let decklist = strategy.default_decklist();
+        let config = GameConfig {
+            seed: Some(0),
+            ..Default::default()
+        };
+
+        let results = simulate_many(&mut strategy, &decklist, &config, games, |_| {}).unwrap();
+
+        Summary::new(&strategy.name(), &results)
+    }
+
+    #[test]
+    fn it_summarizes_and_compares_multiple_configs() {
+        let summaries = vec![
+            simulate(&DeckStrategy::PatternCombo, 5),
+            simulate(&DeckStrategy::Aluren, 5),
+        ];
+
+        assert_eq!(2, summaries.len());
+        for summary in summaries.iter() {
+            assert_eq!(5, summary.simulated_games);
+            assert_eq!(
+                5,
+                summary.total_wins() + summary.total_losses() + summary.total_draws()
+            );
+        }
+
+        let lines = render_comparison(&summaries);
+        assert!(lines[0].contains(&summaries[0].name));
+        assert!(lines[0].contains(&summaries[1].name));
+        assert!(lines.iter().any(|line| line.starts_with("Brick rate")));
+    }
+
+    #[test]
+    fn it_merges_summaries_of_batches_into_the_full_summary() {
+        let mut strategy = from_enum(&DeckStrategy::Aluren);
+        let decklist = strategy.default_decklist();
+        let config = GameConfig {
+            seed: Some(0),
+            ..Default::default()
+        };
+
+        let results = simulate_many(&mut strategy, &decklist, &config, 10, |_| {}).unwrap();
+
+        let mut merged = Summary::new("Aluren", &[]);
+        for batch in results.chunks(3) {
+            merged.merge(&Summary::new("Batch", batch));
+        }
+
+        assert_eq!(Summary::new("Aluren", &results), merged);
+    }
+
+    #[test]
+    fn it_builds_storm_histogram() {
+        let summary = Summary::new("Storm", &[
+            GameResult { max_storm: 7, ..game_result(Outcome::Win, 3) },
+            GameResult { max_storm: 7, ..game_result(Outcome::Win, 4) },
+            GameResult { max_storm: 10, ..game_result(Outcome::Win, 4) },
+            game_result(Outcome::Lose, 12),
+        ]);
+
+        assert_eq!(
+            BTreeMap::from([(0, 1), (7, 2), (10, 1)]),
+            summary.storm_histogram
+        );
+        assert_eq!(6.0, summary.average_max_storm());
+    }
+
+    #[test]
+    fn it_builds_mulligan_distribution() {
+        let results = [0, 1, 0, 2, 0, 1]
+            .into_iter()
+            .map(|mulligan_count| GameResult {
+                mulligan_count,
+                ..game_result(Outcome::Win, 4)
+            })
+            .collect::<Vec<_>>();
+
+        let summary = Summary::new("Mulligans", &results);
+
+        assert_eq!(
+            BTreeMap::from([(0, 3), (1, 2), (2, 1)]),
+            summary.mulligan_distribution
+        );
+        assert_eq!(4, summary.total_mulligans);
+    }
+
+    #[test]
+    fn it_filters_wins_outside_of_turn_window() {
+        let results = [
+            game_result(Outcome::Win, 3),
+            game_result(Outcome::Win, 4),
+            game_result(Outcome::Win, 8),
+            game_result(Outcome::Win, 9),
+            game_result(Outcome::Lose, 12),
+        ];
+
+        let window = TurnWindow {
+            min_turn: Some(4),
+            max_turn: Some(8),
+            exclude_outside: false,
+        };
+
+        let summary = Summary::with_turn_window("Window", &results, &window);
+
+        assert_eq!(BTreeMap::from([(4, 1), (8, 1)]), summary.wins_by_turn);
+        assert_eq!(BTreeMap::from([(3, 1), (9, 1), (12, 1)]), summary.losses_by_turn);
+        assert_eq!(5, summary.simulated_games);
+        assert_eq!(6.0, summary.average_turn());
+
+        let summary = Summary::with_turn_window("Window", &results, &TurnWindow {
+            exclude_outside: true,
+            ..window
+        });
+
+        assert_eq!(BTreeMap::from([(4, 1), (8, 1)]), summary.wins_by_turn);
+        assert_eq!(BTreeMap::from([(12, 1)]), summary.losses_by_turn);
+        assert_eq!(3, summary.simulated_games);
+    }
+
+    #[test]
+    fn it_renders_a_bar_per_winning_turn() {
+        let summary = Summary::new("Chart", &[
+            game_result(Outcome::Win, 3),
+            game_result(Outcome::Win, 4),
+            game_result(Outcome::Win, 4),
+            game_result(Outcome::Win, 6),
+            game_result(Outcome::Lose, 12),
+        ]);
+
+        let chart = render_chart(&summary, 50);
+
+        assert_eq!(3, chart.len());
+        assert_eq!(format!("Turn 04 | {} | 40.0%", "#".repeat(50)), chart[1]);
+        assert_eq!(format!("Turn 06 | {}{} | 20.0%", "#".repeat(25), " ".repeat(25)), chart[2]);
+    }
+
+    #[test]
+    fn it_counts_loss_reasons() {
+        let results = [Some(LossReason::ManaScrew), None, Some(LossReason::ManaScrew), Some(LossReason::DeckedOut)]
+            .into_iter()
+            .map(|loss_reason| GameResult {
+                loss_reason,
+                ..game_result(if loss_reason.is_some() { Outcome::Lose } else { Outcome::Win }, 4)
+            })
+            .collect::<Vec<_>>();
+
+        let summary = Summary::new("Losses", &results);
+
+        assert_eq!(
+            BTreeMap::from([(LossReason::ManaScrew, 2), (LossReason::DeckedOut, 1)]),
+            summary.loss_reasons
+        );
+    }
+
+    #[test]
+    fn it_computes_win_turn_percentiles() {
+        let results = [(3, 2), (4, 3), (5, 4), (7, 1)]
+            .into_iter()
+            .flat_map(|(turn, wins)| (0..wins).map(move |_| game_result(Outcome::Win, turn)))
+            .chain([game_result(Outcome::Lose, 12)])
+            .collect::<Vec<_>>();
+
+        let summary = Summary::new("Percentiles", &results);
+
+        assert_eq!(Some(3), summary.fastest_win());
+        assert_eq!(Some(3), summary.win_turn_percentile(10.0));
+        assert_eq!(Some(4), summary.win_turn_percentile(50.0));
+        assert_eq!(Some(5), summary.win_turn_percentile(90.0));
+        assert_eq!(Some(7), summary.win_turn_percentile(100.0));
+
+        assert_eq!(None, Summary::new("Empty", &[]).win_turn_percentile(50.0));
+    }
+
+    #[test]
+    fn it_counts_draws_separately_from_losses() {
+        let summary = Summary::new("Draws", &[
+            game_result(Outcome::Win, 4),
+            game_result(Outcome::Lose, 8),
+            GameResult {
+                loss_reason: Some(LossReason::ManaScrew),
+                ..game_result(Outcome::Draw, 50)
+            },
+        ]);
+
+        assert_eq!(BTreeMap::from([(50, 1)]), summary.draws_by_turn);
+        assert_eq!(BTreeMap::from([(8, 1)]), summary.losses_by_turn);
+        assert_eq!(1, summary.total_draws());
+        assert_eq!(1, summary.total_losses());
+        assert!(summary.loss_reasons.is_empty());
+
+        let mut merged = Summary::new("Draws", &[]);
+        merged.merge(&summary);
+        assert_eq!(summary, merged);
+    }
+
+    #[test]
+    fn it_averages_library_size_by_turn_over_the_games_that_got_that_far() {
+        let results = [
+            BTreeMap::from([(1, 52), (2, 50)]),
+            BTreeMap::from([(1, 50)]),
+        ]
+        .into_iter()
+        .map(|library_size_by_turn| GameResult {
+            library_size_by_turn,
+            ..game_result(Outcome::Win, 2)
+        })
+        .collect::<Vec<_>>();
+
+        let summary = Summary::new("Library", &results);
+
+        assert_eq!(Some(51.0), summary.average_library_size(1));
+        assert_eq!(Some(50.0), summary.average_library_size(2));
+        assert_eq!(None, summary.average_library_size(3));
+    }
+}
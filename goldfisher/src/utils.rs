@@ -1,9 +1,9 @@
 use crate::{
-    card::{CardRef, CardType, SearchFilter, SubType, Zone},
+    card::{same_card, CardRef, CardType, SearchFilter, SubType, Zone},
     game::Game,
     mana::Mana,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub fn is_battlefield(card: &&CardRef) -> bool {
     card.borrow().zone == Zone::Battlefield
@@ -51,10 +51,7 @@ pub fn is_mana_source(card: &&CardRef) -> bool {
 }
 
 pub fn is_single_use_mana(card: &&CardRef) -> bool {
-    match card.borrow().remaining_uses {
-        Some(uses) => uses == 1,
-        None => false,
-    }
+    card.borrow().remaining_uses() == Some(1)
 }
 
 pub fn is_card_type(card: &&CardRef, card_type: &CardType) -> bool {
@@ -70,7 +67,12 @@ pub fn is_cost_reducer(card: &&CardRef) -> bool {
 }
 
 pub fn is_color(card: &&CardRef, color: Mana) -> bool {
-    match card.borrow().cost.get(&color) {
+    let card = card.borrow();
+    if card.color_indicator == Some(color) {
+        return true;
+    }
+
+    match card.cost.get(&color) {
         Some(cost) => *cost > 0,
         None => false,
     }
@@ -92,47 +94,41 @@ pub fn sort_by_best_mana_to_play(a: &CardRef, b: &CardRef) -> std::cmp::Ordering
     let a = a.borrow();
     let b = b.borrow();
 
-    if a.produced_mana.len() == b.produced_mana.len() {
-        // Play the mana source with most uses
-        return a
-            .remaining_uses
-            .unwrap_or(usize::MAX)
-            .partial_cmp(&b.remaining_uses.unwrap_or(usize::MAX))
-            .unwrap();
-    }
-
     a.produced_mana
         .len()
-        .partial_cmp(&b.produced_mana.len())
-        .unwrap()
+        .cmp(&b.produced_mana.len())
+        // Play the mana source with most uses
+        .then_with(|| {
+            a.remaining_uses()
+                .unwrap_or(u32::MAX)
+                .cmp(&b.remaining_uses().unwrap_or(u32::MAX))
+        })
+        .then_with(|| a.name.cmp(&b.name))
 }
 
 pub fn sort_by_best_mana_to_use(a: &CardRef, b: &CardRef) -> std::cmp::Ordering {
     let a = a.borrow();
     let b = b.borrow();
 
-    if a.produced_mana.len() == b.produced_mana.len() {
-        // Try to save the mana sources with least uses
-        return b
-            .remaining_uses
-            .unwrap_or(usize::MAX)
-            .partial_cmp(&a.remaining_uses.unwrap_or(usize::MAX))
-            .unwrap();
-    }
-
     a.produced_mana
         .len()
-        .partial_cmp(&b.produced_mana.len())
-        .unwrap()
+        .cmp(&b.produced_mana.len())
+        // Try to save the mana sources with least uses
+        .then_with(|| {
+            b.remaining_uses()
+                .unwrap_or(u32::MAX)
+                .cmp(&a.remaining_uses().unwrap_or(u32::MAX))
+        })
+        .then_with(|| a.name.cmp(&b.name))
 }
 
 pub fn sort_by_cmc(a: &CardRef, b: &CardRef) -> std::cmp::Ordering {
-    a.borrow()
-        .cost
-        .values()
-        .sum::<i32>()
-        .partial_cmp(&b.borrow().cost.values().sum())
-        .unwrap()
+    let a = a.borrow();
+    let b = b.borrow();
+
+    a.mana_value()
+        .cmp(&b.mana_value())
+        .then_with(|| a.name.cmp(&b.name))
 }
 
 pub fn is_empty_mana_pool(floating: HashMap<Mana, u32>) -> bool {
@@ -146,85 +142,60 @@ pub fn count_in_hand(game: &Game, names: &[&str]) -> usize {
         .count()
 }
 
+/// Whether the card matches the search filter, regardless of the zone it is in
+pub fn matches_search_filter(card: &&CardRef, search_filter: &Option<SearchFilter>) -> bool {
+    match search_filter {
+        Some(SearchFilter::Creature) => is_card_type(card, &CardType::Creature),
+        Some(SearchFilter::EnchantmentArtifact) => {
+            is_card_type(card, &CardType::Enchantment) || is_card_type(card, &CardType::Artifact)
+        }
+        Some(SearchFilter::Wish(card_types)) => card_types
+            .iter()
+            .any(|card_type| is_card_type(card, card_type)),
+        Some(SearchFilter::BlueInstant) => {
+            is_card_type(card, &CardType::Instant) && is_color(card, Mana::Blue)
+        }
+        Some(SearchFilter::GreenCreature) => {
+            is_card_type(card, &CardType::Creature) && is_color(card, Mana::Green)
+        }
+        Some(SearchFilter::Blue) => is_color(card, Mana::Blue),
+        Some(SearchFilter::Land(land_types)) => {
+            is_card_type(card, &CardType::Land)
+                && land_types
+                    .iter()
+                    .any(|land_type| is_sub_type(card, &SubType::Land(land_type.clone())))
+        }
+        None => true,
+    }
+}
+
 // TODO: Make these composable, and use them like `vec![Library, Creature]` etc
 pub fn apply_search_filter(game: &Game, search_filter: &Option<SearchFilter>) -> Vec<CardRef> {
     match search_filter {
-        Some(SearchFilter::Creature) => game
-            .game_objects
-            .iter()
-            .filter(|card| is_library(card) && is_card_type(card, &CardType::Creature))
-            .cloned()
-            .collect(),
-        Some(SearchFilter::EnchantmentArtifact) => game
-            .game_objects
-            .iter()
-            .filter(|card| {
-                is_library(card)
-                    && (is_card_type(card, &CardType::Enchantment)
-                        || is_card_type(card, &CardType::Artifact))
-            })
-            .cloned()
-            .collect(),
-        Some(SearchFilter::Wish(card_types)) => game
+        Some(SearchFilter::Wish(_)) => game
             .deck
             .sideboard
             .iter()
-            .filter(|card| {
-                card_types
-                    .iter()
-                    .any(|card_type| is_card_type(card, card_type))
-            })
-            .cloned()
-            .collect(),
-        Some(SearchFilter::BlueInstant) => game
-            .game_objects
-            .iter()
-            .filter(|card| {
-                is_library(card)
-                    && is_card_type(card, &CardType::Instant)
-                    && is_color(card, Mana::Blue)
-            })
-            .cloned()
-            .collect(),
-        Some(SearchFilter::GreenCreature) => game
-            .game_objects
-            .iter()
-            .filter(|card| {
-                is_library(card)
-                    && is_card_type(card, &CardType::Creature)
-                    && is_color(card, Mana::Green)
-            })
+            .filter(|card| matches_search_filter(card, search_filter))
             .cloned()
             .collect(),
         Some(SearchFilter::Blue) => game
             .game_objects
             .iter()
-            .filter(|card| is_color(card, Mana::Blue))
-            .cloned()
-            .collect(),
-        Some(SearchFilter::Land(land_types)) => game
-            .game_objects
-            .iter()
-            .filter(|card| {
-                is_library(card)
-                    && is_card_type(card, &CardType::Land)
-                    && land_types
-                        .iter()
-                        .any(|land_type| is_sub_type(card, &SubType::Land(land_type.clone())))
-            })
+            .filter(|card| matches_search_filter(card, search_filter))
             .cloned()
             .collect(),
-        None => game
+        _ => game
             .game_objects
             .iter()
-            .filter(is_library)
+            .filter(|card| is_library(card) && matches_search_filter(card, search_filter))
             .cloned()
             .collect(),
     }
 }
 
-pub fn group_by_name(game_objects: Vec<CardRef>) -> HashMap<String, Vec<CardRef>> {
-    let mut cards = HashMap::new();
+pub fn group_by_name(game_objects: Vec<CardRef>) -> BTreeMap<String, Vec<CardRef>> {
+    let mut cards = BTreeMap::new();
 
     for game_object in game_objects.iter() {
         let name = &game_object.borrow().name;
@@ -237,38 +208,187 @@ pub fn group_by_name(game_objects: Vec<CardRef>) -> HashMap<String, Vec<CardRef>
     cards
 }
 
-pub fn find_named(cards: &HashMap<String, Vec<CardRef>>, name: &str) -> Option<CardRef> {
+pub fn find_named(cards: &BTreeMap<String, Vec<CardRef>>, name: &str) -> Option<CardRef> {
     cards.get(name).and_then(|copies| copies.first()).cloned()
 }
 
+/// Finds `count` cards from the library, first by the names of the priority list in order and then anything.
+/// When the library has fewer cards than that, all of them are returned. Each card is found only once,
+/// the found cards stay in the library until the caller moves them to their destination.
 pub fn find_n_with_priority(game: &Game, count: usize, priority_list: &[&str]) -> Vec<CardRef> {
-    let mut found = Vec::with_capacity(count);
+    let mut found: Vec<CardRef> = Vec::with_capacity(count);
 
     for card_name in priority_list {
+        if found.len() >= count {
+            break;
+        }
+
         let to_find = count - found.len();
 
-        for card in game
+        let copies = game
             .game_objects
             .iter()
             .filter(|card| is_library(card) && card.borrow().name == *card_name)
+            .filter(|card| !found.iter().any(|other| same_card(card, other)))
             .take(to_find)
-        {
-            // Place these outside the game temporarily
-            card.borrow_mut().zone = Zone::Outside;
-            found.push(card.clone());
-        }
+            .cloned()
+            .collect::<Vec<_>>();
+
+        found.extend(copies);
     }
 
     if found.len() < count {
         // Find anything from library to meet the desired count the best we can
-        found.extend(
-            game.game_objects
-                .iter()
-                .filter(is_library)
-                .take(count - found.len())
-                .cloned(),
-        );
+        let rest = game
+            .game_objects
+            .iter()
+            .filter(|card| is_library(card) && !found.iter().any(|other| same_card(card, other)))
+            .take(count - found.len())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        found.extend(rest);
     }
 
     found
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Card, LandType};
+    use std::rc::Rc;
+
+    fn card(name: &str) -> CardRef {
+        Card::new_with_zone(name, Zone::Hand)
+    }
+
+    #[test]
+    fn it_finds_as_many_cards_as_the_library_has() {
+        let aluren = Card::new_with_zone("Aluren", Zone::Library);
+        let forest = Card::new_with_zone("Forest", Zone::Library);
+        let game = Game {
+            game_objects: vec![aluren.clone(), forest.clone(), card("Impulse")],
+            ..Default::default()
+        };
+
+        let found = find_n_with_priority(&game, 3, &["Aluren", "Aluren"]);
+
+        assert_eq!(2, found.len());
+        assert!(Rc::ptr_eq(&aluren, &found[0]));
+        assert!(Rc::ptr_eq(&forest, &found[1]));
+
+        // Finding the cards doesn't move them out of the library
+        assert_eq!(Zone::Library, aluren.borrow().zone);
+        assert_eq!(Zone::Library, forest.borrow().zone);
+    }
+
+    #[test]
+    fn it_matches_dryad_arbor_as_a_forest_and_a_green_creature() {
+        let dryad_arbor = card("Dryad Arbor");
+
+        assert!(is_color(&&dryad_arbor, Mana::Green));
+        assert!(matches_search_filter(&&dryad_arbor, &Some(SearchFilter::GreenCreature)));
+        assert!(matches_search_filter(&&dryad_arbor, &Some(SearchFilter::Creature)));
+        assert!(matches_search_filter(&&dryad_arbor, &Some(SearchFilter::Land(vec![LandType::Forest]))));
+        assert!(!matches_search_filter(&&dryad_arbor, &Some(SearchFilter::Land(vec![LandType::Island]))));
+    }
+
+    #[test]
+    fn it_finds_mana_dorks() {
+        assert!(is_mana_dork(&&card("Llanowar Elves")));
+        assert!(is_mana_dork(&&card("Birds of Paradise")));
+        assert!(is_mana_dork(&&card("Wall of Roots")));
+        assert!(!is_mana_dork(&&card("Forest")));
+        assert!(!is_mana_dork(&&card("Lotus Petal")));
+        assert!(!is_mana_dork(&&card("Academy Rector")));
+    }
+
+    #[test]
+    fn it_finds_sac_outlets() {
+        assert!(is_sac_outlet(&&card("Carrion Feeder")));
+        assert!(is_sac_outlet(&&card("Goblin Bombardment")));
+        assert!(is_sac_outlet(&&card("Altar of Dementia")));
+        assert!(!is_sac_outlet(&&card("Pattern of Rebirth")));
+        assert!(!is_sac_outlet(&&card("Llanowar Elves")));
+    }
+
+    #[test]
+    fn it_finds_basic_lands() {
+        for name in ["Plains", "Island", "Swamp", "Mountain", "Forest"] {
+            assert!(is_basic(&&card(name)));
+        }
+
+        assert!(!is_basic(&&card("Bayou")));
+        assert!(!is_basic(&&card("Dryad Arbor")));
+        assert!(!is_basic(&&card("City of Brass")));
+    }
+
+    #[test]
+    fn it_finds_cost_reducers() {
+        assert!(is_cost_reducer(&&card("Helm of Awakening")));
+        assert!(is_cost_reducer(&&card("Sapphire Medallion")));
+        assert!(is_cost_reducer(&&card("Aluren")));
+        assert!(!is_cost_reducer(&&card("Lotus Petal")));
+    }
+
+    #[test]
+    fn it_finds_single_use_mana() {
+        assert!(is_single_use_mana(&&card("Lotus Petal")));
+        assert!(is_single_use_mana(&&card("Elvish Spirit Guide")));
+        assert!(is_single_use_mana(&&card("Dark Ritual")));
+        assert!(!is_single_use_mana(&&card("Wall of Roots")));
+        assert!(!is_single_use_mana(&&card("Gemstone Mine")));
+        assert!(!is_single_use_mana(&&card("Forest")));
+
+        let wall_of_roots = card("Wall of Roots");
+        wall_of_roots.borrow_mut().charges = Some(1);
+        assert!(is_single_use_mana(&&wall_of_roots));
+    }
+
+    #[test]
+    fn it_finds_colors_and_names() {
+        assert!(is_color(&&card("Cloud of Faeries"), Mana::Blue));
+        assert!(!is_color(&&card("Cloud of Faeries"), Mana::Green));
+        assert!(is_color(&&card("Vindicate"), Mana::White));
+        assert!(is_color(&&card("Vindicate"), Mana::Black));
+
+        // Colors come from the cost, so lands have no color
+        assert!(!is_color(&&card("Forest"), Mana::Green));
+        assert!(!is_color(&&card("Lotus Petal"), Mana::Green));
+
+        assert!(is_named(&&card("Forest"), "Forest"));
+        assert!(!is_named(&&card("Forest"), "Island"));
+    }
+
+    fn sorted_names(names: &[&str], compare: fn(&CardRef, &CardRef) -> std::cmp::Ordering) -> Vec<String> {
+        let mut cards = names.iter().map(|name| card(name)).collect::<Vec<_>>();
+        cards.sort_by(compare);
+        cards.iter().map(|card| card.borrow().name.clone()).collect()
+    }
+
+    #[test]
+    fn it_sorts_equal_mana_value_cards_by_name() {
+        // All of these cost one mana, except for "Mesmeric Fiend"
+        assert_eq!(
+            vec!["Carrion Feeder", "Fyndhorn Elves", "Llanowar Elves", "Mesmeric Fiend"],
+            sorted_names(&["Mesmeric Fiend", "Llanowar Elves", "Carrion Feeder", "Fyndhorn Elves"], sort_by_cmc)
+        );
+        assert_eq!(
+            vec!["Carrion Feeder", "Fyndhorn Elves", "Llanowar Elves", "Mesmeric Fiend"],
+            sorted_names(&["Fyndhorn Elves", "Carrion Feeder", "Mesmeric Fiend", "Llanowar Elves"], sort_by_cmc)
+        );
+    }
+
+    #[test]
+    fn it_sorts_equal_mana_sources_by_name() {
+        assert_eq!(
+            vec!["Forest", "Fyndhorn Elves", "Llanowar Elves", "City of Brass"],
+            sorted_names(&["City of Brass", "Llanowar Elves", "Forest", "Fyndhorn Elves"], sort_by_best_mana_to_play)
+        );
+        assert_eq!(
+            vec!["Forest", "Fyndhorn Elves", "Llanowar Elves", "City of Brass"],
+            sorted_names(&["Llanowar Elves", "Fyndhorn Elves", "City of Brass", "Forest"], sort_by_best_mana_to_use)
+        );
+    }
+}
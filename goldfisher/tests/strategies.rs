@@ -0,0 +1,64 @@
+//! Runs each of the built-in strategies end-to-end with their default decklists, guarding against
+//! changes that quietly break a strategy. The games are seeded for stability, but the bands are
+//! kept loose so that changes to how a strategy plays don't need the tests updated along with them.
+
+use goldfisher::game::{GameConfig, Outcome};
+use goldfisher::strategy::{from_enum, DeckStrategy, STRATEGIES};
+
+const GAMES: usize = 1000;
+const TURN: usize = 6;
+
+/// Share of the games won by the given turn
+fn win_rate_by_turn(deck_strategy: &DeckStrategy, turn: usize) -> f64 {
+    let mut strategy = from_enum(deck_strategy);
+    let decklist = strategy.default_decklist();
+    let config = GameConfig {
+        seed: Some(0),
+        ..strategy.default_config()
+    };
+
+    let results = goldfisher::simulate_many(&mut strategy, &decklist, &config, GAMES, |_| {}).unwrap();
+    let wins = results
+        .iter()
+        .filter(|result| result.result == Outcome::Win && result.turn <= turn)
+        .count();
+
+    wins as f64 / GAMES as f64
+}
+
+fn assert_win_rate_between(deck_strategy: &DeckStrategy, min: f64, max: f64) {
+    let win_rate = win_rate_by_turn(deck_strategy, TURN);
+
+    assert!(
+        (min..=max).contains(&win_rate),
+        "{deck_strategy:?} won {:.1}% of the games by turn {TURN}, expected {:.0}% to {:.0}%",
+        100.0 * win_rate,
+        100.0 * min,
+        100.0 * max,
+    );
+}
+
+#[test]
+fn it_covers_every_strategy() {
+    assert_eq!(4, STRATEGIES.len(), "add a win rate test for the new strategy");
+}
+
+#[test]
+fn pattern_combo_wins_within_its_band() {
+    assert_win_rate_between(&DeckStrategy::PatternCombo, 0.70, 0.90);
+}
+
+#[test]
+fn aluren_wins_within_its_band() {
+    assert_win_rate_between(&DeckStrategy::Aluren, 0.55, 0.75);
+}
+
+#[test]
+fn frantic_storm_wins_within_its_band() {
+    assert_win_rate_between(&DeckStrategy::FranticStorm, 0.35, 0.55);
+}
+
+#[test]
+fn turbo_smog_wins_within_its_band() {
+    assert_win_rate_between(&DeckStrategy::TurboSmog, 0.90, 1.0);
+}